@@ -0,0 +1,8 @@
+#!/bin/sh
+# Claude Code hook that asks a running liment to refresh usage right away.
+# Never fails or blocks the hook, even if liment isn't running.
+
+SOCKET="${XDG_CACHE_HOME:-$HOME/.cache}/liment/liment.sock"
+
+echo refresh | nc -U -w 1 "$SOCKET" >/dev/null 2>&1
+exit 0
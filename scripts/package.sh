@@ -1,7 +1,7 @@
 #!/bin/bash
 set -euo pipefail
 
-VERSION="${1:?Usage: $0 <version>}"
+VERSION="${1:?Usage: [SIGN_IDENTITY=...] $0 <version>}"
 TARGET="aarch64-apple-darwin"
 APP="target/liment.app"
 
@@ -42,6 +42,14 @@ cat > "$APP/Contents/Info.plist" << EOF
 </plist>
 EOF
 
+# Sign with hardened runtime and the sandbox entitlements for notarized distribution
+# (Homebrew cask, App Store). Without an identity the app ad-hoc signs itself on first launch.
+if [[ -n "${SIGN_IDENTITY:-}" ]]; then
+  codesign --force --timestamp --options runtime \
+    --entitlements resources/liment.entitlements \
+    --sign "$SIGN_IDENTITY" "$APP"
+fi
+
 cd target
 zip -r liment.app.zip liment.app
 echo "Packaged: target/liment.app.zip"
@@ -0,0 +1,80 @@
+use color_eyre::eyre::{Context as _, Result};
+use lettre::{
+  Message, SmtpTransport, Transport as _, message::header::ContentType, transport::smtp::authentication::Credentials,
+};
+use security_framework::passwords::get_generic_password;
+use serde::{Deserialize, Serialize};
+
+use crate::alerts::Alert;
+
+/// Keychain service holding the SMTP password, with the username as the account.
+const KEYCHAIN_SERVICE: &str = "liment-smtp";
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+  /// Implicit TLS, usually on port 465.
+  Tls,
+  /// Plain connection upgraded with STARTTLS, usually on port 587.
+  #[default]
+  Starttls,
+  /// Unencrypted, only for local relays.
+  None,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmailSettings {
+  /// SMTP server host.
+  pub server: String,
+
+  /// SMTP port. Defaults to the standard port for `security`.
+  pub port: Option<u16>,
+
+  /// Connection security: "tls", "starttls" or "none".
+  #[serde(default)]
+  pub security: SmtpSecurity,
+
+  /// SMTP username. The password is read from the keychain (service "liment-smtp", account = username).
+  pub username: Option<String>,
+
+  /// Sender address (e.g. "liment <alerts@example.com>").
+  pub from: String,
+
+  /// Recipient address.
+  pub to: String,
+}
+
+/// Sends an alert email. Performs blocking network I/O.
+pub fn send(settings: &EmailSettings, alert: &Alert) -> Result<()> {
+  log::debug!("Sending alert email to {}: {}", settings.to, alert.title);
+
+  let message = Message::builder()
+    .from(settings.from.parse().context("Invalid sender address")?)
+    .to(settings.to.parse().context("Invalid recipient address")?)
+    .subject(&alert.title)
+    .header(ContentType::TEXT_PLAIN)
+    .body(alert.body.clone())
+    .context("Failed to build alert email")?;
+
+  let mut transport = match settings.security {
+    SmtpSecurity::Tls => SmtpTransport::relay(&settings.server).context("Failed to set up SMTP relay")?,
+    SmtpSecurity::Starttls => SmtpTransport::starttls_relay(&settings.server).context("Failed to set up SMTP relay")?,
+    SmtpSecurity::None => SmtpTransport::builder_dangerous(&settings.server),
+  };
+
+  if let Some(port) = settings.port {
+    transport = transport.port(port);
+  }
+
+  if let Some(username) = &settings.username {
+    let password = get_generic_password(KEYCHAIN_SERVICE, username)
+      .context("Failed to find the SMTP password in keychain")
+      .and_then(|p| String::from_utf8(p).context("SMTP password is not valid UTF-8"))?;
+
+    transport = transport.credentials(Credentials::new(username.clone(), password));
+  }
+
+  transport.build().send(&message).context("Failed to send alert email")?;
+
+  return Ok(());
+}
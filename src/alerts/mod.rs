@@ -0,0 +1,178 @@
+pub mod email;
+pub mod ntfy;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  alerts::{email::EmailSettings, ntfy::NtfySettings},
+  providers::UsageWindow,
+  utils::notification,
+};
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertChannel {
+  Native,
+  Ntfy,
+  Email,
+}
+
+const ALL_CHANNELS: &[AlertChannel] = &[AlertChannel::Native, AlertChannel::Ntfy, AlertChannel::Email];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertLevel {
+  /// Informational, e.g. the monitored account changed.
+  Info,
+  /// Something to keep an eye on, e.g. usage ahead of the weekly goal pace.
+  Warning,
+  /// A limit was hit, e.g. a window at 100% or spend over budget.
+  Critical,
+}
+
+/// Channels to deliver each alert level to. Unset levels go to every configured channel.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct AlertRoutes {
+  pub info: Option<Vec<AlertChannel>>,
+  pub warning: Option<Vec<AlertChannel>>,
+  pub critical: Option<Vec<AlertChannel>>,
+}
+
+impl AlertRoutes {
+  fn channels(&self, level: AlertLevel) -> &[AlertChannel] {
+    let route = match level {
+      AlertLevel::Info => &self.info,
+      AlertLevel::Warning => &self.warning,
+      AlertLevel::Critical => &self.critical,
+    };
+
+    return route.as_deref().unwrap_or(ALL_CHANNELS);
+  }
+}
+
+/// Native notification sounds: "none", "default" or the name of a system sound (e.g. "Glass").
+/// A window's sound takes precedence over its alert level's. Unset means silent.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct AlertSounds {
+  pub info: Option<String>,
+  pub warning: Option<String>,
+  pub critical: Option<String>,
+
+  /// Sounds for alerts about a specific window, by tray label or title (e.g. "5h").
+  #[serde(default)]
+  pub windows: HashMap<String, String>,
+}
+
+impl AlertSounds {
+  /// Returns the sound to play for the alert, or `None` to stay silent.
+  fn sound(&self, alert: &Alert) -> Option<&str> {
+    let by_level = match alert.level {
+      AlertLevel::Info => &self.info,
+      AlertLevel::Warning => &self.warning,
+      AlertLevel::Critical => &self.critical,
+    };
+
+    let sound = alert.window.as_ref().and_then(|w| self.windows.get(w)).or(by_level.as_ref())?;
+
+    return if sound == "none" { None } else { Some(sound.as_str()) };
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct AlertSettings {
+  /// ntfy topic to push alerts to, in addition to native notifications.
+  pub ntfy: Option<NtfySettings>,
+
+  /// SMTP server to email alerts through, for networks where push services are blocked.
+  pub email: Option<EmailSettings>,
+
+  /// Which channels each alert level goes to.
+  #[serde(default)]
+  pub routes: AlertRoutes,
+
+  /// Sounds of native notifications, per level or window.
+  #[serde(default)]
+  pub sounds: AlertSounds,
+
+  /// Utilization levels below 100% to warn at, once per window period (e.g. `[75, 90]`).
+  #[serde(default)]
+  pub thresholds: Vec<f64>,
+
+  /// Windows to never alert about, by tray label or title (e.g. "Review 7d").
+  #[serde(default)]
+  pub muted_windows: Vec<String>,
+}
+
+impl AlertSettings {
+  /// Whether alerts about the window are turned off.
+  pub fn is_muted(&self, window: &UsageWindow) -> bool {
+    return self.muted_windows.iter().any(|muted| {
+      return *muted == window.title || window.short_title.as_ref() == Some(muted);
+    });
+  }
+}
+
+/// A usage alert, delivered to the channels routed for its level.
+#[derive(Debug, Clone)]
+pub struct Alert {
+  pub level: AlertLevel,
+  pub title: String,
+  pub body: String,
+
+  /// Tray label or title of the window the alert is about, if any.
+  pub window: Option<String>,
+}
+
+impl Alert {
+  pub fn new(level: AlertLevel, title: impl Into<String>, body: impl Into<String>) -> Self {
+    return Self {
+      level,
+      title: title.into(),
+      body: body.into(),
+      window: None,
+    };
+  }
+
+  pub fn with_window(self, window: impl Into<String>) -> Self {
+    return Self { window: Some(window.into()), ..self };
+  }
+}
+
+/// Sends an alert to the channels routed for its level. Remote channels are delivered on a background thread.
+pub fn send(settings: &AlertSettings, alert: Alert) {
+  let channels = settings.routes.channels(alert.level);
+  deliver(settings, alert, channels);
+}
+
+/// Sends a test alert to every configured channel regardless of routes, to check the setup
+/// without waiting for a real alert.
+pub fn send_test(settings: &AlertSettings) {
+  let alert = Alert::new(AlertLevel::Info, "Liment", "Test notification");
+  deliver(settings, alert, ALL_CHANNELS);
+}
+
+fn deliver(settings: &AlertSettings, alert: Alert, channels: &[AlertChannel]) {
+  if channels.contains(&AlertChannel::Native) {
+    notification::send_with_sound(&alert.title, &alert.body, settings.sounds.sound(&alert));
+  }
+
+  let ntfy = settings.ntfy.clone().filter(|_| channels.contains(&AlertChannel::Ntfy));
+  let email = settings.email.clone().filter(|_| channels.contains(&AlertChannel::Email));
+
+  if ntfy.is_none() && email.is_none() {
+    return;
+  }
+
+  std::thread::spawn(move || {
+    if let Some(ntfy) = &ntfy {
+      ntfy::send(ntfy, &alert);
+    }
+
+    if let Some(email) = &email
+      && let Err(e) = email::send(email, &alert)
+    {
+      log::warn!("Failed to email alert: {e:#}");
+    }
+  });
+}
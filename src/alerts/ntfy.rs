@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{alerts::Alert, utils::http};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NtfySettings {
+  /// Full topic URL (e.g. "https://ntfy.sh/my-liment-alerts").
+  pub url: String,
+
+  /// Access token for protected topics.
+  pub token: Option<String>,
+
+  /// Message priority, 1 (min) to 5 (max). Defaults to the server's default.
+  pub priority: Option<u8>,
+}
+
+/// Publishes an alert to the ntfy topic. Performs a blocking HTTP request.
+pub fn send(settings: &NtfySettings, alert: &Alert) {
+  log::debug!("Publishing alert to ntfy: {}", alert.title);
+
+  let result = http::with_retries(|| {
+    let mut request =
+      ureq::post(&settings.url).header("Title", &alert.title).header("Tags", "chart_with_upwards_trend");
+
+    if let Some(priority) = settings.priority {
+      request = request.header("Priority", &priority.clamp(1, 5).to_string());
+    }
+
+    if let Some(token) = &settings.token {
+      request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+
+    return http::send(request, &alert.body);
+  });
+
+  if let Err(e) = result {
+    log::warn!("Failed to publish alert to ntfy: {}", e);
+  }
+}
@@ -0,0 +1,577 @@
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
+
+use jiff::{SignedDuration, Timestamp, Zoned, civil::Date};
+
+use crate::{
+  alerts::{Alert, AlertLevel},
+  budget_week::BudgetWeek,
+  config::Config,
+  exchange_rate::ExchangeRate,
+  history::History,
+  limit_history::LimitHistory,
+  morning_summary,
+  providers::{AccountInfo, ProviderError, ProviderKind, TierInfo, UsageData},
+  session_plan::{self, BurnRate, SessionForecast},
+  spend_forecast::SpendForecaster,
+  team_sync::TeamMember,
+  utils::time,
+  weekly_goal::{self, GoalProgress},
+};
+
+/// How long the "Account changed" notice stays in the menu.
+const ACCOUNT_NOTICE_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// How long a window reset notice stays in the menu, at most until the next fetch.
+const RESET_NOTICE_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// How far back the menu compares tray windows to.
+const TREND_OFFSET: SignedDuration = SignedDuration::from_hours(1);
+
+/// How long before the subscription renews the renewal reminder is sent, in seconds.
+const RENEWAL_NOTICE_SECONDS: i64 = 24 * 60 * 60;
+
+/// A window that rolled over between the last two fetches.
+pub struct WindowReset {
+  pub title: String,
+
+  /// When the previous period ended.
+  pub reset_at: Timestamp,
+}
+
+/// Result of a single refresh.
+pub struct FetchResult {
+  pub data: Option<UsageData>,
+
+  /// Why there's no data, when the fetch failed.
+  pub error: Option<ProviderError>,
+
+  pub profile: Option<TierInfo>,
+  pub exchange_rate: Option<ExchangeRate>,
+  pub team: Option<Vec<TeamMember>>,
+
+  /// Usage of the secondary provider, shown read-only under the main one's.
+  pub secondary: Option<SecondaryFetch>,
+}
+
+/// Result of fetching the secondary provider alongside the main one.
+pub struct SecondaryFetch {
+  pub kind: ProviderKind,
+  pub data: Option<UsageData>,
+  pub error: Option<ProviderError>,
+}
+
+/// What the tray and menu should show, so every view handles missing data the same way.
+pub enum ViewState<'a> {
+  /// No fetch has completed yet.
+  Loading,
+
+  /// The last fetch succeeded.
+  Data {
+    fetch: &'a FetchResult,
+    data: &'a UsageData,
+  },
+
+  /// The last fetch failed, the data is from the last one that succeeded.
+  Stale {
+    fetch: &'a FetchResult,
+    data: &'a UsageData,
+    failed_at: Timestamp,
+    error: &'a ProviderError,
+  },
+
+  /// Every fetch so far failed.
+  Error {
+    failed_at: Timestamp,
+    error: &'a ProviderError,
+  },
+
+  /// The configured provider couldn't be set up, e.g. without credentials.
+  NoProvider,
+}
+
+/// State derived from fetch results, independent of the UI that renders it. The UI applies each
+/// fetch here, delivers the returned alerts, and renders from the accessors.
+#[derive(Default)]
+pub struct AppState {
+  /// Result of the last completed fetch, and the provider it came from. A failed fetch doesn't
+  /// replace one with data from the same provider.
+  last_fetch: Option<FetchResult>,
+  last_fetch_kind: Option<ProviderKind>,
+
+  /// When and why the last fetch failed, if it did.
+  failure: Option<(Timestamp, ProviderError)>,
+
+  /// Extra usage spend samples for the end-of-month projection.
+  spend_forecast: SpendForecaster,
+
+  /// Last seen account of the current provider, used to detect token switches.
+  account: Option<(ProviderKind, AccountInfo)>,
+
+  /// When the account last changed under the same provider.
+  account_changed_at: Option<Instant>,
+
+  /// Reset time of each exhausted window the limit alert was last sent for, by window title.
+  limit_notified: HashMap<String, Timestamp>,
+
+  /// Reset time and highest threshold of each window the threshold alert was last sent for, by window title.
+  threshold_notified: HashMap<String, (Timestamp, f64)>,
+
+  /// Windows exhausted over the last week, for the plan hint.
+  limit_history: LimitHistory,
+
+  /// Weekly window usage since the configured first day of the user's own week.
+  budget_week: BudgetWeek,
+
+  /// Every fetched snapshot, for trends.
+  history: History,
+
+  /// Month (year, month) the over-budget alert was last sent for.
+  budget_notified_for: Option<(i16, i8)>,
+
+  /// Reset time of the weekly window the goal alert was last sent for.
+  goal_notified_for: Option<Timestamp>,
+
+  /// Renewal date the renewal reminder was last sent for.
+  renewal_notified_for: Option<Timestamp>,
+
+  /// Day the morning summary was last sent on.
+  morning_summary_sent_on: Option<Date>,
+
+  /// Windows that reset between the last two fetches, and when that fetch completed.
+  recent_resets: Vec<WindowReset>,
+  recent_resets_at: Option<Instant>,
+
+  /// Utilization growth of the session window.
+  burn_rate: BurnRate,
+
+  /// When the user plans a heavy session, and whether they were already warned about it.
+  planned_session: Option<Timestamp>,
+  planned_session_warned: bool,
+}
+
+impl AppState {
+  /// State with the limit history, budget week and usage history from previous runs.
+  pub fn new() -> Self {
+    return Self {
+      limit_history: LimitHistory::load(),
+      budget_week: BudgetWeek::load(),
+      history: History::load(),
+      ..Self::default()
+    };
+  }
+
+  pub fn last_fetch(&self) -> Option<&FetchResult> {
+    return self.last_fetch.as_ref();
+  }
+
+  pub fn view_state(&self, kind: ProviderKind) -> ViewState<'_> {
+    if kind == ProviderKind::Unknown {
+      return ViewState::NoProvider;
+    }
+
+    let Some(fetch) = &self.last_fetch
+    else {
+      return ViewState::Loading;
+    };
+
+    return match (&fetch.data, &self.failure) {
+      (Some(data), None) => ViewState::Data { fetch, data },
+      (Some(data), Some((failed_at, error))) => {
+        ViewState::Stale {
+          fetch,
+          data,
+          failed_at: *failed_at,
+          error,
+        }
+      }
+      (None, Some((failed_at, error))) => ViewState::Error { failed_at: *failed_at, error },
+      // A fetch without data always carries its error.
+      (None, None) => ViewState::Loading,
+    };
+  }
+
+  pub fn spend_forecast(&self) -> &SpendForecaster {
+    return &self.spend_forecast;
+  }
+
+  pub fn limit_history(&self) -> &LimitHistory {
+    return &self.limit_history;
+  }
+
+  pub fn budget_week(&self) -> &BudgetWeek {
+    return &self.budget_week;
+  }
+
+  /// Persists limit hits, budget week usage and snapshots recorded by real fetches, so synthetic ones (e.g. `--bench-render`) don't end up in the history.
+  pub fn store_history(&mut self) {
+    self.limit_history.store();
+    self.budget_week.store();
+    self.history.store();
+  }
+
+  /// Utilization of a window of the current account over the last day, oldest first.
+  pub fn usage_series(&self, kind: ProviderKind, title: &str) -> Vec<(Timestamp, f64)> {
+    return self.history.series(kind, self.account_id(kind), title);
+  }
+
+  /// Id of the account last seen for the provider, which the usage history is recorded under.
+  fn account_id(&self, kind: ProviderKind) -> Option<&str> {
+    return self.account.as_ref().filter(|(k, _)| *k == kind).map(|(_, account)| account.id.as_str());
+  }
+
+  /// Utilization of the current tray windows an hour ago, by tray label, for those the history has.
+  pub fn trend(&self) -> Vec<(&str, f64)> {
+    let (Some(kind), Some(data)) = (self.last_fetch_kind, self.last_fetch.as_ref().and_then(|f| f.data.as_ref()))
+    else {
+      return Vec::new();
+    };
+
+    let at = data.fetched_at - TREND_OFFSET;
+
+    return data
+      .windows
+      .iter()
+      .filter_map(|window| {
+        let label = window.short_title.as_deref()?;
+        let utilization = self.history.utilization_at(kind, self.account_id(kind), &window.title, at)?;
+        return Some((label, utilization));
+      })
+      .collect();
+  }
+
+  /// Returns the new account if it changed recently enough to still show a notice.
+  pub fn changed_account(&self) -> Option<&AccountInfo> {
+    let changed_at = self.account_changed_at?;
+    if changed_at.elapsed() > ACCOUNT_NOTICE_DURATION {
+      return None;
+    }
+
+    return self.account.as_ref().map(|(_, account)| account);
+  }
+
+  /// Returns windows that reset since the previous fetch, for a short while after detecting it.
+  pub fn recent_resets(&self) -> &[WindowReset] {
+    if self.recent_resets_at.is_none_or(|at| at.elapsed() > RESET_NOTICE_DURATION) {
+      return &[];
+    }
+
+    return &self.recent_resets;
+  }
+
+  pub fn planned_session(&self) -> Option<Timestamp> {
+    return self.planned_session;
+  }
+
+  /// Plans a heavy session at the given time, or clears the plan. Returns the warning if the
+  /// session window isn't projected to have enough headroom by then.
+  pub fn plan_session(&mut self, at: Option<Timestamp>) -> Option<Alert> {
+    self.planned_session = at;
+    self.planned_session_warned = false;
+
+    return self.check_planned_session();
+  }
+
+  /// Projects the session window utilization at the planned session start.
+  pub fn session_forecast(&self) -> Option<SessionForecast> {
+    let at = self.planned_session?;
+    let data = self.last_fetch.as_ref()?.data.as_ref()?;
+    let window = session_plan::session_window(data)?;
+
+    return Some(SessionForecast::compute(at, window, self.burn_rate.per_hour()));
+  }
+
+  /// Updates the state with a fetch result from the given provider and returns the alerts it triggered.
+  pub fn apply_fetch(&mut self, kind: ProviderKind, fetch: FetchResult, config: &Config) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+
+    alerts.extend(self.track_account(kind, fetch.profile.as_ref()));
+
+    if config.notify_before_renewal {
+      alerts.extend(self.check_renewal(fetch.profile.as_ref()));
+    }
+
+    if let Some(data) = &fetch.data {
+      if let Some(api_usage) = &data.api_usage {
+        self.spend_forecast.record(api_usage.usage_usd);
+      }
+
+      let account = self.account_id(kind).map(str::to_string);
+      self.history.record(kind, account.as_deref(), data);
+
+      alerts.extend(self.check_thresholds(data, config));
+      alerts.extend(self.check_limits(data, config));
+      alerts.extend(self.check_weekly_goal(data, config));
+      alerts.extend(self.check_morning_summary(data, config));
+
+      self.track_resets(data);
+
+      if let Some(first_day) = config.budget_week_start
+        && let Some(window) = weekly_goal::weekly_window(data)
+      {
+        self.budget_week.record(window, first_day);
+      }
+
+      if let Some(window) = session_plan::session_window(data) {
+        self.burn_rate.record(window);
+      }
+    }
+
+    self.failure = match &fetch.data {
+      Some(_) => None,
+      None => fetch.error.clone().map(|error| (Timestamp::now(), error)),
+    };
+
+    // Keep showing the last good data when a fetch fails.
+    let has_data = fetch.data.is_some();
+    let keep_previous = { true }
+      && !has_data
+      && self.last_fetch_kind == Some(kind)
+      && self.last_fetch.as_ref().is_some_and(|last| last.data.is_some());
+
+    if !keep_previous {
+      self.last_fetch = Some(fetch);
+      self.last_fetch_kind = Some(kind);
+    }
+
+    alerts.extend(self.check_planned_session());
+
+    return alerts;
+  }
+
+  /// Detects the provider's token switching to a different account. Per-account state is reset
+  /// and a notice is shown, so data from two accounts doesn't get mixed silently. The usage
+  /// history is kept, it's recorded per account.
+  fn track_account(&mut self, kind: ProviderKind, profile: Option<&TierInfo>) -> Option<Alert> {
+    let account = profile.and_then(|p| p.account.as_ref())?;
+    let previous = self.account.replace((kind, account.clone()));
+
+    // Switching providers isn't an account change, and neither is a renamed one.
+    let (previous_kind, previous) = previous?;
+    if previous_kind != kind || previous.id == account.id {
+      return None;
+    }
+
+    log::warn!("Account changed: {} -> {}", previous.name, account.name);
+
+    // The previous account's data isn't kept as a fallback or compared against for resets.
+    self.last_fetch = None;
+    self.last_fetch_kind = None;
+
+    self.spend_forecast = SpendForecaster::default();
+    self.limit_history.clear();
+    self.budget_week.clear();
+    self.burn_rate = BurnRate::default();
+    self.limit_notified.clear();
+    self.threshold_notified.clear();
+    self.budget_notified_for = None;
+    self.goal_notified_for = None;
+    self.renewal_notified_for = None;
+    self.recent_resets.clear();
+    self.recent_resets_at = None;
+    self.account_changed_at = Some(Instant::now());
+
+    let body = format!("Now monitoring {}", account.name);
+    return Some(Alert::new(AlertLevel::Info, "Account changed", body));
+  }
+
+  /// Detects windows whose reset time moved forward since the previous fetch, i.e. rolled over.
+  fn track_resets(&mut self, data: &UsageData) {
+    let previous = self.last_fetch.as_ref().and_then(|f| f.data.as_ref());
+
+    self.recent_resets = data
+      .windows
+      .iter()
+      .filter_map(|window| {
+        let resets_at = window.resets_at?;
+        let previous = previous?.windows.iter().find(|w| w.title == window.title)?.resets_at?;
+
+        // Reset times jitter by a few seconds between fetches.
+        if resets_at.as_second() - previous.as_second() < 60 {
+          return None;
+        }
+
+        log::info!("{} reset at {}", window.title, previous);
+
+        return Some(WindowReset {
+          title: window.title.clone(),
+          reset_at: previous,
+        });
+      })
+      .collect();
+    self.recent_resets_at = Some(Instant::now());
+  }
+
+  /// Warns once per window period and threshold when a window crosses one of the configured
+  /// thresholds. Reaching 100% is left to the limit alert.
+  fn check_thresholds(&mut self, data: &UsageData, config: &Config) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+
+    for window in &data.windows {
+      let crossed = config
+        .alerts
+        .thresholds
+        .iter()
+        .copied()
+        .filter(|threshold| *threshold < 100.0 && window.utilization >= *threshold)
+        .reduce(f64::max);
+
+      let (Some(threshold), Some(resets_at)) = (crossed, window.resets_at)
+      else {
+        continue;
+      };
+
+      if config.alerts.is_muted(window) {
+        continue;
+      }
+
+      // Only the highest threshold crossed since the last alert, e.g. a jump from 70% to 95% warns once.
+      if let Some((notified_for, notified)) = self.threshold_notified.get(&window.title)
+        && *notified_for == resets_at
+        && *notified >= threshold
+      {
+        continue;
+      }
+
+      self.threshold_notified.insert(window.title.clone(), (resets_at, threshold));
+
+      let label = window.short_title.as_ref().unwrap_or(&window.title);
+      let title = format!("{} at {:.0}%", window.title, window.utilization);
+      let body = format!("Resets in {}", time::format_reset_time(&resets_at));
+      alerts.push(Alert::new(AlertLevel::Warning, title, body).with_window(label));
+    }
+
+    return alerts;
+  }
+
+  /// Alerts once per window period when a window is exhausted, and once per month when paid
+  /// extra usage goes over the configured budget.
+  fn check_limits(&mut self, data: &UsageData, config: &Config) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+
+    for window in &data.windows {
+      let Some(resets_at) = window.resets_at.filter(|_| window.utilization >= 100.0)
+      else {
+        continue;
+      };
+
+      if config.alerts.is_muted(window) {
+        continue;
+      }
+
+      if self.limit_notified.get(&window.title) == Some(&resets_at) {
+        continue;
+      }
+
+      self.limit_notified.insert(window.title.clone(), resets_at);
+
+      let label = window.short_title.as_ref().unwrap_or(&window.title);
+      self.limit_history.record(label, resets_at);
+
+      let body = format!("Resets in {}", time::format_reset_time(&resets_at));
+      let title = if window.is_over_limit() {
+        format!("{} over limit ({:.0}%)", window.title, window.utilization)
+      }
+      else {
+        format!("{} reached", window.title)
+      };
+      alerts.push(Alert::new(AlertLevel::Critical, title, body).with_window(label));
+    }
+
+    let (Some(api_usage), Some(budget)) = (&data.api_usage, config.extra_usage_budget)
+    else {
+      return alerts;
+    };
+
+    let paid = (api_usage.usage_usd - api_usage.free_credits_usd.unwrap_or(0.0)).max(0.0);
+    let now = Zoned::now();
+    let month = (now.year(), now.month());
+
+    if paid > budget && self.budget_notified_for != Some(month) {
+      self.budget_notified_for = Some(month);
+
+      let body = format!("${:.2} spent this month, budget is ${:.2}", paid, budget);
+      alerts.push(Alert::new(AlertLevel::Critical, "Extra usage over budget", body));
+    }
+
+    return alerts;
+  }
+
+  /// Reminds once per renewal, a day before the subscription renews.
+  fn check_renewal(&mut self, profile: Option<&TierInfo>) -> Option<Alert> {
+    let renews_at = profile?.renews_at?;
+    let remaining = renews_at.as_second() - Timestamp::now().as_second();
+
+    if !(0 ..= RENEWAL_NOTICE_SECONDS).contains(&remaining) || self.renewal_notified_for == Some(renews_at) {
+      return None;
+    }
+
+    self.renewal_notified_for = Some(renews_at);
+
+    let body = format!("Renews {}", time::format_until_time(&renews_at));
+    return Some(Alert::new(AlertLevel::Info, "Subscription renews tomorrow", body));
+  }
+
+  /// Alerts once per week when usage gets ahead of the pace towards the weekly goal.
+  fn check_weekly_goal(&mut self, data: &UsageData, config: &Config) -> Option<Alert> {
+    let progress = GoalProgress::compute(config.weekly_goal?, data)?;
+
+    if !progress.is_over_pace() || self.goal_notified_for == Some(progress.resets_at) {
+      return None;
+    }
+
+    log::info!(
+      "Weekly usage ahead of goal pace: {:.0}% used, {:.0}% expected",
+      progress.utilization,
+      progress.paced_goal
+    );
+
+    self.goal_notified_for = Some(progress.resets_at);
+
+    let body = format!(
+      "{:.0}% of the 7d limit used, the pace for your {:.0}% goal is {:.0}% by now",
+      progress.utilization, progress.goal, progress.paced_goal
+    );
+    return Some(Alert::new(AlertLevel::Warning, "Ahead of weekly goal", body));
+  }
+
+  /// Sends the weekly window summary once a day, on the first fetch after the configured hour.
+  fn check_morning_summary(&mut self, data: &UsageData, config: &Config) -> Option<Alert> {
+    let hour = config.morning_summary?;
+    let now = Zoned::now();
+
+    if !morning_summary::is_due(&now, hour) || self.morning_summary_sent_on == Some(now.date()) {
+      return None;
+    }
+
+    let body = morning_summary::compose(data)?;
+    self.morning_summary_sent_on = Some(now.date());
+
+    return Some(Alert::new(AlertLevel::Info, "Good morning", body));
+  }
+
+  /// Warns once per plan when the planned session isn't projected to have enough headroom, and
+  /// drops the plan once it has started.
+  fn check_planned_session(&mut self) -> Option<Alert> {
+    if self.planned_session.is_some_and(|at| at <= Timestamp::now()) {
+      self.planned_session = None;
+    }
+
+    let forecast = self.session_forecast()?;
+    if forecast.has_headroom() || self.planned_session_warned {
+      return None;
+    }
+
+    self.planned_session_warned = true;
+
+    let body = format!(
+      "Projected at {:.0}% by {}, leaving less than {:.0}% for the session",
+      forecast.utilization,
+      time::format_until_time(&forecast.at),
+      session_plan::MIN_SESSION_HEADROOM
+    );
+    return Some(Alert::new(AlertLevel::Warning, "Not enough headroom for planned session", body));
+  }
+}
@@ -0,0 +1,95 @@
+use std::time::{Duration, Instant};
+
+use color_eyre::eyre::{ContextCompat as _, Result};
+use jiff::{SignedDuration, Timestamp};
+
+use crate::{
+  app_state::FetchResult,
+  delegate::AppDelegate,
+  providers::{ApiUsage, UsageData, UsageWindow},
+};
+
+/// Renders the tray image and rebuilds the menu `iterations` times from synthetic usage, and
+/// prints how long each took. No requests are made and no alerts are sent.
+pub fn run(delegate: &AppDelegate, iterations: usize) -> Result<()> {
+  let iterations = iterations.max(1);
+  let mut rebuilds = Vec::with_capacity(iterations);
+  let mut trays = Vec::with_capacity(iterations);
+
+  for i in 0 .. iterations {
+    // Vary utilization so every color threshold gets rendered.
+    delegate.load_fetch(sample_fetch(i as f64 * 7.0 % 110.0));
+
+    // The tray menu is only filled when it opens, so it's built separately.
+    let start = Instant::now();
+    delegate.rebuild_ui();
+    delegate.build_menu();
+    rebuilds.push(start.elapsed());
+
+    // The tray image draws lazily, rasterizing it forces the drawing handler to run.
+    let image = delegate.tray_image().context("Tray has no image")?;
+    let start = Instant::now();
+    image.TIFFRepresentation().context("Failed to rasterize tray image")?;
+    trays.push(start.elapsed());
+  }
+
+  println!("{iterations} iterations");
+  println!("{}", summary("Menu rebuild", &mut rebuilds));
+  println!("{}", summary("Tray image", &mut trays));
+
+  return Ok(());
+}
+
+/// Formats min, median, 95th percentile and max of the timings, e.g. "Tray image: min 0.41ms, …".
+fn summary(name: &str, timings: &mut [Duration]) -> String {
+  timings.sort();
+
+  let at = |q: f64| timings[((timings.len() - 1) as f64 * q).round() as usize];
+  let ms = |d: Duration| format!("{:.2}ms", d.as_secs_f64() * 1000.0);
+
+  return format!("{name}: min {}, median {}, p95 {}, max {}", ms(at(0.0)), ms(at(0.5)), ms(at(0.95)), ms(at(1.0)),);
+}
+
+/// Made-up fetch with two tray windows, one more in the menu and extra usage, with the session
+/// window at `utilization` percent.
+pub fn sample_fetch(utilization: f64) -> FetchResult {
+  let now = Timestamp::now();
+
+  let window = |title: &str, short_title: Option<&str>, utilization: f64, hours: i64| {
+    return UsageWindow {
+      title: title.to_string(),
+      short_title: short_title.map(str::to_string),
+      utilization,
+      resets_at: Some(now + SignedDuration::from_hours(hours / 2)),
+      period_seconds: Some(hours * 3600),
+      group: None,
+      amount: None,
+    };
+  };
+
+  let data = UsageData {
+    api_usage: Some(ApiUsage {
+      is_enabled: true,
+      usage_usd: utilization / 2.0,
+      max_paid_usd: Some(50.0),
+      free_credits_usd: None,
+    }),
+    peak_hours: None,
+    windows: vec![
+      window("5h Limit", Some("5h"), utilization, 5),
+      window("7d Limit", Some("7d"), utilization / 2.0, 168),
+      window("7d Sonnet", None, utilization / 3.0, 168),
+    ],
+    fetched_at: now,
+    source: "bench".to_string(),
+  };
+
+  return FetchResult {
+    data: Some(data),
+    error: None,
+    profile: None,
+    exchange_rate: None,
+    team: None,
+    secondary: None,
+  };
+}
@@ -0,0 +1,112 @@
+use jiff::{Timestamp, ToSpan as _, Zoned, civil};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Weekday, providers::UsageWindow, utils::cache, weekly_goal};
+
+const BUDGET_WEEK_CACHE_FILE: &str = "budget_week.json";
+
+/// Usage of the weekly window over the user's own week, e.g. aligned to a sprint, which starts on
+/// a configured weekday instead of whenever the provider's window resets. Kept across restarts.
+#[derive(Default, Deserialize, Serialize)]
+pub struct BudgetWeek {
+  /// Local midnight the current week started at.
+  starts_at: Option<Timestamp>,
+
+  /// Utilization added to the weekly window since the week started, in percentage points. Adds up
+  /// across window resets, so it can go over 100.
+  used: f64,
+
+  /// Utilization and reset time of the weekly window at the last fetch.
+  last: Option<(f64, Timestamp)>,
+
+  #[serde(skip)]
+  changed: bool,
+}
+
+impl BudgetWeek {
+  pub fn load() -> Self {
+    return cache::load(BUDGET_WEEK_CACHE_FILE).unwrap_or_default();
+  }
+
+  /// Writes the week to the cache if a fetch was recorded since the last call.
+  pub fn store(&mut self) {
+    if !self.changed {
+      return;
+    }
+
+    cache::store(BUDGET_WEEK_CACHE_FILE, self);
+    self.changed = false;
+  }
+
+  pub fn used(&self) -> f64 {
+    return self.used;
+  }
+
+  /// Starts counting over, e.g. when another account is monitored.
+  pub fn clear(&mut self) {
+    *self = Self { changed: true, ..Self::default() };
+  }
+
+  /// Adds the weekly window's growth since the last fetch, starting over when a new week begins.
+  pub fn record(&mut self, window: &UsageWindow, first_day: Weekday) {
+    let now = Zoned::now();
+    let Some(starts_at) = week_start(&now, first_day)
+    else {
+      return;
+    };
+
+    if self.starts_at != Some(starts_at) {
+      self.starts_at = Some(starts_at);
+      self.used = 0.0;
+    }
+
+    let resets_at = window.resets_at.unwrap_or(now.timestamp());
+    let growth = match self.last {
+      // Reset times jitter by a few seconds between fetches, a later one means the window rolled
+      // over and everything in it is new.
+      Some((_, last_reset)) if resets_at.as_second() - last_reset.as_second() >= 60 => window.utilization,
+      Some((last, _)) => (window.utilization - last).max(0.0),
+
+      // Usage before the first fetch is unknown, counting starts here.
+      None => 0.0,
+    };
+
+    self.used += growth;
+    self.last = Some((window.utilization, resets_at));
+    self.changed = true;
+  }
+
+  /// Share of the week elapsed so far (0–1).
+  pub fn elapsed(&self) -> f64 {
+    let Some(starts_at) = self.starts_at
+    else {
+      return 0.0;
+    };
+
+    let seconds = Timestamp::now().as_second() - starts_at.as_second();
+    return (seconds as f64 / weekly_goal::WEEK_SECONDS as f64).clamp(0.0, 1.0);
+  }
+}
+
+/// Local midnight of the last `first_day`, today included.
+fn week_start(now: &Zoned, first_day: Weekday) -> Option<Timestamp> {
+  let today = now.date();
+  let days = today.weekday().since(first_day.into());
+  let start = today.checked_sub(i64::from(days).days()).ok()?;
+
+  return start.to_zoned(now.time_zone().clone()).ok().map(|zoned| zoned.timestamp());
+}
+
+impl From<Weekday> for civil::Weekday {
+  fn from(day: Weekday) -> Self {
+    return match day {
+      Weekday::Monday => civil::Weekday::Monday,
+      Weekday::Tuesday => civil::Weekday::Tuesday,
+      Weekday::Wednesday => civil::Weekday::Wednesday,
+      Weekday::Thursday => civil::Weekday::Thursday,
+      Weekday::Friday => civil::Weekday::Friday,
+      Weekday::Saturday => civil::Weekday::Saturday,
+      Weekday::Sunday => civil::Weekday::Sunday,
+    };
+  }
+}
@@ -1,12 +1,18 @@
+use std::collections::HashMap;
+
 use color_eyre::eyre::ContextCompat as _;
 use documented::DocumentedFields;
 use serde::{Deserialize, Serialize};
 use smart_default::SmartDefault;
+use strum::IntoEnumIterator as _;
 use toml_edit::DocumentMut;
 
 use crate::{
   CONFIG_PATH,
+  alerts::AlertSettings,
+  exchange_rate::CurrencySettings,
   providers::{ProviderKind, ProviderSettings},
+  team_sync::TeamSettings,
 };
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
@@ -23,6 +29,136 @@ pub enum DateTimeFormat {
   Absolute,
 }
 
+/// Order of day and month in dates.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateOrder {
+  /// Follow the system locale.
+  Locale,
+  /// 13.02
+  DayMonth,
+  /// 02/13
+  MonthDay,
+}
+
+/// First day of the week.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, strum::Display)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+  Monday,
+  Tuesday,
+  Wednesday,
+  Thursday,
+  Friday,
+  Saturday,
+  Sunday,
+}
+
+/// Threshold colors for utilization.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorScale {
+  /// Yellow, orange, red.
+  Default,
+  /// Cyan, blue, purple, distinguishable with deuteranopia and protanopia.
+  BluePurple,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigEditor {
+  /// App associated with `.toml` files.
+  Default,
+  /// The default text editor (`open -t`).
+  TextEditor,
+  /// `$EDITOR` in a new Terminal window.
+  Terminal,
+}
+
+/// Switches for in-progress features that ship disabled. Not written to the default config.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Experimental {
+  /// Show usage in a popover instead of the menu.
+  pub popover: bool,
+
+  /// Render usage history sparklines.
+  pub sparklines: bool,
+
+  /// Fetch providers concurrently.
+  pub async_providers: bool,
+}
+
+impl Experimental {
+  /// Names of the enabled experimental features.
+  pub fn enabled(&self) -> Vec<&'static str> {
+    let flags = [
+      ("popover", self.popover),
+      ("sparklines", self.sparklines),
+      ("async_providers", self.async_providers),
+    ];
+
+    return flags.into_iter().filter(|(_, on)| *on).map(|(name, _)| name).collect();
+  }
+
+  fn is_empty(&self) -> bool {
+    return self.enabled().is_empty();
+  }
+}
+
+/// Menu key equivalents (used with ⌘). An empty string disables the shortcut.
+#[derive(SmartDefault, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Shortcuts {
+  #[default = "r"]
+  pub refresh: String,
+
+  #[default = "d"]
+  pub toggle_display_mode: String,
+
+  #[default = ","]
+  pub open_config: String,
+
+  #[default = "q"]
+  pub quit: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayFontWeight {
+  Regular,
+  Medium,
+  Semibold,
+  Bold,
+}
+
+/// Look of the tray item.
+#[derive(SmartDefault, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TrayStyle {
+  /// Text size in points, from 7 to 11 so two lines fit the menu bar.
+  #[default = 9.0]
+  pub font_size: f64,
+
+  #[default(TrayFontWeight::Semibold)]
+  pub font_weight: TrayFontWeight,
+
+  /// Whether to draw the provider logo before the text.
+  #[default = true]
+  pub show_logo: bool,
+
+  /// Whether to pick text colors for contrast against the menu bar instead of using the system colors.
+  pub auto_contrast: bool,
+}
+
+/// Global hotkeys that work while other apps are focused, e.g. "ctrl+option+d". An empty string disables one.
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Hotkeys {
+  pub toggle_display_mode: String,
+  pub cycle_tray: String,
+}
+
 #[derive(SmartDefault, Deserialize, Serialize, DocumentedFields)]
 #[serde(default)]
 pub struct Config {
@@ -30,10 +166,21 @@ pub struct Config {
   #[default = true]
   pub monochrome_icon: bool,
 
+  /// Logo per provider instead of its own: a bundled one ("claude", "codex", "copilot") or a path to an SVG file.
+  #[serde(skip_serializing_if = "HashMap::is_empty")]
+  pub icons: HashMap<ProviderKind, String>,
+
   /// Whether to color the tray stats by utilization level.
   #[default = true]
   pub stats_colors: bool,
 
+  /// Tray text size and weight, and whether to show the provider logo.
+  pub tray: TrayStyle,
+
+  /// Colors for utilization levels and warnings: "default" (yellow to red) or "blue_purple" (colorblind-friendly).
+  #[default(ColorScale::Default)]
+  pub color_scale: ColorScale,
+
   /// Display mode: "usage" or "remaining".
   #[default(DisplayMode::Usage)]
   pub display_mode: DisplayMode,
@@ -44,6 +191,14 @@ pub struct Config {
   /// Whether to show a warning indicator when usage outpaces time elapsed.
   pub show_pacing_warning: bool,
 
+  /// Whether to show the headroom of the tightest window, relative to the time left in it, in the menu.
+  #[default = true]
+  pub show_effective_headroom: bool,
+
+  /// Whether to show what the tray windows were at an hour ago in the menu, from the usage history.
+  #[default = true]
+  pub show_trend: bool,
+
   /// Whether to show the pacing warning indicator in the tray icon.
   pub show_tray_pacing_warning: bool,
 
@@ -55,10 +210,25 @@ pub struct Config {
   #[default(DateTimeFormat::Relative)]
   pub reset_time_format: DateTimeFormat,
 
+  /// Order of day and month in dates: "locale", "day_month" (13.02) or "month_day" (02/13).
+  #[default(DateOrder::Locale)]
+  pub date_order: DateOrder,
+
   /// How often to refetch usage data, in seconds.
   #[default = 450]
   pub refetch_interval: u32,
 
+  /// Maximum fetches per provider in any hour, guarding against refresh loops. Set to 0 to disable.
+  #[default = 150]
+  pub max_fetches_per_hour: u32,
+
+  /// How many times a provider request is retried after a network error, timeout or server error. Set to 0 to disable.
+  #[default = 2]
+  pub fetch_retries: u32,
+
+  /// Whether malformed sections of API responses fail the whole response instead of being skipped.
+  pub strict_parsing: bool,
+
   /// Whether to automatically check for updates on startup.
   #[default = true]
   pub check_updates: bool,
@@ -66,13 +236,65 @@ pub struct Config {
   /// Whether to automatically install updates when available.
   pub auto_update: bool,
 
+  /// Personal goal for the 7d window utilization, in percent. You get notified when usage gets ahead of an even pace.
+  pub weekly_goal: Option<f64>,
+
+  /// Day your own budgeting week starts on (e.g. "monday"). The menu then also shows 7d usage since that day.
+  pub budget_week_start: Option<Weekday>,
+
+  /// Personal limits below the real ones, in percent by tray label (e.g. `soft_limits = { 5h = 80 }`), shown as 100%.
+  #[serde(skip_serializing_if = "HashMap::is_empty")]
+  pub soft_limits: HashMap<String, f64>,
+
+  /// Monthly extra usage budget in USD. The projected bill is highlighted when it exceeds this (defaults to the cap).
+  pub extra_usage_budget: Option<f64>,
+
+  /// Display spend in another currency, converted from USD (e.g. `currency = { code = "EUR" }`).
+  pub currency: Option<CurrencySettings>,
+
+  /// Whether to notify a day before the subscription renews, for providers that report the date.
+  pub notify_before_renewal: bool,
+
+  /// Hour (0–23) to get a morning notification with the 7d window and where it's headed by tonight.
+  pub morning_summary: Option<u8>,
+
+  /// Whether to suggest another plan in the menu when a window hits its limit 3 or more times in a week.
+  #[default = true]
+  pub plan_hints: bool,
+
+  /// Extra channels for usage alerts, in addition to native notifications.
+  pub alerts: AlertSettings,
+
+  /// Share 5h utilization with a self-hosted team server and show teammates in a "Team" submenu.
+  pub team: Option<TeamSettings>,
+
   /// Whether to ad-hoc codesign the app if not already signed (enables notifications).
   pub auto_codesign: bool,
 
+  /// How "Open Config…" opens the file: "default" (app for .toml), "text_editor" or "terminal" ($EDITOR).
+  #[default(ConfigEditor::TextEditor)]
+  pub config_editor: ConfigEditor,
+
+  /// Menu keyboard shortcuts, used with ⌘. Set one to "" to disable it.
+  pub shortcuts: Shortcuts,
+
+  /// Global hotkeys to toggle the display mode and cycle the tray windows, without opening the menu.
+  pub hotkeys: Hotkeys,
+
+  /// In-progress features, disabled by default.
+  #[serde(skip_serializing_if = "Experimental::is_empty")]
+  pub experimental: Experimental,
+
   /// Default data provider, the LLM subscription you use.
   #[default(ProviderKind::ClaudeCode)]
+  #[serde(deserialize_with = "known_provider")]
   pub provider: ProviderKind,
 
+  /// Another provider whose usage is shown read-only under the default one's, e.g. "codex" next to
+  /// Claude Code. It's fetched on the same refreshes, without alerts or history.
+  #[serde(deserialize_with = "known_secondary_provider")]
+  pub secondary_provider: Option<ProviderKind>,
+
   /// Provider-specific settings.
   pub settings: ProviderSettings,
 }
@@ -82,15 +304,23 @@ impl Config {
     let toml_str = toml_edit::ser::to_string_pretty(&Self::default())?;
     let mut doc: DocumentMut = toml_str.parse()?;
 
-    for (i, (key, comment)) in doc.clone().iter().zip(Self::FIELD_DOCS.iter()).enumerate() {
-      let prefix = if i == 0 { format!("# {comment}\n") } else { format!("\n# {comment}\n") };
-      let item = doc.get_mut(key.0).context("missing key in serialized config")?;
+    // Unset optional fields are skipped during serialization, so look up docs by key name.
+    let mut is_first = true;
+    for (key, comment) in Self::FIELD_NAMES.iter().zip(Self::FIELD_DOCS.iter()) {
+      if !doc.contains_key(key) {
+        continue;
+      }
+
+      let prefix = if is_first { format!("# {comment}\n") } else { format!("\n# {comment}\n") };
+      is_first = false;
+
+      let item = doc.get_mut(key).context("missing key in serialized config")?;
 
       if let Some(table) = item.as_table_mut() {
         table.decor_mut().set_prefix(prefix);
       }
       else {
-        let mut key = doc.key_mut(key.0).context("missing key in serialized config")?;
+        let mut key = doc.key_mut(key).context("missing key in serialized config")?;
         key.leaf_decor_mut().set_prefix(prefix);
       }
     }
@@ -98,6 +328,23 @@ impl Config {
     return Ok(doc.to_string());
   }
 
+  /// Serializes the config to TOML, with a comment after each value naming where it came from
+  /// according to `origin` (given the dotted key, `None` for defaults). With `redacted`, tokens,
+  /// passwords and request headers are masked, so the output is safe to paste into bug reports.
+  pub fn to_toml(&self, redacted: bool, origin: impl Fn(&str) -> Option<String>) -> color_eyre::eyre::Result<String> {
+    let mut doc: DocumentMut = toml_edit::ser::to_string_pretty(self)?.parse()?;
+
+    for (key, item) in doc.as_table_mut().iter_mut() {
+      if redacted {
+        redact(key.get(), item, false);
+      }
+
+      annotate_origin(key.get(), item, &origin);
+    }
+
+    return Ok(doc.to_string());
+  }
+
   pub fn ensure_exists() -> color_eyre::eyre::Result<()> {
     let config_path = &*CONFIG_PATH;
 
@@ -112,3 +359,110 @@ impl Config {
     return Ok(());
   }
 }
+
+/// Deserializes the configured provider, rejecting names that aren't known instead of falling back
+/// to no provider like `ProviderKind` itself does.
+fn known_provider<'de, D>(deserializer: D) -> Result<ProviderKind, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let name = String::deserialize(deserializer)?;
+
+  let mut names = Vec::new();
+  for kind in ProviderKind::iter().filter(|kind| *kind != ProviderKind::Unknown) {
+    let known = serde_json::to_value(kind).map_err(serde::de::Error::custom)?;
+    if known == name.as_str() {
+      return Ok(kind);
+    }
+
+    names.push(known.to_string());
+  }
+
+  return Err(serde::de::Error::custom(format!("unknown provider {name:?}, expected one of {}", names.join(", "))));
+}
+
+fn known_secondary_provider<'de, D>(deserializer: D) -> Result<Option<ProviderKind>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  return known_provider(deserializer).map(Some);
+}
+
+/// Key fragments that mark a value as secret.
+const SECRET_KEYS: &[&str] = &["token", "secret", "password", "key"];
+
+/// Masks secret string values under `item`, including in arrays of tables such as the Claude API
+/// workspaces. Every value of a `headers` table counts as secret, since those usually carry
+/// credentials.
+fn redact(key: &str, item: &mut toml_edit::Item, in_headers: bool) {
+  match item {
+    toml_edit::Item::Value(value) => redact_value(key, value, in_headers),
+
+    toml_edit::Item::Table(table) => {
+      for (child_key, child) in table.iter_mut() {
+        redact(child_key.get(), child, in_headers || key == "headers");
+      }
+    }
+
+    toml_edit::Item::ArrayOfTables(array) => {
+      for table in array.iter_mut() {
+        for (child_key, child) in table.iter_mut() {
+          redact(child_key.get(), child, in_headers);
+        }
+      }
+    }
+
+    toml_edit::Item::None => {}
+  }
+}
+
+fn redact_value(key: &str, value: &mut toml_edit::Value, in_headers: bool) {
+  match value {
+    toml_edit::Value::InlineTable(table) => {
+      for (child_key, child) in table.iter_mut() {
+        redact_value(child_key.get(), child, in_headers || key == "headers");
+      }
+    }
+
+    // Elements fall under the array's key, e.g. a list of tokens.
+    toml_edit::Value::Array(array) => {
+      for child in array.iter_mut() {
+        redact_value(key, child, in_headers);
+      }
+    }
+
+    toml_edit::Value::String(_) if in_headers || SECRET_KEYS.iter().any(|s| key.to_ascii_lowercase().contains(s)) => {
+      *value = "<redacted>".into();
+    }
+
+    _ => {}
+  }
+}
+
+/// Comments each value under `item`, at the dotted key `path`, with where it came from. Arrays of
+/// tables are a single value to the config sources, so their headers are commented instead.
+fn annotate_origin(path: &str, item: &mut toml_edit::Item, origin: &impl Fn(&str) -> Option<String>) {
+  match item {
+    toml_edit::Item::Value(value) => {
+      if let Some(origin) = origin(path) {
+        value.decor_mut().set_suffix(format!(" # {origin}"));
+      }
+    }
+
+    toml_edit::Item::Table(table) => {
+      for (key, child) in table.iter_mut() {
+        annotate_origin(&format!("{path}.{}", key.get()), child, origin);
+      }
+    }
+
+    toml_edit::Item::ArrayOfTables(array) => {
+      if let Some(origin) = origin(path) {
+        for table in array.iter_mut() {
+          table.decor_mut().set_suffix(format!(" # {origin}"));
+        }
+      }
+    }
+
+    toml_edit::Item::None => {}
+  }
+}
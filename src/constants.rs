@@ -22,5 +22,12 @@ pub const LIMENT_DEBUG_TIER: &str = "LIMENT_DEBUG_TIER";
 /// Append ":disabled" to force `is_enabled = false` (e.g. "0:0:50:disabled").
 pub const LIMENT_DEBUG_EXTRA_USAGE: &str = "LIMENT_DEBUG_EXTRA_USAGE";
 
+/// Attach absolute amounts to all windows: "total" or "total:unit" (e.g. "2000:requests").
+/// The used amount follows each window's utilization.
+pub const LIMENT_DEBUG_AMOUNT: &str = "LIMENT_DEBUG_AMOUNT";
+
+/// Show extra usage spend in dollars as the second tray line: "1"/"true" to enable.
+pub const LIMENT_DEBUG_TRAY_SPEND: &str = "LIMENT_DEBUG_TRAY_SPEND";
+
 /// Override peak hours state: "1"/"true" forces peak, "0"/"false" forces off-peak.
 pub const LIMENT_DEBUG_PEAK_HOURS: &str = "LIMENT_DEBUG_PEAK_HOURS";
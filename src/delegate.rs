@@ -1,36 +1,57 @@
-use std::{cell::RefCell, ffi::c_void, sync::Arc};
+use std::{
+  cell::{Cell, RefCell},
+  process::Command,
+  sync::Arc,
+  time::{Duration, Instant},
+};
 
 use block2::RcBlock;
 use dispatch2::{DispatchQueue, MainThreadBound};
+use jiff::Timestamp;
+use notify::RecommendedWatcher;
 use objc2::{
   AnyThread, DefinedClass, MainThreadMarker, MainThreadOnly, Message, define_class, msg_send,
   rc::Retained,
-  runtime::{AnyObject, Bool, NSObject},
+  runtime::{AnyObject, Bool, NSObject, ProtocolObject},
+  sel,
 };
 use objc2_app_kit::{
-  NSApplication, NSApplicationDelegate, NSAttributedStringNSStringDrawing, NSColor, NSCompositingOperation, NSFont,
-  NSFontAttributeName, NSFontWeightSemibold, NSForegroundColorAttributeName, NSImage, NSRectFillUsingOperation,
-  NSStatusBar, NSStatusItem, NSVariableStatusItemLength, NSWindow,
+  NSApplication, NSApplicationDelegate, NSAttributedStringNSStringDrawing, NSColor, NSCompositingOperation, NSEvent,
+  NSFont, NSFontAttributeName, NSFontWeightSemibold, NSForegroundColorAttributeName, NSImage, NSMenu, NSMenuDelegate,
+  NSMenuItem, NSModalResponseOK, NSRectFillUsingOperation, NSSavePanel, NSStatusBar, NSStatusItem,
+  NSVariableStatusItemLength, NSWindow, NSWorkspace, NSWorkspaceSessionDidBecomeActiveNotification,
+  NSWorkspaceSessionDidResignActiveNotification,
 };
 use objc2_core_foundation::CGPoint;
 use objc2_foundation::{
-  NSAttributedString, NSData, NSMutableAttributedString, NSNotification, NSObjectProtocol, NSRange, NSRect, NSSize,
-  NSString, NSTimer,
+  NSAttributedString, NSCopying as _, NSMutableAttributedString, NSNotification, NSObjectProtocol, NSRange, NSRect,
+  NSSize, NSString, NSTimer,
 };
 use strum::IntoEnumIterator as _;
 use tap::Tap;
 
 use crate::{
-  CONFIG_PATH,
-  config::{Config, DisplayMode},
+  CONFIG_PATH, alerts,
+  app_state::{AppState, FetchResult, SecondaryFetch, ViewState},
+  config::{ColorScale, Config, ConfigEditor, DisplayMode},
   constants::LIMENT_DEBUG_REFETCH_INTERVAL,
+  exchange_rate::ExchangeRateCache,
+  fetch_backoff::FetchBackoff,
+  fetch_metrics::FetchMetrics,
+  hotkeys::Hotkey,
+  ipc,
   profile_cache::ProfileCache,
-  providers::{DataProvider, NullProvider, ProviderKind, TierInfo, UsageData, debug::DebugProvider},
-  ui::views,
+  providers::{DataProvider, NullProvider, ProviderKind, claude_code, debug::DebugProvider},
+  request_guard::RequestGuard,
+  soft_limit, statusline, team_sync,
+  ui::{hud, logo, snapshot, token_dialog, views},
   updater::{self, UpdateState, Updater},
-  utils::{codesign, log::LOG_DIR, macos::schedule_timer, notification, toml::serialize_to_item},
+  utils::{self, codesign, log::LOG_DIR, macos::schedule_timer, notification, sandbox, toml::serialize_to_item},
 };
 
+/// Smallest and largest tray font sizes, in points. Two lines of the largest still fit the menu bar.
+const TRAY_FONT_SIZES: (f64, f64) = (7.0, 11.0);
+
 struct TrayBucket<'a> {
   text: &'a str,
   utilization: f64,
@@ -41,9 +62,21 @@ pub struct AppDelegateIvars {
   /// Provider to fetch usage data.
   provider: RefCell<Arc<dyn DataProvider>>,
 
+  /// Provider shown read-only under the main one's usage, if configured.
+  secondary_provider: RefCell<Option<Arc<dyn DataProvider>>>,
+
   /// Cached profile tier info per provider, shared with background threads.
   profile_cache: Arc<ProfileCache>,
 
+  /// Cached USD exchange rate for the display currency, shared with background threads.
+  exchange_rates: Arc<ExchangeRateCache>,
+
+  /// Fetch latency and failure counters per provider, shared with background threads.
+  fetch_metrics: Arc<FetchMetrics>,
+
+  /// Backoff after failures per provider, persisted and shared with background threads.
+  fetch_backoff: Arc<FetchBackoff>,
+
   /// Status bar item for displaying the current usage.
   status_item: Retained<NSStatusItem>,
 
@@ -55,6 +88,37 @@ pub struct AppDelegateIvars {
 
   /// Retained about window (kept alive so it doesn't get deallocated).
   about_window: RefCell<Option<Retained<NSWindow>>>,
+
+  /// State derived from fetch results (last data, spend samples, alert bookkeeping).
+  state: RefCell<AppState>,
+
+  /// Periodic refresh timer, replaced when the refetch interval changes.
+  refresh_timer: RefCell<Option<Retained<NSTimer>>>,
+
+  /// Hourly fetch cap per provider.
+  request_guard: RefCell<RequestGuard>,
+
+  /// Whether Claude Code credentials were in the keychain at the last check, while waiting for sign-in.
+  had_keychain_credentials: Cell<Option<bool>>,
+
+  /// Whether periodic refreshes are paused, via `liment ctl pause`.
+  paused: Cell<bool>,
+
+  /// Whether another user is switched in (fast user switching). Polling and alerts stop meanwhile.
+  session_inactive: Cell<bool>,
+
+  /// Tray line shown on its own, cycled with a hotkey. 0 shows all lines, otherwise 1-based.
+  tray_selection: Cell<usize>,
+
+  /// HUD currently on screen, hidden right away when another one is shown.
+  hud: RefCell<Option<Retained<NSWindow>>>,
+
+  /// Whether the tray menu is open.
+  menu_open: Cell<bool>,
+
+  /// Config file watcher and global hotkey monitor, stopped on shutdown.
+  config_watcher: RefCell<Option<RecommendedWatcher>>,
+  hotkey_monitor: RefCell<Option<Retained<AnyObject>>>,
 }
 
 impl AppDelegateIvars {
@@ -66,9 +130,29 @@ impl AppDelegateIvars {
     return self.config.borrow();
   }
 
+  pub fn fetch_metrics(&self) -> &FetchMetrics {
+    return &self.fetch_metrics;
+  }
+
   pub fn update_state(&self) -> std::cell::Ref<'_, UpdateState> {
     return self.updater.state();
   }
+
+  pub fn state(&self) -> std::cell::Ref<'_, AppState> {
+    return self.state.borrow();
+  }
+
+  /// Returns how long until the current provider may fetch again, if it's throttled.
+  pub fn throttled_for(&self) -> Option<Duration> {
+    let kind = self.provider().kind();
+    let cap = self.config().max_fetches_per_hour;
+
+    return self.request_guard.borrow().throttled_for(kind, cap);
+  }
+
+  pub fn is_paused(&self) -> bool {
+    return self.paused.get();
+  }
 }
 
 define_class!(
@@ -81,9 +165,29 @@ define_class!(
   impl AppDelegate {
     #[unsafe(method(onTimer:))]
     fn on_timer(&self, _timer: &NSTimer) {
+      if self.ivars().is_paused() || self.ivars().session_inactive.get() {
+        return;
+      }
+
       self.refresh();
     }
 
+    #[unsafe(method(onSessionResignActive:))]
+    fn on_session_resign_active(&self, _notification: &NSNotification) {
+      log::info!("Session became inactive, pausing polling");
+      self.ivars().session_inactive.set(true);
+    }
+
+    #[unsafe(method(onSessionBecomeActive:))]
+    fn on_session_become_active(&self, _notification: &NSNotification) {
+      log::info!("Session became active again, resuming polling");
+      self.ivars().session_inactive.set(false);
+
+      if !self.ivars().is_paused() {
+        self.refresh();
+      }
+    }
+
     #[unsafe(method(onQuit:))]
     fn on_quit(&self, _sender: &AnyObject) {
       let app = NSApplication::sharedApplication(self.mtm());
@@ -93,6 +197,7 @@ define_class!(
 
     #[unsafe(method(onRefresh:))]
     fn on_refresh(&self, _sender: &AnyObject) {
+      self.ivars().fetch_backoff.forgive(self.ivars().provider().kind());
       self.refresh();
 
       // Reopen the menu so the user sees the update in-place.
@@ -102,10 +207,16 @@ define_class!(
       }
     }
 
+    #[unsafe(method(onToggleDisplayMode:))]
+    fn on_toggle_display_mode(&self, _sender: &AnyObject) {
+      self.toggle_display_mode();
+    }
+
     #[unsafe(method(onOpenConfig:))]
     fn on_open_config(&self, _sender: &AnyObject) {
-      if let Err(e) = open::that(&*CONFIG_PATH) {
-        log::error!("Failed to open config file: {}", e);
+      let editor = self.ivars().config().config_editor;
+      if let Err(e) = Self::open_config(editor) {
+        log::error!("Failed to open config file: {:#}", e);
       }
     }
 
@@ -116,6 +227,71 @@ define_class!(
       }
     }
 
+    #[unsafe(method(onCopyTokenDiagnostics:))]
+    fn on_copy_token_diagnostics(&self, _sender: &AnyObject) {
+      let provider = self.ivars().provider();
+      let diagnostics = provider.token_diagnostics().unwrap_or_else(|| "No token diagnostics for this provider".to_string());
+
+      utils::macos::copy_to_pasteboard(&format!("Provider: {}\n{diagnostics}", provider.kind()));
+      log::info!("Copied token diagnostics");
+    }
+
+    #[unsafe(method(onSaveSnapshot:))]
+    fn on_save_snapshot(&self, _sender: &AnyObject) {
+      let mtm = self.mtm();
+      let Some(png) = snapshot::render_menu_png(&self.build_menu())
+      else {
+        log::warn!("Nothing to snapshot");
+        return;
+      };
+
+      let now = jiff::Zoned::now();
+      let file_name = format!("liment-{}.png", now.strftime("%Y-%m-%d-%H%M"));
+
+      let panel = NSSavePanel::savePanel(mtm);
+      panel.setNameFieldStringValue(&NSString::from_str(&file_name));
+
+      #[allow(deprecated)]
+      NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+
+      if panel.runModal() != NSModalResponseOK {
+        return;
+      }
+
+      let Some(url) = panel.URL()
+      else {
+        return;
+      };
+
+      if !png.writeToURL_atomically(&url, true) {
+        log::error!("Failed to save snapshot to {:?}", url.path());
+      }
+    }
+
+    #[unsafe(method(onSendTestNotification:))]
+    fn on_send_test_notification(&self, _sender: &AnyObject) {
+      log::info!("Sending test notification");
+      alerts::send_test(&self.ivars().config().alerts);
+    }
+
+    #[unsafe(method(onEnterToken:))]
+    fn on_enter_token(&self, _sender: &AnyObject) {
+      self.prompt_manual_token(None);
+    }
+
+    #[unsafe(method(onForgetToken:))]
+    fn on_forget_token(&self, _sender: &AnyObject) {
+      if let Err(e) = claude_code::remove_manual_token() {
+        let msg = format!("Failed to remove the entered token: {e:#}");
+        log::error!("{msg}");
+        notification::send_error(&msg);
+        return;
+      }
+
+      log::info!("Removed manually entered token from keychain");
+      self.reauthenticate();
+    }
+
     #[unsafe(method(onCheckForUpdates:))]
     fn on_check_for_updates(&self, _sender: &AnyObject) {
       self.attempt_update(true);
@@ -170,10 +346,105 @@ define_class!(
       self.change_provider(kind);
     }
 
+    #[unsafe(method(onProviderAction:))]
+    fn on_provider_action(&self, sender: &AnyObject) {
+      let tag: isize = unsafe { msg_send![sender, tag] };
+      let actions = self.ivars().provider().actions();
+      let Some(action) = actions.get(tag as usize)
+      else {
+        return;
+      };
+
+      if let Err(e) = open::that(&action.url) {
+        log::error!("Failed to open {}: {e}", action.url);
+      }
+    }
+
+    #[unsafe(method(onReauthenticate:))]
+    fn on_reauthenticate(&self, _sender: &AnyObject) {
+      self.reauthenticate();
+    }
+
+    #[unsafe(method(onOpenPlans:))]
+    fn on_open_plans(&self, _sender: &AnyObject) {
+      let Some(url) = self.ivars().provider().plans_url()
+      else {
+        return;
+      };
+
+      if let Err(e) = open::that(url) {
+        log::error!("Failed to open {url}: {e}");
+      }
+    }
+
+    #[unsafe(method(onChangeInterval:))]
+    fn on_change_interval(&self, sender: &AnyObject) {
+      let tag: isize = unsafe { msg_send![sender, tag] };
+      let Ok(interval) = u32::try_from(tag)
+      else {
+        return;
+      };
+
+      if interval == self.ivars().config().refetch_interval {
+        return;
+      }
+
+      // The file watcher picks up the change and reschedules the timer.
+      Self::write_config_value("refetch_interval", serialize_to_item(interval));
+    }
+
+    #[unsafe(method(onPlanSession:))]
+    fn on_plan_session(&self, sender: &AnyObject) {
+      let tag: isize = unsafe { msg_send![sender, tag] };
+
+      // Tag 0 clears the plan, others are the session start in unix seconds.
+      let at = if tag == 0 { None } else { Timestamp::from_second(tag as i64).ok() };
+      let warning = self.ivars().state.borrow_mut().plan_session(at);
+
+      if let Some(alert) = warning {
+        alerts::send(&self.ivars().config().alerts, alert);
+      }
+
+      self.rebuild_ui();
+    }
+
   }
 
   unsafe impl NSObjectProtocol for AppDelegate {}
 
+  unsafe impl NSMenuDelegate for AppDelegate {
+    #[unsafe(method(menuNeedsUpdate:))]
+    fn menu_needs_update(&self, menu: &NSMenu) {
+      self.populate(menu);
+    }
+
+    #[unsafe(method(menuWillOpen:))]
+    fn menu_will_open(&self, _menu: &NSMenu) {
+      self.ivars().menu_open.set(true);
+    }
+
+    #[unsafe(method(menuDidClose:))]
+    fn menu_did_close(&self, menu: &NSMenu) {
+      self.ivars().menu_open.set(false);
+
+      // Release the rows until the next opening. Deferred so the chosen item's action is sent
+      // while it's still in the menu. One separator stays, an empty status item menu doesn't open.
+      // Skipped if the action reopened the menu (e.g. Refresh or Retry), it's filled by then.
+      let menu = MainThreadBound::new(menu.retain(), self.mtm());
+      let this = MainThreadBound::new(self.retain(), self.mtm());
+      DispatchQueue::main().exec_async(move || {
+        let mtm = MainThreadMarker::new().expect("Must be on main thread");
+        if this.get(mtm).ivars().menu_open.get() {
+          return;
+        }
+
+        let menu = menu.get(mtm);
+        menu.removeAllItems();
+        menu.addItem(&NSMenuItem::separatorItem(mtm));
+      });
+    }
+  }
+
   unsafe impl NSApplicationDelegate for AppDelegate {
     #[unsafe(method(applicationDidFinishLaunching:))]
     fn did_finish_launching(&self, _notification: &NSNotification) {
@@ -181,26 +452,29 @@ define_class!(
       notification::request_authorization();
 
       // Auto-codesign if enabled and not already signed.
-      if self.ivars().config().auto_codesign && codesign::ensure_signed() {
+      if self.ivars().config().auto_codesign && !sandbox::is_sandboxed() && codesign::ensure_signed() {
         NSApplication::sharedApplication(self.mtm()).terminate(None);
         return;
       }
 
+      // Stop polling while another user is switched in.
+      self.observe_session();
+
       // First refresh.
       self.refresh();
 
-      // Check for updates on startup if enabled.
-      if self.ivars().config().check_updates {
+      // Check for updates on startup if enabled. Sandboxed builds are updated by their package manager.
+      if self.ivars().config().check_updates && !sandbox::is_sandboxed() {
         self.attempt_update(false);
       }
 
-      let refetch_interval = std::env::var(LIMENT_DEBUG_REFETCH_INTERVAL)
-        .ok()
-        .and_then(|v| v.parse::<f64>().ok())
-        .unwrap_or(self.ivars().config().refetch_interval as f64);
-
       // Refresh UI periodically.
-      schedule_timer!(refetch_interval, self, onTimer);
+      self.schedule_refresh_timer();
+    }
+
+    #[unsafe(method(applicationWillTerminate:))]
+    fn will_terminate(&self, _notification: &NSNotification) {
+      self.shutdown();
     }
   }
 );
@@ -211,21 +485,40 @@ impl AppDelegate {
     let status_item = status_bar.statusItemWithLength(NSVariableStatusItemLength);
 
     let provider = Self::provider_from_config(&config);
+    let secondary_provider = Self::secondary_provider_from_config(&config);
 
     let this = mtm.alloc::<AppDelegate>();
     let this = this.set_ivars(AppDelegateIvars {
       provider: RefCell::new(provider),
+      secondary_provider: RefCell::new(secondary_provider),
       profile_cache: Arc::new(ProfileCache::default()),
+      exchange_rates: Arc::new(ExchangeRateCache::default()),
+      fetch_metrics: Arc::new(FetchMetrics::default()),
+      fetch_backoff: Arc::new(FetchBackoff::load()),
       status_item,
       config: RefCell::new(config),
       updater: Updater::new(),
       about_window: RefCell::new(None),
+      state: RefCell::new(AppState::new()),
+      refresh_timer: RefCell::new(None),
+      request_guard: RefCell::new(RequestGuard::default()),
+      had_keychain_credentials: Cell::new(None),
+      paused: Cell::new(false),
+      session_inactive: Cell::new(false),
+      tray_selection: Cell::new(0),
+      hud: RefCell::new(None),
+      menu_open: Cell::new(false),
+      config_watcher: RefCell::new(None),
+      hotkey_monitor: RefCell::new(None),
     });
     let this: Retained<Self> = unsafe { msg_send![super(this), init] };
 
     // Set initial menu so the tray is interactive while loading.
-    let loading_menu = views::loading_menu(mtm, &this);
-    this.ivars().status_item.setMenu(Some(&loading_menu));
+    let menu = objc2_app_kit::NSMenu::new(mtm);
+    let kind = this.ivars().provider().kind();
+    views::populate_status_menu(&menu, mtm, &this, &this.ivars().state().view_state(kind));
+    menu.setDelegate(Some(ProtocolObject::from_ref(&*this)));
+    this.ivars().status_item.setMenu(Some(&menu));
 
     return this;
   }
@@ -235,7 +528,12 @@ impl AppDelegate {
       return;
     }
 
-    // Update the config file on disk.
+    Self::write_config_value("provider", serialize_to_item(kind));
+  }
+
+  /// Updates a single top-level key in the config file on disk, preserving the rest of it.
+  /// The file watcher picks up the change and calls `reload_config`.
+  fn write_config_value(key: &str, value: toml_edit::Item) {
     let config_str = match fs_err::read_to_string(&*CONFIG_PATH) {
       Ok(s) => s,
       Err(e) => {
@@ -252,26 +550,55 @@ impl AppDelegate {
       }
     };
 
-    doc["provider"] = serialize_to_item(kind);
+    doc[key] = value;
 
     if let Err(e) = fs_err::write(&*CONFIG_PATH, doc.to_string()) {
       log::error!("Failed to write config: {e}");
     }
-
-    // The file watcher will pick up the change and call reload_config.
   }
 
   pub fn reload_config(&self, new_config: Config) {
     // Auto-codesign if enabled and not already signed.
-    if new_config.auto_codesign && codesign::ensure_signed() {
+    if new_config.auto_codesign && !sandbox::is_sandboxed() && codesign::ensure_signed() {
       NSApplication::sharedApplication(self.mtm()).terminate(None);
       return;
     }
 
-    let provider = Self::provider_from_config(&new_config);
-    *self.ivars().provider.borrow_mut() = provider;
+    utils::json::set_strict_parsing(new_config.strict_parsing);
+    utils::http::set_fetch_retries(new_config.fetch_retries);
+    utils::time::set_date_order(new_config.date_order);
+
+    let interval_changed = new_config.refetch_interval != self.ivars().config().refetch_interval;
+
+    // Most reloads come from toggles written by the app itself. Recreating the provider reads the
+    // keychain again and the fetch uses up a request, so both wait for a change that needs them.
+    // The settings don't implement `PartialEq`, their serialized form is compared instead.
+    let provider_changed = {
+      let config = self.ivars().config();
+      let settings = |config: &Config| serde_json::to_value(&config.settings).ok();
+      new_config.provider != config.provider
+        || new_config.secondary_provider != config.secondary_provider
+        || settings(&new_config) != settings(&config)
+    };
+
+    if provider_changed {
+      let provider = Self::provider_from_config(&new_config);
+      // The settings may hold new credentials, give them a try.
+      self.ivars().fetch_backoff.forgive(provider.kind());
+      *self.ivars().provider.borrow_mut() = provider;
+      *self.ivars().secondary_provider.borrow_mut() = Self::secondary_provider_from_config(&new_config);
+    }
     *self.ivars().config.borrow_mut() = new_config;
 
+    if interval_changed {
+      self.schedule_refresh_timer();
+    }
+
+    if !provider_changed {
+      self.rebuild_ui();
+      return;
+    }
+
     // Update the provider checkmark immediately so it reflects the actual provider,
     // even if the fetch hasn't completed yet (or returns None for NullProvider).
     let mtm = self.mtm();
@@ -283,6 +610,63 @@ impl AppDelegate {
     self.refresh();
   }
 
+  /// Opens the config file in the configured editor.
+  fn open_config(editor: ConfigEditor) -> color_eyre::eyre::Result<()> {
+    use color_eyre::eyre::Context as _;
+
+    // Sandboxed builds can't script Terminal.
+    let editor = if editor == ConfigEditor::Terminal && sandbox::is_sandboxed() {
+      log::warn!("Terminal config editor isn't available in the sandbox, using the text editor");
+      ConfigEditor::TextEditor
+    }
+    else {
+      editor
+    };
+
+    match editor {
+      ConfigEditor::Default => {
+        open::that(&*CONFIG_PATH)?;
+      }
+
+      ConfigEditor::TextEditor => {
+        Command::new("open").arg("-t").arg(CONFIG_PATH.as_str()).spawn().context("Failed to run open")?;
+      }
+
+      ConfigEditor::Terminal => {
+        // `$EDITOR` is expanded by the login shell in Terminal, GUI apps don't inherit it.
+        let path = CONFIG_PATH.as_str().replace('\'', "'\\''");
+        let command = format!("${{EDITOR:-vi}} '{path}'").replace('\\', "\\\\").replace('"', "\\\"");
+
+        Command::new("osascript")
+          .arg("-e")
+          .arg(format!("tell application \"Terminal\" to do script \"{command}\""))
+          .arg("-e")
+          .arg("tell application \"Terminal\" to activate")
+          .spawn()
+          .context("Failed to run osascript")?;
+      }
+    }
+
+    return Ok(());
+  }
+
+  /// (Re)schedules the periodic refresh timer using the configured interval.
+  fn schedule_refresh_timer(&self) {
+    let refetch_interval = std::env::var(LIMENT_DEBUG_REFETCH_INTERVAL)
+      .ok()
+      .and_then(|v| v.parse::<f64>().ok())
+      .unwrap_or(self.ivars().config().refetch_interval as f64);
+
+    if let Some(old_timer) = self.ivars().refresh_timer.take() {
+      old_timer.invalidate();
+    }
+
+    log::debug!("Scheduling refresh every {refetch_interval}s");
+
+    let timer = schedule_timer!(refetch_interval, self, onTimer);
+    *self.ivars().refresh_timer.borrow_mut() = Some(timer);
+  }
+
   fn provider_from_config(config: &Config) -> Arc<dyn DataProvider> {
     let provider = match config.provider.into_provider(&config.settings) {
       Ok(provider) => provider,
@@ -300,25 +684,348 @@ impl AppDelegate {
     }
   }
 
+  /// The configured secondary provider, unless it's the main one. `None` if it couldn't be created.
+  fn secondary_provider_from_config(config: &Config) -> Option<Arc<dyn DataProvider>> {
+    let kind = config.secondary_provider.filter(|kind| *kind != config.provider)?;
+
+    return match kind.into_provider(&config.settings) {
+      Ok(provider) => Some(provider),
+      Err(e) => {
+        let msg = format!("Failed to create secondary provider: {e:#}");
+        log::error!("{msg}");
+        notification::send_error(&msg);
+        None
+      }
+    };
+  }
+
+  /// If the Claude Code provider couldn't start because there were no credentials, recreates it
+  /// once they appear in the keychain. Until then the failed provider stays in place, so the
+  /// token isn't re-read (and possibly prompted for) every cycle.
+  fn resume_after_sign_in(&self) {
+    let waiting_for_sign_in = {
+      let config = self.ivars().config();
+      let has_token_override = config.settings.claude_code.as_ref().is_some_and(|s| s.token.is_some());
+
+      config.provider == ProviderKind::ClaudeCode
+        && !has_token_override
+        && self.ivars().provider().kind() == ProviderKind::Unknown
+    };
+
+    if !waiting_for_sign_in {
+      return;
+    }
+
+    // Only retry when the credentials go from missing to present, so a provider that failed for
+    // another reason (e.g. a denied keychain prompt) isn't retried every cycle.
+    let has_credentials = claude_code::has_keychain_credentials();
+    let had_credentials = self.ivars().had_keychain_credentials.replace(Some(has_credentials));
+    if !has_credentials || had_credentials != Some(false) {
+      return;
+    }
+
+    log::info!("Claude Code credentials appeared in the keychain, recreating provider");
+
+    let provider = Self::provider_from_config(&self.ivars().config());
+    if provider.kind() == ProviderKind::Unknown {
+      return;
+    }
+
+    *self.ivars().provider.borrow_mut() = provider;
+    notification::send("Sign-in detected", "Resuming usage monitoring");
+
+    let mtm = self.mtm();
+    if let Some(menu) = self.ivars().status_item.menu(mtm) {
+      views::update_provider_item(&menu, mtm, self, ProviderKind::ClaudeCode);
+    }
+  }
+
+  /// Asks for a Claude Code token, then validates and saves it on a background thread. A rejected
+  /// token reopens the dialog with the error, a valid one replaces the failed provider.
+  fn prompt_manual_token(&self, error: Option<&str>) {
+    let mtm = self.mtm();
+    let Some(token) = token_dialog::prompt_token(mtm, error)
+    else {
+      return;
+    };
+
+    let settings = self.ivars().config().settings.claude_code.clone().unwrap_or_default();
+    let this = MainThreadBound::new(self.retain(), mtm);
+
+    std::thread::spawn(move || {
+      let result = claude_code::save_manual_token(&settings, &token).map_err(|e| format!("{e:#}"));
+
+      DispatchQueue::main().exec_async(move || {
+        let mtm = MainThreadMarker::new().expect("Must be on main thread");
+        let delegate = this.get(mtm);
+
+        if let Err(msg) = result {
+          log::warn!("Manually entered token rejected: {msg}");
+          delegate.prompt_manual_token(Some(&msg));
+          return;
+        }
+
+        delegate.reauthenticate();
+      });
+    });
+  }
+
+  /// Recreates the provider, so its credentials are loaded again from the config or keychain
+  /// (e.g. after signing in to Claude Code again), and refetches.
+  fn reauthenticate(&self) {
+    log::info!("Re-authenticating {}", self.ivars().provider().kind());
+
+    let provider = Self::provider_from_config(&self.ivars().config());
+    self.ivars().fetch_backoff.forgive(provider.kind());
+    *self.ivars().provider.borrow_mut() = provider;
+    self.refresh();
+  }
+
   /// Refetches latest data from the API and updates the UI.
-  fn refresh(&self) {
+  pub fn refresh(&self) {
+    self.resume_after_sign_in();
+
     let provider = Arc::clone(&self.ivars().provider());
+
+    if let Some(error) = self.ivars().fetch_backoff.active(provider.kind()) {
+      log::info!("Skipping fetch: {} is backing off ({error})", provider.kind());
+      self.finish_refresh(FetchResult {
+        data: None,
+        error: Some(error),
+        profile: None,
+        exchange_rate: None,
+        team: None,
+        secondary: None,
+      });
+      return;
+    }
+
+    let cap = self.ivars().config().max_fetches_per_hour;
+    if !self.ivars().request_guard.borrow_mut().try_acquire(provider.kind(), cap) {
+      log::warn!("Skipping fetch: {} reached {} fetches in the last hour", provider.kind(), cap);
+      self.rebuild_ui();
+      return;
+    }
+
+    // A secondary provider that's backing off isn't fetched, its slot stays free.
+    let secondary = self.ivars().secondary_provider.borrow().clone().filter(|secondary| {
+      return self.ivars().fetch_backoff.active(secondary.kind()).is_some()
+        || self.ivars().request_guard.borrow_mut().try_acquire(secondary.kind(), cap);
+    });
+
     let profile_cache = Arc::clone(&self.ivars().profile_cache);
+    let exchange_rates = Arc::clone(&self.ivars().exchange_rates);
+    let fetch_metrics = Arc::clone(&self.ivars().fetch_metrics);
+    let fetch_backoff = Arc::clone(&self.ivars().fetch_backoff);
+    let currency = self.ivars().config().currency.clone();
+    let team_settings = self.ivars().config().team.clone();
     let mtm = self.mtm();
     let this = MainThreadBound::new(self.retain(), mtm);
 
     std::thread::spawn(move || {
-      let data = provider.fetch_data();
+      let started = Instant::now();
+      let result = provider.fetch_data();
+      fetch_metrics.record(provider.kind(), started.elapsed(), result.is_ok());
+      fetch_backoff.record(provider.kind(), result.as_ref().map(|_| ()));
+
+      let (data, error) = match result {
+        Ok(data) => (Some(data), None),
+        Err(e) => {
+          log::warn!("Fetch failed for {}: {e}", provider.kind());
+          (None, Some(e))
+        }
+      };
+
       let profile = profile_cache.resolve(&*provider);
 
+      // Only resolve the exchange rate when there's spend to convert.
+      let exchange_rate = currency
+        .filter(|_| data.as_ref().is_some_and(|d| d.api_usage.is_some()))
+        .and_then(|c| exchange_rates.resolve(&c));
+
+      let team = team_settings.zip(data.as_ref()).and_then(|(settings, data)| team_sync::sync(&settings, data));
+
+      let secondary = secondary.map(|secondary| {
+        let kind = secondary.kind();
+        if let Some(error) = fetch_backoff.active(kind) {
+          log::info!("Skipping fetch: {kind} is backing off ({error})");
+          return SecondaryFetch { kind, data: None, error: Some(error) };
+        }
+
+        let started = Instant::now();
+        let result = secondary.fetch_data();
+        fetch_metrics.record(kind, started.elapsed(), result.is_ok());
+        fetch_backoff.record(kind, result.as_ref().map(|_| ()));
+
+        return match result {
+          Ok(data) => SecondaryFetch { kind, data: Some(data), error: None },
+          Err(e) => {
+            log::warn!("Fetch failed for {kind}: {e}");
+            SecondaryFetch { kind, data: None, error: Some(e) }
+          }
+        };
+      });
+
       DispatchQueue::main().exec_async(move || {
         let mtm = MainThreadMarker::new().expect("Must be on main thread");
 
-        this.get(mtm).rebuild_ui(data.as_ref(), profile.as_ref());
+        this.get(mtm).finish_refresh(FetchResult {
+          data,
+          error,
+          profile,
+          exchange_rate,
+          team,
+          secondary,
+        });
       });
     });
   }
 
+  fn toggle_display_mode(&self) -> DisplayMode {
+    let display_mode = match self.ivars().config().display_mode {
+      DisplayMode::Usage => DisplayMode::Remaining,
+      DisplayMode::Remaining => DisplayMode::Usage,
+    };
+
+    // Apply right away instead of waiting for the file watcher to reload the config.
+    self.ivars().config.borrow_mut().display_mode = display_mode;
+    self.rebuild_ui();
+
+    let mtm = self.mtm();
+    if let Some(menu) = self.ivars().status_item.menu(mtm) {
+      views::update_display_mode_item(&menu, mtm, self);
+    }
+
+    Self::write_config_value("display_mode", serialize_to_item(display_mode));
+
+    return display_mode;
+  }
+
+  /// Runs the action of a configured global hotkey, if the key press matches one.
+  pub fn handle_hotkey(&self, event: &NSEvent) {
+    let hotkeys = self.ivars().config().hotkeys.clone();
+
+    if Hotkey::parse(&hotkeys.toggle_display_mode).is_some_and(|h| h.matches(event)) {
+      let text = match self.toggle_display_mode() {
+        DisplayMode::Usage => "Showing usage",
+        DisplayMode::Remaining => "Showing remaining",
+      };
+      self.show_hud(text);
+    }
+    else if Hotkey::parse(&hotkeys.cycle_tray).is_some_and(|h| h.matches(event)) {
+      self.cycle_tray();
+    }
+  }
+
+  /// Shows the next tray line on its own, then all of them again.
+  fn cycle_tray(&self) {
+    let labels = {
+      let state = self.ivars().state();
+      let Some(data) = state.last_fetch().and_then(|f| f.data.as_ref())
+      else {
+        return;
+      };
+
+      let display_mode = self.ivars().config().display_mode;
+      let descriptor = self.ivars().provider().tray_descriptor(data, display_mode);
+      descriptor.lines.into_iter().map(|l| l.label).collect::<Vec<_>>()
+    };
+
+    if labels.len() < 2 {
+      self.show_hud("Only one window in the tray");
+      return;
+    }
+
+    let selection = (self.ivars().tray_selection.get() + 1) % (labels.len() + 1);
+    self.ivars().tray_selection.set(selection);
+    self.rebuild_ui();
+
+    let text = match selection {
+      0 => "Tray: all windows".to_string(),
+      n => format!("Tray: {}", labels[n - 1]),
+    };
+    self.show_hud(&text);
+  }
+
+  fn show_hud(&self, text: &str) {
+    let window = hud::show(self.mtm(), text);
+
+    if let Some(previous) = self.ivars().hud.replace(Some(window)) {
+      previous.orderOut(None);
+    }
+  }
+
+  /// Keeps the config watcher and hotkey monitor alive until shutdown.
+  pub fn attach_services(
+    &self,
+    config_watcher: Option<RecommendedWatcher>,
+    hotkey_monitor: Option<Retained<AnyObject>>,
+  ) {
+    *self.ivars().config_watcher.borrow_mut() = config_watcher;
+    *self.ivars().hotkey_monitor.borrow_mut() = hotkey_monitor;
+  }
+
+  /// Stops background work and cleans up before the app exits, whichever way it's quit.
+  /// `terminate` exits the process without returning from the run loop, so nothing owned by
+  /// `main` gets dropped, this is the only chance to clean up.
+  fn shutdown(&self) {
+    log::info!("Shutting down");
+
+    if let Some(timer) = self.ivars().refresh_timer.take() {
+      timer.invalidate();
+    }
+
+    // Dropping the watcher closes its channel, which ends the reload thread.
+    drop(self.ivars().config_watcher.take());
+
+    if let Some(monitor) = self.ivars().hotkey_monitor.take() {
+      unsafe { NSEvent::removeMonitor(&monitor) };
+    }
+
+    unsafe { NSWorkspace::sharedWorkspace().notificationCenter().removeObserver(self) };
+
+    self.ivars().state.borrow_mut().store_history();
+    ipc::remove_socket();
+
+    NSStatusBar::systemStatusBar().removeStatusItem(&self.ivars().status_item);
+  }
+
+  /// Subscribes to fast user switching notifications, which NSWorkspace posts on its own center.
+  fn observe_session(&self) {
+    let center = NSWorkspace::sharedWorkspace().notificationCenter();
+
+    unsafe {
+      center.addObserver_selector_name_object(
+        self,
+        sel!(onSessionResignActive:),
+        Some(NSWorkspaceSessionDidResignActiveNotification),
+        None,
+      );
+      center.addObserver_selector_name_object(
+        self,
+        sel!(onSessionBecomeActive:),
+        Some(NSWorkspaceSessionDidBecomeActiveNotification),
+        None,
+      );
+    }
+  }
+
+  /// Pauses or resumes periodic refreshes. Resuming fetches right away.
+  pub fn set_paused(&self, paused: bool) {
+    if paused == self.ivars().paused.replace(paused) {
+      return;
+    }
+
+    log::info!("Periodic refresh {}", if paused { "paused" } else { "resumed" });
+
+    if paused {
+      self.rebuild_ui();
+    }
+    else {
+      self.refresh();
+    }
+  }
+
   /// Checks for updates on a background thread, updates state and menu when done.
   /// If `reopen_menu` is true, reopens the menu when an update is available.
   fn attempt_update(&self, reopen_menu: bool) {
@@ -395,30 +1102,147 @@ impl AppDelegate {
     }
   }
 
-  fn rebuild_ui(&self, data: Option<&UsageData>, profile: Option<&TierInfo>) {
+  /// Applies a fetch result to the app state, delivers the alerts it triggered and re-renders the UI.
+  fn finish_refresh(&self, mut fetch: FetchResult) {
+    let kind = self.ivars().provider().kind();
+    let config = self.ivars().config();
+
+    if let Some(data) = &mut fetch.data {
+      soft_limit::apply(data, &config.soft_limits);
+    }
+    if let Some(data) = &fetch.data {
+      let descriptor = self.ivars().provider().tray_descriptor(data, config.display_mode);
+      statusline::Snapshot::new(&descriptor, data.fetched_at).store();
+    }
+
+    let triggered = self.ivars().state.borrow_mut().apply_fetch(kind, fetch, &config);
+    self.ivars().state.borrow_mut().store_history();
+
+    // Another user is at the screen, their notifications shouldn't include ours.
+    if self.ivars().session_inactive.get() {
+      if !triggered.is_empty() {
+        log::info!("Session is inactive, dropping {} alert(s)", triggered.len());
+      }
+    }
+    else {
+      for alert in triggered {
+        alerts::send(&config.alerts, alert);
+      }
+    }
+
+    self.rebuild_ui();
+  }
+
+  /// Replaces the last fetch without sending its alerts, e.g. with synthetic data for `--bench-render`.
+  pub fn load_fetch(&self, fetch: FetchResult) {
+    let kind = self.ivars().provider().kind();
+    let config = self.ivars().config();
+
+    let _ = self.ivars().state.borrow_mut().apply_fetch(kind, fetch, &config);
+  }
+
+  /// Logo of the current provider, as configured in `icons`.
+  fn logo(&self) -> Retained<NSImage> {
+    let provider = self.ivars().provider();
+    let config = self.ivars().config();
+
+    return logo::provider_logo(provider.as_ref(), config.icons.get(&provider.kind()).map(String::as_str));
+  }
+
+  /// Image currently shown in the tray.
+  pub fn tray_image(&self) -> Option<Retained<NSImage>> {
+    return self.ivars().status_item.button(self.mtm())?.image();
+  }
+
+  /// Fills the menu from the last fetch.
+  fn populate(&self, menu: &NSMenu) {
+    let mtm = self.mtm();
+
+    let kind = self.ivars().provider().kind();
+    let state = self.ivars().state();
+    let view_state = state.view_state(kind);
+
+    match &view_state {
+      ViewState::Data { fetch, data } | ViewState::Stale { fetch, data, .. } => {
+        views::populate_menu(
+          menu,
+          mtm,
+          self,
+          &self.logo(),
+          data,
+          fetch.profile.as_ref(),
+          fetch.exchange_rate.as_ref(),
+          fetch.team.as_deref(),
+          fetch.secondary.as_ref(),
+        );
+      }
+      _ => views::populate_status_menu(menu, mtm, self, &view_state),
+    }
+  }
+
+  /// Menu built from the last fetch, apart from the tray's, e.g. for snapshots and `--dump-menu`.
+  pub fn build_menu(&self) -> Retained<NSMenu> {
+    return NSMenu::new(self.mtm()).tap(|menu| self.populate(menu));
+  }
+
+  /// Brings the menu up to date if it's open. Rebuilding it would make it jump or close, so a
+  /// fresh one is built aside and patched in. If its structure changed, it stays as is until the
+  /// next time it opens.
+  fn update_open_menu(&self) {
+    if !self.ivars().menu_open.get() {
+      return;
+    }
+
+    let Some(menu) = self.ivars().status_item.menu(self.mtm())
+    else {
+      return;
+    };
+
+    if !views::patch_open_menu(&menu, &self.build_menu()) {
+      log::debug!("Menu changed while open, it's updated when opened next");
+    }
+  }
+
+  /// Renders the tray from the last fetch and updates the menu if it's open. A closed menu is
+  /// filled right before it opens, in `menuNeedsUpdate:`.
+  pub fn rebuild_ui(&self) {
     let mtm = MainThreadMarker::from(self);
     let status_item = &self.ivars().status_item;
 
+    let kind = self.ivars().provider().kind();
+    let state = self.ivars().state();
+    let view_state = state.view_state(kind);
+    let data = match &view_state {
+      ViewState::Data { data, .. } | ViewState::Stale { data, .. } => Some(*data),
+      _ => None,
+    };
+
     let config = self.ivars().config();
-    let tray_icon_svg = self.ivars().provider().tray_icon_svg();
+    let logo = self.logo();
+
+    if let Some(tray_button) = status_item.button(mtm) {
+      let tooltip = views::tray_tooltip(kind, &view_state, config.display_mode);
+      tray_button.setToolTip(Some(&NSString::from_str(&tooltip)));
+    }
+
+    self.update_open_menu();
 
     let Some(data) = data
     else {
       if let Some(tray_button) = status_item.button(mtm) {
         let img = Self::build_tray_image(
-          tray_icon_svg,
+          &logo,
           TrayBucket {
             text: "-- --",
             utilization: 0.0,
             warn: false,
           },
-          TrayBucket {
+          Some(TrayBucket {
             text: "-- --",
             utilization: 0.0,
             warn: false,
-          },
-          config.monochrome_icon,
-          config.stats_colors,
+          }),
+          &config,
         );
 
         tray_button.setImage(Some(&img));
@@ -427,62 +1251,65 @@ impl AppDelegate {
     };
 
     if let Some(tray_button) = status_item.button(mtm) {
-      // Use first two windows that have a short_title for tray display.
-      let mut tray_windows = data.windows.iter().filter(|w| w.short_title.is_some());
-      let w0 = tray_windows.next();
-      let w1 = tray_windows.next();
-      let is_remaining = config.display_mode == DisplayMode::Remaining;
-      let u0 = w0.map(|w| w.utilization).unwrap_or(0.0);
-      let u1 = w1.map(|w| w.utilization).unwrap_or(0.0);
-      let p0 = if is_remaining { 100.0 - u0 } else { u0 };
-      let p1 = if is_remaining { 100.0 - u1 } else { u1 };
-
-      let v0 = p0 as i64;
-      let v1 = p1 as i64;
-      let w = (v0.max(1).ilog10() as usize + 1).max(v1.max(1).ilog10() as usize + 1);
-
-      let label0 = w0.and_then(|w| w.short_title.as_deref()).unwrap_or("--");
-      let label1 = w1.and_then(|w| w.short_title.as_deref()).unwrap_or("--");
-      let line1 = format!("{} {:>w$}%", label0, v0);
-      let line2 = format!("{} {:>w$}%", label1, v1);
+      let mut descriptor = self.ivars().provider().tray_descriptor(data, config.display_mode);
+
+      // The cycle hotkey can narrow the tray down to a single line.
+      let selection = self.ivars().tray_selection.get();
+      if selection > 0 && selection <= descriptor.lines.len() {
+        descriptor.lines = vec![descriptor.lines.swap_remove(selection - 1)];
+      }
+
+      let l0 = descriptor.lines.first();
+      let l1 = descriptor.lines.get(1);
+
+      // A single-line provider gets one centered line instead of a "--" placeholder.
+      let single_line = l0.is_some() && l1.is_none();
+
+      // Values are right-aligned to the same width.
+      let v0 = l0.map(|l| l.formatted_value()).unwrap_or_else(|| "0%".to_string());
+      let v1 = l1.map(|l| l.formatted_value()).unwrap_or_else(|| "0%".to_string());
+      let w = v0.chars().count().max(v1.chars().count());
+
+      let label0 = l0.map(|l| l.label.as_str()).unwrap_or("--");
+      let label1 = l1.map(|l| l.label.as_str()).unwrap_or("--");
+      let line1 = format!("{} {:>w$}", label0, v0);
+      let line2 = format!("{} {:>w$}", label1, v1);
 
       let tray_warn_enabled = config.show_tray_pacing_warning;
-      let warn0 = tray_warn_enabled && w0.is_some_and(|w| w.is_pacing_warning());
-      let warn1 = tray_warn_enabled && w1.is_some_and(|w| w.is_pacing_warning());
+      let warn0 = tray_warn_enabled && l0.is_some_and(|l| l.pacing_warning);
+      let warn1 = tray_warn_enabled && l1.is_some_and(|l| l.pacing_warning);
 
-      let u0 = u0 / 100.0;
-      let u1 = u1 / 100.0;
+      let u0 = l0.map(|l| l.utilization).unwrap_or(0.0);
+      let u1 = l1.map(|l| l.utilization).unwrap_or(0.0);
+      let bucket2 = if single_line {
+        None
+      }
+      else {
+        Some(TrayBucket {
+          text: &line2,
+          utilization: u1,
+          warn: warn1,
+        })
+      };
       let img = Self::build_tray_image(
-        tray_icon_svg,
+        &logo,
         TrayBucket {
           text: &line1,
           utilization: u0,
           warn: warn0,
         },
-        TrayBucket {
-          text: &line2,
-          utilization: u1,
-          warn: warn1,
-        },
-        config.monochrome_icon,
-        config.stats_colors,
+        bucket2,
+        &config,
       );
 
       tray_button.setImage(Some(&img));
     }
-
-    let menu = status_item.menu(mtm).unwrap_or_else(|| {
-      return objc2_app_kit::NSMenu::new(mtm).tap(|menu| {
-        status_item.setMenu(Some(menu));
-      });
-    });
-
-    views::populate_menu(&menu, mtm, self, data, profile);
   }
 
   /// Builds a two-line attributed string with per-line colors.
-  fn build_attributed_line(text: &str, p: f64, stats_colors: bool) -> Retained<NSAttributedString> {
-    let font = NSFont::monospacedSystemFontOfSize_weight(9.0, unsafe { NSFontWeightSemibold });
+  fn build_attributed_line(text: &str, p: f64, config: &Config) -> Retained<NSAttributedString> {
+    let font_size = config.tray.font_size.clamp(TRAY_FONT_SIZES.0, TRAY_FONT_SIZES.1);
+    let font = NSFont::monospacedSystemFontOfSize_weight(font_size, config.tray.font_weight.ns_weight());
     let str = NSString::from_str(text);
 
     let attr = unsafe { NSAttributedString::initWithString_attributes(NSAttributedString::alloc(), &str, None) };
@@ -492,7 +1319,15 @@ impl AppDelegate {
     // NSAttributedString indexes characters in UTF-16 code units, not bytes.
     let range = NSRange::new(0, text.encode_utf16().count());
 
-    let color = if stats_colors { Self::utilization_color(p) } else { NSColor::controlTextColor() };
+    let color = if config.stats_colors && config.tray.auto_contrast {
+      config.color_scale.contrast_utilization_color(p)
+    }
+    else if config.stats_colors {
+      config.color_scale.utilization_color(p)
+    }
+    else {
+      NSColor::controlTextColor()
+    };
     unsafe {
       result.addAttribute_value_range(NSFontAttributeName, &font, range);
       result.addAttribute_value_range(NSForegroundColorAttributeName, &color, range);
@@ -502,34 +1337,35 @@ impl AppDelegate {
     return Retained::into_super(result);
   }
 
-  /// Renders provider logo and two colored lines into an NSImage for the tray button.
+  /// Renders provider logo and two colored lines (or one, vertically centered) into an NSImage
+  /// for the tray button.
   /// Using an image instead of an attributed title allows macOS to properly
   /// dim the content on inactive displays via menu bar compositing.
   fn build_tray_image(
-    icon_svg: &'static [u8],
+    logo: &NSImage,
     bucket1: TrayBucket,
-    bucket2: TrayBucket,
-    monochrome_icon: bool,
-    stats_colors: bool,
+    bucket2: Option<TrayBucket>,
+    config: &Config,
   ) -> Retained<NSImage> {
     let TrayBucket {
       text: line1,
       utilization: p1,
       warn: warn1,
     } = bucket1;
+    let single_line = bucket2.is_none();
     let TrayBucket {
       text: line2,
       utilization: p2,
       warn: warn2,
-    } = bucket2;
+    } = bucket2.unwrap_or(TrayBucket { text: "", utilization: 0.0, warn: false });
 
-    let attr1 = Self::build_attributed_line(line1, p1, stats_colors);
-    let attr2 = Self::build_attributed_line(line2, p2, stats_colors);
+    let attr1 = Self::build_attributed_line(line1, p1, config);
+    let attr2 = Self::build_attributed_line(line2, p2, config);
 
     // Pre-build the warning character once if any line needs it; we use its measured size
     // to reserve space in the tray image layout and draw it in the block.
     let warn_attr: Option<Retained<NSAttributedString>> =
-      if warn1 || warn2 { Some(Self::build_warning_char()) } else { None };
+      if warn1 || warn2 { Some(Self::build_warning_char(config.color_scale)) } else { None };
     let warn_size = warn_attr.as_ref().map(|a| a.size()).unwrap_or(NSSize::new(0.0, 0.0));
     let warn_width = warn_size.width;
     let warn_height = warn_size.height;
@@ -543,12 +1379,15 @@ impl AppDelegate {
 
     // Find longest line width (text + optional trailing warning glyph).
     let text_width = (size1.width + tri_extra_1).max(size2.width + tri_extra_2).ceil();
-    let line_height = 10.0_f64;
+    let font_size = config.tray.font_size.clamp(TRAY_FONT_SIZES.0, TRAY_FONT_SIZES.1);
+    let line_height = font_size + 1.0;
     let text_height = line_height * 2.0;
 
-    // Logo size and padding.
-    let logo_size = 14.0_f64;
-    let logo_padding = 8.0_f64;
+    // Logo size and padding, none when the logo is hidden to save width.
+    let show_logo = config.tray.show_logo;
+    let logo_size = if show_logo { 14.0_f64 } else { 0.0 };
+    let logo_padding = if show_logo { 8.0_f64 } else { 0.0 };
+    let monochrome_icon = config.monochrome_icon;
 
     // Offset for text: logo width + x padding.
     let text_x = logo_size + logo_padding;
@@ -557,10 +1396,8 @@ impl AppDelegate {
     let (width, height) = (text_x + text_width, text_height);
     let image_size = NSSize::new(width, height);
 
-    // Load provider logo from embedded SVG.
-    let logo_data = unsafe { NSData::dataWithBytes_length(icon_svg.as_ptr() as *const c_void, icon_svg.len()) };
-    let logo_img = NSImage::initWithData(NSImage::alloc(), &logo_data).expect("failed to load provider logo");
-
+    // A copy, so resizing it doesn't affect the menu header's.
+    let logo_img = logo.copy();
     logo_img.setSize(NSSize::new(logo_size, logo_size));
 
     let size1_width = size1.width;
@@ -572,28 +1409,35 @@ impl AppDelegate {
     // keeps the visible glyph vertically centered in the line without clipping.
     let warn_y_overhang = (warn_height - line_height).max(0.0) / 2.0;
 
+    // A single line sits in the middle of the two-line block.
+    let line1_y = if single_line { line_height / 2.0 } else { line_height };
+
     let block = RcBlock::new(move |_rect: NSRect| -> Bool {
       // Draw logo on the left, vertically centered.
-      let logo_y = (height - logo_size) / 2.0;
-      let logo_rect = NSRect::new(CGPoint::new(0.0, logo_y), NSSize::new(logo_size, logo_size));
-      logo_img.drawInRect(logo_rect);
-
-      // Tint the logo to match the system text color by filling with SourceIn compositing,
-      // which replaces the color of non-transparent pixels while preserving alpha.
-      if monochrome_icon {
-        NSColor::controlTextColor().setFill();
-        NSRectFillUsingOperation(logo_rect, NSCompositingOperation::SourceIn);
+      if show_logo {
+        let logo_y = (height - logo_size) / 2.0;
+        let logo_rect = NSRect::new(CGPoint::new(0.0, logo_y), NSSize::new(logo_size, logo_size));
+        logo_img.drawInRect(logo_rect);
+
+        // Tint the logo to match the system text color by filling with SourceIn compositing,
+        // which replaces the color of non-transparent pixels while preserving alpha.
+        if monochrome_icon {
+          NSColor::controlTextColor().setFill();
+          NSRectFillUsingOperation(logo_rect, NSCompositingOperation::SourceIn);
+        }
       }
 
       // Draw text lines to the right of the logo.
-      attr1.drawAtPoint(CGPoint::new(text_x, line_height));
-      attr2.drawAtPoint(CGPoint::new(text_x, 0.0));
+      attr1.drawAtPoint(CGPoint::new(text_x, line1_y));
+      if !single_line {
+        attr2.drawAtPoint(CGPoint::new(text_x, 0.0));
+      }
 
-      // Draw a yellow warning glyph at the end of each line that needs one. The top line is
+      // Draw a warning glyph at the end of each line that needs one. The top line is
       // shifted down by the warn glyph's vertical overhang so its top edge fits in the image.
       if let Some(wa) = warn_attr.as_ref() {
         if warn1 {
-          wa.drawAtPoint(CGPoint::new(text_x + size1_width + TRI_PADDING, line_height - warn_y_overhang));
+          wa.drawAtPoint(CGPoint::new(text_x + size1_width + TRI_PADDING, line1_y - warn_y_overhang));
         }
         if warn2 {
           wa.drawAtPoint(CGPoint::new(text_x + size2_width + TRI_PADDING, 0.0));
@@ -608,9 +1452,9 @@ impl AppDelegate {
     return img;
   }
 
-  /// Builds a yellow, bold `⚠` attributed string used as the pacing-warning indicator
-  /// next to a tray line.
-  fn build_warning_char() -> Retained<NSAttributedString> {
+  /// Builds a bold `⚠` attributed string in the scale's warning color, used as the
+  /// pacing-warning indicator next to a tray line.
+  fn build_warning_char(color_scale: ColorScale) -> Retained<NSAttributedString> {
     let font = NSFont::systemFontOfSize_weight(11.0, unsafe { NSFontWeightSemibold });
     let str = NSString::from_str("⚠");
 
@@ -620,20 +1464,9 @@ impl AppDelegate {
     let range = NSRange::new(0, "⚠".encode_utf16().count());
     unsafe {
       result.addAttribute_value_range(NSFontAttributeName, &font, range);
-      result.addAttribute_value_range(NSForegroundColorAttributeName, &NSColor::yellowColor(), range);
+      result.addAttribute_value_range(NSForegroundColorAttributeName, &color_scale.warning_color(), range);
     }
 
     return Retained::into_super(result);
   }
-
-  /// Returns a system catalog color based on utilization level.
-  /// Uses catalog colors so macOS vibrancy compositing properly dims them on inactive displays.
-  fn utilization_color(pct: f64) -> Retained<NSColor> {
-    match pct {
-      p if p < 0.5 => NSColor::controlTextColor(),
-      p if p < 0.75 => NSColor::yellowColor(),
-      p if p < 0.90 => NSColor::orangeColor(),
-      _ => NSColor::redColor(),
-    }
-  }
 }
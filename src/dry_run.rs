@@ -0,0 +1,49 @@
+use color_eyre::eyre::Result;
+use strum::IntoEnumIterator as _;
+
+use crate::{CONFIG_PATH, config::Config, exchange_rate::EXCHANGE_RATE_URL, providers::ProviderKind, updater};
+
+/// Prints what the app would request with this config, and how often, without sending anything.
+pub fn print(config: &Config) -> Result<()> {
+  println!("Config: {}", *CONFIG_PATH);
+  println!(
+    "Refresh every {}s, at most {} fetches per hour",
+    config.refetch_interval, config.max_fetches_per_hour
+  );
+
+  for kind in ProviderKind::iter().filter(|kind| *kind != ProviderKind::Unknown) {
+    let selected = if kind == config.provider { " (selected)" } else { "" };
+    println!();
+    println!("{kind}{selected}");
+
+    match kind.planned_requests(&config.settings) {
+      Ok(requests) => {
+        for request in requests {
+          println!("  {request}");
+        }
+      }
+      Err(e) => println!("  Not configured: {e}"),
+    }
+  }
+
+  let mut other = Vec::new();
+  if config.check_updates {
+    other.push(format!("GET {}, on launch", updater::GITHUB_RELEASES_URL));
+  }
+  if config.currency.as_ref().is_some_and(|currency| currency.rate.is_none()) {
+    other.push(format!("GET {EXCHANGE_RATE_URL}, once a day"));
+  }
+  if let Some(team) = &config.team {
+    other.push(format!("POST {}, every refresh", team.url));
+  }
+
+  if !other.is_empty() {
+    println!();
+    println!("Other");
+    for request in other {
+      println!("  {request}");
+    }
+  }
+
+  return Ok(());
+}
@@ -0,0 +1,145 @@
+use std::{
+  collections::HashMap,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::http;
+
+const EXCHANGE_RATE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long a failed fetch waits before trying again, short so a network blip doesn't turn off
+/// conversion for a day.
+const FAILED_FETCH_TTL: Duration = Duration::from_secs(15 * 60);
+pub const EXCHANGE_RATE_URL: &str = "https://open.er-api.com/v6/latest/USD";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CurrencySettings {
+  /// ISO 4217 currency code to display spend in (e.g. "EUR", "JPY").
+  pub code: String,
+
+  /// Manual USD → `code` exchange rate. If not set, the rate is fetched once a day.
+  pub rate: Option<f64>,
+}
+
+/// Resolved conversion from USD into the display currency.
+#[derive(Clone)]
+pub struct ExchangeRate {
+  code: String,
+  rate: f64,
+}
+
+impl ExchangeRate {
+  /// Formats a USD amount in the display currency.
+  pub fn format(&self, usd: f64) -> String {
+    let amount = usd * self.rate;
+
+    return match self.code.as_str() {
+      "EUR" => format!("€{:.2}", amount),
+      "GBP" => format!("£{:.2}", amount),
+      "JPY" => format!("¥{:.0}", amount),
+      code => format!("{:.2} {}", amount, code),
+    };
+  }
+}
+
+/// Formats a USD amount, converting it if a display currency is configured.
+pub fn format_usd(usd: f64, exchange_rate: Option<&ExchangeRate>) -> String {
+  return match exchange_rate {
+    Some(rate) => rate.format(usd),
+    None => format!("${:.2}", usd),
+  };
+}
+
+struct CacheEntry {
+  code: String,
+
+  /// Last fetched rate, kept when a later fetch fails.
+  rate: Option<f64>,
+
+  /// When the rate was last fetched or failed to, so a failing API or unknown code isn't asked
+  /// again before the TTL passes.
+  last: Instant,
+
+  /// Whether the last fetch failed, which uses the shorter `FAILED_FETCH_TTL`.
+  failed: bool,
+}
+
+impl CacheEntry {
+  fn is_fresh(&self) -> bool {
+    let ttl = if self.failed { FAILED_FETCH_TTL } else { EXCHANGE_RATE_TTL };
+
+    return self.last.elapsed() < ttl;
+  }
+}
+
+#[derive(Default)]
+pub struct ExchangeRateCache(Mutex<Option<CacheEntry>>);
+
+impl ExchangeRateCache {
+  /// Returns the manual rate if configured, otherwise a cached rate refreshed once a day.
+  /// Falls back to a stale rate if refreshing fails, and tries again 15 minutes later. Performs a
+  /// blocking HTTP request.
+  pub fn resolve(&self, settings: &CurrencySettings) -> Option<ExchangeRate> {
+    let code = settings.code.to_ascii_uppercase();
+
+    if code == "USD" {
+      return None;
+    }
+
+    if let Some(rate) = settings.rate {
+      return Some(ExchangeRate { code, rate });
+    }
+
+    // Retrieve cached entry if exists and fresh.
+    let stale = {
+      let cache = self.0.lock().unwrap();
+      match cache.as_ref() {
+        Some(entry) if entry.code == code && entry.is_fresh() => {
+          return entry.rate.map(|rate| ExchangeRate { code, rate });
+        }
+        Some(entry) if entry.code == code => entry.rate,
+        _ => None,
+      }
+    };
+
+    let fetched = fetch_rate(&code);
+    let rate = fetched.or(stale);
+    *self.0.lock().unwrap() = Some(CacheEntry {
+      code: code.clone(),
+      rate,
+      last: Instant::now(),
+      failed: fetched.is_none(),
+    });
+
+    return rate.map(|rate| ExchangeRate { code, rate });
+  }
+}
+
+fn fetch_rate(code: &str) -> Option<f64> {
+  #[derive(Deserialize)]
+  struct RatesResponse {
+    rates: HashMap<String, f64>,
+  }
+
+  log::debug!("Fetching USD exchange rates");
+
+  let mut response = http::with_retries(|| http::call(ureq::get(EXCHANGE_RATE_URL)))
+    .inspect_err(|e| log::warn!("Failed to fetch exchange rates: {}", e))
+    .ok()?;
+
+  let body = http::read_body(&mut response)
+    .inspect_err(|e| log::warn!("Failed to read exchange rates: {}", e))
+    .ok()?;
+
+  let response: RatesResponse = serde_json::from_str(&body)
+    .inspect_err(|e| log::warn!("Failed to parse exchange rates: {}", e))
+    .ok()?;
+
+  return response.rates.get(code).copied().or_else(|| {
+    log::warn!("No exchange rate for currency {}", code);
+    None
+  });
+}
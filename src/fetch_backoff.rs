@@ -0,0 +1,113 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  providers::{ProviderError, ProviderKind},
+  utils::cache,
+};
+
+/// Cache file the backoff is persisted to, so a relaunch doesn't hit a failing endpoint again.
+const FETCH_BACKOFF_CACHE_FILE: &str = "fetch_backoff.json";
+
+/// Delay after the first failure, doubled for each further one up to `MAX_DOUBLINGS` times.
+const BASE_DELAY: SignedDuration = SignedDuration::from_secs(60);
+const MAX_DOUBLINGS: u32 = 4;
+
+/// Server errors in a row before backing off. A single one is usually a blip.
+const SERVER_ERROR_STREAK: u32 = 3;
+
+#[derive(Default, Clone, Deserialize, Serialize)]
+struct ProviderBackoff {
+  /// Failures in a row that back off.
+  consecutive_failures: u32,
+
+  /// When fetching is allowed again, and the failure that caused the wait.
+  until: Option<(Timestamp, ProviderError)>,
+}
+
+/// Stops fetching for a while after failures that retrying right away can't fix: rate limiting,
+/// rejected credentials and repeated server errors. Kept per provider and across restarts, shared
+/// with background threads.
+pub struct FetchBackoff(Mutex<HashMap<ProviderKind, ProviderBackoff>>);
+
+impl FetchBackoff {
+  pub fn load() -> Self {
+    let backoff: HashMap<ProviderKind, ProviderBackoff> = cache::load(FETCH_BACKOFF_CACHE_FILE).unwrap_or_default();
+
+    for (kind, state) in &backoff {
+      if let Some((until, error)) = state.until.as_ref().filter(|(until, _)| Timestamp::now() < *until) {
+        log::info!("Resuming backoff of {kind} from the last run until {until}: {error}");
+      }
+    }
+
+    return Self(Mutex::new(backoff));
+  }
+
+  /// The failure the provider is backing off from, if it still is.
+  pub fn active(&self, kind: ProviderKind) -> Option<ProviderError> {
+    let backoff = self.0.lock().unwrap();
+    let (until, error) = backoff.get(&kind)?.until.as_ref()?;
+
+    return (Timestamp::now() < *until).then(|| error.clone());
+  }
+
+  /// Updates the provider's backoff with the outcome of a fetch.
+  pub fn record(&self, kind: ProviderKind, result: Result<(), &ProviderError>) {
+    let mut backoff = self.0.lock().unwrap();
+    let state = backoff.entry(kind).or_default();
+
+    let error = match result {
+      Ok(()) if state.consecutive_failures == 0 => return,
+      Ok(()) => {
+        log::info!("Fetch succeeded, resetting backoff of {kind}");
+        *state = ProviderBackoff::default();
+        cache::store(FETCH_BACKOFF_CACHE_FILE, &*backoff);
+        return;
+      }
+      // Offline or an unexpected response, the regular interval is slow enough.
+      Err(ProviderError::Network(_) | ProviderError::Parse(_)) => return,
+      Err(error) => error,
+    };
+
+    state.consecutive_failures += 1;
+    if matches!(error, ProviderError::Server(_)) && state.consecutive_failures < SERVER_ERROR_STREAK {
+      return;
+    }
+
+    let delay = BASE_DELAY * 2i32.pow(state.consecutive_failures.saturating_sub(1).min(MAX_DOUBLINGS));
+    let mut until = Timestamp::now() + delay;
+
+    // Wait at least as long as the API asked to.
+    if let ProviderError::RateLimited(Some(limited_until)) = error {
+      until = until.max(*limited_until);
+    }
+
+    log::warn!("Backing off {kind} until {until} after {} failure(s): {error}", state.consecutive_failures);
+
+    let error = match error {
+      ProviderError::RateLimited(_) => ProviderError::RateLimited(Some(until)),
+      error => error.clone(),
+    };
+    state.until = Some((until, error));
+    cache::store(FETCH_BACKOFF_CACHE_FILE, &*backoff);
+  }
+
+  /// Lets the provider fetch right away, unless it's rate limited, where fetching would only
+  /// extend the limit. For manual refreshes and changed credentials.
+  pub fn forgive(&self, kind: ProviderKind) {
+    let mut backoff = self.0.lock().unwrap();
+    let Some(state) = backoff.get_mut(&kind)
+    else {
+      return;
+    };
+
+    if state.until.as_ref().is_none_or(|(_, error)| matches!(error, ProviderError::RateLimited(_))) {
+      return;
+    }
+
+    state.until = None;
+    cache::store(FETCH_BACKOFF_CACHE_FILE, &*backoff);
+  }
+}
@@ -0,0 +1,79 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use crate::providers::ProviderKind;
+
+/// Fetches slower than this count towards a latency degradation warning.
+const SLOW_FETCH: Duration = Duration::from_secs(5);
+
+/// Consecutive slow fetches before warning about degraded latency.
+const SLOW_STREAK_WARNING: u32 = 3;
+
+/// Smoothing factor for the latency moving average.
+const EWMA_ALPHA: f64 = 0.3;
+
+#[derive(Default, Clone)]
+pub struct ProviderMetrics {
+  /// Total fetches since launch.
+  pub fetches: u32,
+
+  /// Fetches that returned no data.
+  pub failures: u32,
+
+  /// Latency of the most recent fetch.
+  pub last_latency: Duration,
+
+  /// Exponentially weighted moving average of fetch latency.
+  pub average_latency: Duration,
+
+  /// Consecutive fetches slower than `SLOW_FETCH`.
+  slow_streak: u32,
+}
+
+/// In-process fetch latency and failure counters per provider, shared with background threads.
+#[derive(Default)]
+pub struct FetchMetrics(Mutex<HashMap<ProviderKind, ProviderMetrics>>);
+
+impl FetchMetrics {
+  /// Records the outcome of a single `fetch_data` call.
+  pub fn record(&self, kind: ProviderKind, latency: Duration, success: bool) {
+    let mut metrics = self.0.lock().unwrap();
+    let entry = metrics.entry(kind).or_default();
+
+    entry.average_latency = if entry.fetches == 0 {
+      latency
+    }
+    else {
+      entry.average_latency.mul_f64(1.0 - EWMA_ALPHA) + latency.mul_f64(EWMA_ALPHA)
+    };
+
+    entry.fetches += 1;
+    entry.last_latency = latency;
+
+    if !success {
+      entry.failures += 1;
+    }
+
+    if latency < SLOW_FETCH {
+      entry.slow_streak = 0;
+      return;
+    }
+
+    entry.slow_streak += 1;
+
+    // Warn once per streak rather than on every slow fetch.
+    if entry.slow_streak == SLOW_STREAK_WARNING {
+      log::warn!(
+        "{} fetch latency degraded: {} consecutive fetches over {}s (average {}ms)",
+        kind,
+        entry.slow_streak,
+        SLOW_FETCH.as_secs(),
+        entry.average_latency.as_millis()
+      );
+    }
+  }
+
+  /// Returns a snapshot of the metrics for the given provider.
+  pub fn get(&self, kind: ProviderKind) -> Option<ProviderMetrics> {
+    return self.0.lock().unwrap().get(&kind).cloned();
+  }
+}
@@ -0,0 +1,48 @@
+use jiff::Timestamp;
+
+use crate::providers::UsageData;
+
+/// How much the tightest tray window leaves to use right now, so one number answers "can I keep going?".
+pub struct EffectiveHeadroom {
+  /// Tray label of the window that binds (e.g. "7d").
+  pub window: String,
+
+  /// Headroom in percent (0–100). 100 means on or under an even pace in every window, 0 means a
+  /// window is exhausted.
+  pub headroom: f64,
+}
+
+impl EffectiveHeadroom {
+  /// Compares the tray windows by what's left relative to the time left in each: 30% left of a 7d
+  /// window with 3 days to go is tighter than 20% left of a 5h window that resets in an hour.
+  /// Returns `None` unless there are at least two windows with a period and reset time.
+  pub fn compute(data: &UsageData) -> Option<Self> {
+    let now = Timestamp::now().as_second();
+
+    let candidates = data
+      .windows
+      .iter()
+      .filter(|w| w.short_title.is_some())
+      .filter_map(|window| {
+        let period = window.period_seconds.filter(|p| *p > 0)?;
+        let remaining_seconds = (window.resets_at?.as_second() - now).clamp(0, period);
+        let time_left = remaining_seconds as f64 / period as f64;
+
+        // What's left compared to an even pace over the rest of the period.
+        let left = (100.0 - window.utilization).max(0.0);
+        let headroom = if time_left > 0.0 { (left / time_left).min(100.0) } else { 100.0 };
+
+        let label = window.short_title.clone().unwrap_or_else(|| window.title.clone());
+        return Some((label, headroom));
+      })
+      .collect::<Vec<_>>();
+
+    if candidates.len() < 2 {
+      return None;
+    }
+
+    let (window, headroom) = candidates.into_iter().min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    return Some(Self { window, headroom });
+  }
+}
@@ -0,0 +1,181 @@
+use std::{
+  io::Write as _,
+  sync::{LazyLock, Mutex},
+};
+
+use camino::Utf8PathBuf;
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::providers::{ProviderKind, UsageData};
+
+/// Every fetched snapshot, one JSON object per line, for trends and anything built on them.
+pub static HISTORY_PATH: LazyLock<Utf8PathBuf> = LazyLock::new(|| {
+  let data_dir = etcetera::base_strategy::Xdg::new()
+    .ok()
+    .and_then(|x| Utf8PathBuf::try_from(etcetera::BaseStrategy::data_dir(&x)).ok())
+    .unwrap_or_else(|| Utf8PathBuf::from("~/.local/share"));
+
+  return data_dir.join("liment").join("history.jsonl");
+});
+
+/// How long snapshots are kept on disk.
+const RETENTION: SignedDuration = SignedDuration::from_hours(90 * 24);
+
+/// How far back snapshots are kept in memory for trends.
+pub const RECENT_SPAN: SignedDuration = SignedDuration::from_hours(24);
+
+/// How much older than the requested time a snapshot may be to stand in for it.
+const LOOKUP_TOLERANCE: SignedDuration = SignedDuration::from_mins(15);
+
+/// Serializes appends from background threads, so lines from two writes don't interleave.
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Deserialize, Serialize)]
+pub struct Snapshot {
+  pub at: Timestamp,
+  pub provider: ProviderKind,
+
+  /// Id of the account the usage belongs to, if the provider identifies it.
+  #[serde(default)]
+  pub account: Option<String>,
+
+  pub windows: Vec<WindowSnapshot>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WindowSnapshot {
+  pub title: String,
+  pub utilization: f64,
+  pub resets_at: Option<Timestamp>,
+}
+
+/// Fetched usage over time, appended to `HISTORY_PATH` with the last day kept in memory.
+#[derive(Default)]
+pub struct History {
+  recent: Vec<Snapshot>,
+
+  /// Snapshots not written to disk yet.
+  pending: usize,
+}
+
+impl History {
+  /// Reads the last day from disk, dropping snapshots past the retention period from the file.
+  pub fn load() -> Self {
+    let Ok(contents) = fs_err::read_to_string(&*HISTORY_PATH)
+    else {
+      return Self::default();
+    };
+
+    let now = Timestamp::now();
+    let lines: Vec<&str> = contents.lines().collect();
+    let snapshots: Vec<Snapshot> = lines
+      .iter()
+      .filter_map(|line| {
+        return serde_json::from_str(line).inspect_err(|e| log::warn!("Skipping history entry: {e}")).ok();
+      })
+      .collect();
+
+    let kept: Vec<Snapshot> = snapshots.into_iter().filter(|s| now.duration_since(s.at) <= RETENTION).collect();
+    if kept.len() < lines.len() {
+      let contents: String =
+        kept.iter().filter_map(|s| serde_json::to_string(s).ok()).map(|line| line + "\n").collect();
+      if let Err(e) = fs_err::write(&*HISTORY_PATH, contents) {
+        log::warn!("Failed to prune history: {e}");
+      }
+    }
+
+    let recent = kept.into_iter().filter(|s| now.duration_since(s.at) <= RECENT_SPAN).collect();
+
+    return Self { recent, pending: 0 };
+  }
+
+  /// Adds a fetched snapshot. It's written to disk by [`History::store`].
+  pub fn record(&mut self, provider: ProviderKind, account: Option<&str>, data: &UsageData) {
+    let windows = data
+      .windows
+      .iter()
+      .map(|w| {
+        return WindowSnapshot {
+          title: w.title.clone(),
+          utilization: w.utilization,
+          resets_at: w.resets_at,
+        };
+      })
+      .collect();
+
+    self.recent.push(Snapshot {
+      at: data.fetched_at,
+      provider,
+      account: account.map(str::to_string),
+      windows,
+    });
+    self.pending += 1;
+
+    let now = Timestamp::now();
+    self.recent.retain(|s| now.duration_since(s.at) <= RECENT_SPAN);
+    self.pending = self.pending.min(self.recent.len());
+  }
+
+  /// Appends snapshots recorded since the last call to the file, on a background thread.
+  pub fn store(&mut self) {
+    if self.pending == 0 {
+      return;
+    }
+
+    let lines: String = self.recent[self.recent.len() - self.pending ..]
+      .iter()
+      .filter_map(|s| serde_json::to_string(s).ok())
+      .map(|line| line + "\n")
+      .collect();
+
+    std::thread::spawn(move || {
+      let _guard = APPEND_LOCK.lock().unwrap();
+      if let Err(e) = append(&lines) {
+        log::warn!("Failed to write history: {e}");
+      }
+    });
+
+    self.pending = 0;
+  }
+
+  /// Utilization of an account's window over the last day, oldest first.
+  pub fn series(&self, provider: ProviderKind, account: Option<&str>, title: &str) -> Vec<(Timestamp, f64)> {
+    return self
+      .recent
+      .iter()
+      .filter(|s| s.provider == provider && s.account.as_deref() == account)
+      .filter_map(|s| s.windows.iter().find(|w| w.title == title).map(|w| (s.at, w.utilization)))
+      .collect();
+  }
+
+  /// Utilization of an account's window at the given time, from the last snapshot taken at most
+  /// `LOOKUP_TOLERANCE` before it.
+  pub fn utilization_at(
+    &self,
+    provider: ProviderKind,
+    account: Option<&str>,
+    title: &str,
+    at: Timestamp,
+  ) -> Option<f64> {
+    let snapshot = self
+      .recent
+      .iter()
+      .rev()
+      .filter(|s| s.provider == provider && s.account.as_deref() == account)
+      .find(|s| s.at <= at)
+      .filter(|s| at.duration_since(s.at) <= LOOKUP_TOLERANCE)?;
+
+    return snapshot.windows.iter().find(|w| w.title == title).map(|w| w.utilization);
+  }
+}
+
+fn append(lines: &str) -> std::io::Result<()> {
+  if let Some(dir) = HISTORY_PATH.parent() {
+    fs_err::create_dir_all(dir)?;
+  }
+
+  let mut file = fs_err::OpenOptions::new().create(true).append(true).open(&*HISTORY_PATH)?;
+
+  return file.write_all(lines.as_bytes());
+}
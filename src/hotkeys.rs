@@ -0,0 +1,62 @@
+use std::ptr::NonNull;
+
+use block2::RcBlock;
+use objc2::{rc::Retained, runtime::AnyObject};
+use objc2_app_kit::{NSEvent, NSEventMask, NSEventModifierFlags};
+
+use crate::delegate::AppDelegate;
+
+/// A global keyboard shortcut, e.g. "ctrl+option+d".
+pub struct Hotkey {
+  modifiers: NSEventModifierFlags,
+  key: String,
+}
+
+impl Hotkey {
+  /// Parses "modifier+…+key", with cmd, ctrl, option (or alt) and shift as modifiers. Returns
+  /// `None` for an empty string, which disables the hotkey, or for unknown modifiers.
+  pub fn parse(s: &str) -> Option<Self> {
+    let mut parts = s.split('+').map(|p| p.trim().to_lowercase()).collect::<Vec<_>>();
+    let key = parts.pop().filter(|k| !k.is_empty())?;
+
+    let mut modifiers = NSEventModifierFlags::empty();
+    for part in parts {
+      modifiers |= match part.as_str() {
+        "cmd" | "command" => NSEventModifierFlags::Command,
+        "ctrl" | "control" => NSEventModifierFlags::Control,
+        "option" | "alt" => NSEventModifierFlags::Option,
+        "shift" => NSEventModifierFlags::Shift,
+        other => {
+          log::warn!("Unknown modifier {other:?} in hotkey {s:?}");
+          return None;
+        }
+      };
+    }
+
+    return Some(Self { modifiers, key });
+  }
+
+  pub fn matches(&self, event: &NSEvent) -> bool {
+    let relevant = NSEventModifierFlags::Command
+      | NSEventModifierFlags::Control
+      | NSEventModifierFlags::Option
+      | NSEventModifierFlags::Shift;
+
+    if event.modifierFlags() & relevant != self.modifiers {
+      return false;
+    }
+
+    return event.charactersIgnoringModifiers().is_some_and(|c| c.to_string().to_lowercase() == self.key);
+  }
+}
+
+/// Watches key presses in other apps for the configured hotkeys. macOS only delivers them once
+/// liment is allowed under Privacy & Security → Accessibility.
+pub fn monitor(delegate: &Retained<AppDelegate>) -> Option<Retained<AnyObject>> {
+  let delegate = delegate.clone();
+  let handler = RcBlock::new(move |event: NonNull<NSEvent>| {
+    delegate.handle_hotkey(unsafe { event.as_ref() });
+  });
+
+  return NSEvent::addGlobalMonitorForEventsMatchingMask_handler(NSEventMask::KeyDown, &handler);
+}
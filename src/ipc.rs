@@ -0,0 +1,263 @@
+use std::{
+  io::{BufRead as _, BufReader, Write as _},
+  os::unix::net::{UnixListener, UnixStream},
+  sync::{Arc, LazyLock},
+  time::{Duration, Instant},
+};
+
+use camino::Utf8PathBuf;
+use color_eyre::eyre::{Context as _, Report, Result};
+use dispatch2::{DispatchQueue, MainThreadBound};
+use jiff::Timestamp;
+use objc2::{MainThreadMarker, rc::Retained};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator as _;
+
+use crate::{
+  alerts,
+  delegate::AppDelegate,
+  providers::{ProviderKind, UsageData},
+  utils::cache::CACHE_DIR,
+};
+
+/// Socket local tools (e.g. the Claude Code hook in `scripts/claude-hook.sh`) use to reach the running app.
+pub static SOCKET_PATH: LazyLock<Utf8PathBuf> = LazyLock::new(|| CACHE_DIR.join("liment.sock"));
+
+/// Hooks can fire after every tool call, so refresh requests closer together than this are dropped.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Listens on the socket for one-line commands and answers each with one line:
+/// - `refresh`: fetch now.
+/// - `get-json`: the last fetch as JSON.
+/// - `pause` / `resume`: stop and restart periodic refreshes.
+/// - `notify-test`: send a test alert to every configured channel.
+pub fn listen(delegate: &Retained<AppDelegate>, mtm: MainThreadMarker) -> Result<()> {
+  fs_err::create_dir_all(&*CACHE_DIR)?;
+
+  // A socket left behind by a previous instance would make bind fail.
+  if fs_err::exists(&*SOCKET_PATH).unwrap_or(false) {
+    fs_err::remove_file(&*SOCKET_PATH)?;
+  }
+
+  let listener = UnixListener::bind(&*SOCKET_PATH).with_context(|| format!("Failed to bind {}", *SOCKET_PATH))?;
+  let delegate = Arc::new(MainThreadBound::new(delegate.clone(), mtm));
+
+  std::thread::spawn(move || {
+    let mut last_refresh: Option<Instant> = None;
+
+    for stream in listener.incoming() {
+      let result = stream.map_err(Report::from).and_then(|stream| {
+        return handle_connection(stream, &delegate, &mut last_refresh);
+      });
+
+      if let Err(e) = result {
+        log::warn!("IPC error: {e:#}");
+      }
+    }
+  });
+
+  return Ok(());
+}
+
+fn handle_connection(
+  stream: UnixStream,
+  delegate: &Arc<MainThreadBound<Retained<AppDelegate>>>,
+  last_refresh: &mut Option<Instant>,
+) -> Result<()> {
+  stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+
+  let mut command = String::new();
+  BufReader::new(&stream).read_line(&mut command)?;
+
+  // Nothing sent before hanging up, e.g. `is_running` checking for the app.
+  if command.trim().is_empty() {
+    return Ok(());
+  }
+
+  let reply = match command.trim() {
+    "refresh" => {
+      if last_refresh.is_some_and(|at| at.elapsed() < MIN_REFRESH_INTERVAL) {
+        log::debug!("IPC refresh ignored, last one was less than {}s ago", MIN_REFRESH_INTERVAL.as_secs());
+      }
+      else {
+        log::info!("IPC refresh requested");
+        *last_refresh = Some(Instant::now());
+        on_main(delegate, |delegate| delegate.refresh());
+      }
+
+      "ok".to_string()
+    }
+
+    "get-json" => {
+      let mut report = None;
+      DispatchQueue::main().exec_sync(|| {
+        let mtm = MainThreadMarker::new().expect("Must be on main thread");
+        report = Some(UsageReport::new(delegate.get(mtm)));
+      });
+
+      serde_json::to_string(&report)?
+    }
+
+    "pause" => {
+      on_main(delegate, |delegate| delegate.set_paused(true));
+      "ok".to_string()
+    }
+
+    "resume" => {
+      on_main(delegate, |delegate| delegate.set_paused(false));
+      "ok".to_string()
+    }
+
+    "notify-test" => {
+      on_main(delegate, |delegate| alerts::send_test(&delegate.ivars().config().alerts));
+      "ok".to_string()
+    }
+
+    other => {
+      log::warn!("Unknown IPC command: {other:?}");
+      format!("unknown command: {other}")
+    }
+  };
+
+  (&stream).write_all(format!("{reply}\n").as_bytes())?;
+
+  return Ok(());
+}
+
+/// Runs `work` with the delegate on the main thread, without waiting for it.
+fn on_main(delegate: &Arc<MainThreadBound<Retained<AppDelegate>>>, work: impl FnOnce(&AppDelegate) + Send + 'static) {
+  let delegate = Arc::clone(delegate);
+  DispatchQueue::main().exec_async(move || {
+    let mtm = MainThreadMarker::new().expect("Must be on main thread");
+    work(delegate.get(mtm));
+  });
+}
+
+/// Removes the socket on shutdown, so clients fail right away instead of connecting to nothing.
+pub fn remove_socket() {
+  if let Err(e) = fs_err::remove_file(&*SOCKET_PATH) {
+    log::debug!("Failed to remove IPC socket: {e}");
+  }
+}
+
+/// Whether an app is listening on the socket. Connects without sending a command, which the app
+/// ignores.
+pub fn is_running() -> bool {
+  return UnixStream::connect(&*SOCKET_PATH).is_ok();
+}
+
+/// Sends a command to the running app and returns its reply.
+pub fn request(command: &str) -> Result<String> {
+  let mut stream = UnixStream::connect(&*SOCKET_PATH)
+    .with_context(|| format!("Failed to connect to {}, is liment running?", *SOCKET_PATH))?;
+  stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+  stream.write_all(format!("{command}\n").as_bytes())?;
+
+  let mut reply = String::new();
+  BufReader::new(&stream).read_line(&mut reply)?;
+
+  return Ok(reply.trim_end().to_string());
+}
+
+/// Version of the `get-json` output. Fields may be added within a version, it's bumped when one
+/// is removed, renamed or changes meaning.
+pub const USAGE_REPORT_VERSION: u32 = 1;
+
+/// Last fetch as returned by `get-json`.
+#[derive(Deserialize, Serialize)]
+pub struct UsageReport {
+  pub version: u32,
+  pub provider: ProviderKind,
+  pub paused: bool,
+  pub fetched_at: Option<Timestamp>,
+  pub source: Option<String>,
+  pub windows: Vec<WindowReport>,
+  pub extra_usage_usd: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WindowReport {
+  pub title: String,
+  pub utilization: f64,
+  pub resets_at: Option<Timestamp>,
+}
+
+impl UsageReport {
+  fn new(delegate: &AppDelegate) -> Self {
+    let ivars = delegate.ivars();
+    let state = ivars.state();
+    let data = state.last_fetch().and_then(|fetch| fetch.data.as_ref());
+
+    return Self::from_data(ivars.provider().kind(), ivars.is_paused(), data);
+  }
+
+  pub fn from_data(provider: ProviderKind, paused: bool, data: Option<&UsageData>) -> Self {
+    let windows = data
+      .map(|data| {
+        return data
+          .windows
+          .iter()
+          .map(|window| {
+            return WindowReport {
+              title: window.title.clone(),
+              utilization: window.utilization,
+              resets_at: window.resets_at,
+            };
+          })
+          .collect();
+      })
+      .unwrap_or_default();
+
+    return Self {
+      version: USAGE_REPORT_VERSION,
+      provider,
+      paused,
+      fetched_at: data.map(|data| data.fetched_at),
+      source: data.map(|data| data.source.clone()),
+      windows,
+      extra_usage_usd: data.and_then(|data| data.api_usage.as_ref()).map(|api_usage| api_usage.usage_usd),
+    };
+  }
+}
+
+/// JSON Schema of `UsageReport`, printed by `liment schema` for integrations to validate against.
+/// Keep it in sync with the structs above.
+pub fn usage_report_schema() -> serde_json::Value {
+  let providers = ProviderKind::iter().map(|kind| serde_json::to_value(kind).unwrap_or_default()).collect::<Vec<_>>();
+
+  return serde_json::json!({
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "title": "liment usage report",
+    "description": "Last fetch, as returned by `liment ctl get-json`.",
+    "type": "object",
+    "required": ["version", "provider", "paused", "fetched_at", "source", "windows", "extra_usage_usd"],
+    "properties": {
+      "version": {
+        "const": USAGE_REPORT_VERSION,
+        "description": "Bumped when a field is removed, renamed or changes meaning. New fields may appear within a version."
+      },
+      "provider": { "enum": providers },
+      "paused": { "type": "boolean", "description": "Whether periodic refreshes are paused." },
+      "fetched_at": {
+        "type": ["string", "null"],
+        "format": "date-time",
+        "description": "When the data was fetched, null before the first successful fetch."
+      },
+      "source": { "type": ["string", "null"], "description": "Where the data came from, e.g. the API base URL." },
+      "windows": {
+        "type": "array",
+        "items": {
+          "type": "object",
+          "required": ["title", "utilization", "resets_at"],
+          "properties": {
+            "title": { "type": "string" },
+            "utilization": { "type": "number", "minimum": 0, "description": "Percent used, above 100 when over the limit." },
+            "resets_at": { "type": ["string", "null"], "format": "date-time" }
+          }
+        }
+      },
+      "extra_usage_usd": { "type": ["number", "null"], "description": "Extra usage spent this month, in USD." }
+    }
+  });
+}
@@ -0,0 +1,66 @@
+use camino::Utf8PathBuf;
+use color_eyre::eyre::{Context as _, Result};
+
+/// launchd label, same as the bundle identifier.
+const LABEL: &str = "fish.stupid.liment";
+
+pub fn plist_path() -> Result<Utf8PathBuf> {
+  let home = Utf8PathBuf::try_from(etcetera::home_dir()?).context("Home directory is not valid UTF-8")?;
+
+  return Ok(home.join("Library").join("LaunchAgents").join(format!("{LABEL}.plist")));
+}
+
+/// Writes a launchd agent that starts the current executable at login.
+pub fn install() -> Result<()> {
+  let exe = Utf8PathBuf::try_from(std::env::current_exe().context("Failed to get current exe")?)
+    .context("Exe path is not valid UTF-8")?;
+
+  // Escape the only XML-special characters that can appear in a path.
+  let exe = exe.as_str().replace('&', "&amp;").replace('<', "&lt;");
+
+  let plist = format!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>Label</key>
+  <string>{LABEL}</string>
+  <key>ProgramArguments</key>
+  <array>
+    <string>{exe}</string>
+  </array>
+  <key>RunAtLoad</key>
+  <true/>
+  <key>LimitLoadToSessionType</key>
+  <string>Aqua</string>
+  <key>ProcessType</key>
+  <string>Interactive</string>
+</dict>
+</plist>
+"#
+  );
+
+  let path = plist_path()?;
+  if let Some(dir) = path.parent() {
+    fs_err::create_dir_all(dir)?;
+  }
+  fs_err::write(&path, plist)?;
+
+  log::info!("Installed launch agent: {path}, liment will start at login");
+
+  return Ok(());
+}
+
+/// Removes the launch agent written by [`install`].
+pub fn uninstall() -> Result<()> {
+  let path = plist_path()?;
+  if !fs_err::exists(&path)? {
+    log::info!("No launch agent installed at {path}");
+    return Ok(());
+  }
+
+  fs_err::remove_file(&path)?;
+  log::info!("Removed launch agent: {path}");
+
+  return Ok(());
+}
@@ -0,0 +1,90 @@
+use jiff::{SignedDuration, Timestamp};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::cache;
+
+const LIMIT_HISTORY_CACHE_FILE: &str = "limit_history.json";
+
+/// How far back limit hits are kept and counted.
+const HISTORY_SPAN: SignedDuration = SignedDuration::from_hours(7 * 24);
+
+/// Hits of the same window within the span before suggesting another plan.
+pub const PLAN_HINT_MIN_HITS: usize = 3;
+
+#[derive(Deserialize, Serialize)]
+struct LimitHit {
+  /// Tray label or title of the window.
+  window: String,
+
+  /// When the exhausted period ended, to count each period once across restarts.
+  resets_at: Timestamp,
+  at: Timestamp,
+}
+
+/// Windows that reached 100% over the last week, kept across restarts to tell a one-off from a
+/// plan that's too small.
+#[derive(Default, Deserialize, Serialize)]
+pub struct LimitHistory {
+  hits: Vec<LimitHit>,
+
+  #[serde(skip)]
+  changed: bool,
+}
+
+impl LimitHistory {
+  pub fn load() -> Self {
+    return cache::load(LIMIT_HISTORY_CACHE_FILE).unwrap_or_default();
+  }
+
+  /// Writes the history to the cache if a hit was recorded since the last call.
+  pub fn store(&mut self) {
+    if !self.changed {
+      return;
+    }
+
+    cache::store(LIMIT_HISTORY_CACHE_FILE, self);
+    self.changed = false;
+  }
+
+  /// Forgets every hit, e.g. when another account is monitored.
+  pub fn clear(&mut self) {
+    self.hits.clear();
+    self.changed = true;
+  }
+
+  /// Records that a window was exhausted, once per window period.
+  pub fn record(&mut self, window: &str, resets_at: Timestamp) {
+    // Reset times jitter by a few seconds between fetches.
+    let same_period = |hit: &LimitHit| (hit.resets_at.as_second() - resets_at.as_second()).abs() < 60;
+    if self.hits.iter().any(|hit| hit.window == window && same_period(hit)) {
+      return;
+    }
+
+    let now = Timestamp::now();
+    self.hits.push(LimitHit {
+      window: window.to_string(),
+      resets_at,
+      at: now,
+    });
+    self.hits.retain(|hit| now.duration_since(hit.at) <= HISTORY_SPAN);
+    self.changed = true;
+  }
+
+  /// Windows hit at least `PLAN_HINT_MIN_HITS` times over the last week, with their hit count,
+  /// in the order they were first hit.
+  pub fn frequent(&self) -> Vec<(&str, usize)> {
+    let now = Timestamp::now();
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+
+    for hit in self.hits.iter().filter(|hit| now.duration_since(hit.at) <= HISTORY_SPAN) {
+      match counts.iter_mut().find(|(window, _)| *window == hit.window) {
+        Some((_, count)) => *count += 1,
+        None => counts.push((&hit.window, 1)),
+      }
+    }
+
+    counts.retain(|(_, count)| *count >= PLAN_HINT_MIN_HITS);
+
+    return counts;
+  }
+}
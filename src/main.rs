@@ -1,7 +1,7 @@
 use std::{process::Command, sync::LazyLock};
 
 use camino::Utf8PathBuf;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use color_eyre::eyre::{Context as _, ContextCompat as _, Result};
 use figment2::{
   Figment,
@@ -12,19 +12,46 @@ use objc2_app_kit::{NSApplication, NSApplicationActivationPolicy};
 
 use crate::{config::Config, delegate::AppDelegate, watcher::watch_config};
 
+mod alerts;
+mod app_state;
+mod bench;
+mod budget_week;
 mod config;
 mod constants;
 mod delegate;
+mod dry_run;
+mod exchange_rate;
+mod fetch_backoff;
+mod fetch_metrics;
+mod headroom;
+mod history;
+mod hotkeys;
+mod ipc;
+mod launch_agent;
+mod limit_history;
+mod morning_summary;
 mod profile_cache;
 mod providers;
+mod request_guard;
+mod session_plan;
+mod soft_limit;
+mod spend_forecast;
+mod status;
+mod statusline;
+mod team_sync;
 mod ui;
+mod uninstall;
 mod updater;
 mod utils;
 mod watcher;
+mod weekly_goal;
 
 #[derive(Parser)]
 #[command()]
 struct CliArgs {
+  #[command(subcommand)]
+  command: Option<CliCommand>,
+
   /// Open the configuration file in the default text editor.
   #[arg(long)]
   open_config: bool,
@@ -36,26 +63,131 @@ struct CliArgs {
   /// Ad-hoc codesign the current executable and restart.
   #[arg(long)]
   self_sign: bool,
+
+  /// Log HTTP request/response metadata to a separate trace file in the logs directory.
+  #[arg(long)]
+  trace_http: bool,
+
+  /// Render the tray and menu this many times from synthetic usage, print timings and exit.
+  #[arg(long, value_name = "N")]
+  bench_render: Option<usize>,
+
+  /// Print the providers, the requests each would make and how often, then exit without sending any.
+  #[arg(long)]
+  dry_run: bool,
+
+  /// Print the menu built from synthetic usage as a text tree and exit, e.g. to diff layout changes.
+  #[arg(long)]
+  dump_menu: bool,
 }
 
-static CONFIG_PATH: LazyLock<Utf8PathBuf> = LazyLock::new(|| {
-  let config_dir = etcetera::base_strategy::Xdg::new()
-    .ok()
-    .and_then(|s| Utf8PathBuf::try_from(etcetera::BaseStrategy::config_dir(&s)).ok())
-    .unwrap_or_else(|| Utf8PathBuf::from("~/.config"));
+#[derive(Subcommand)]
+enum CliCommand {
+  /// Install a launchd agent that starts liment at login.
+  InstallAgent,
+
+  /// Remove the launchd agent installed by `install-agent`.
+  UninstallAgent,
+
+  /// Remove everything liment wrote: the launch agent, config, logs, history and cache.
+  Uninstall {
+    /// Leave the config file in place.
+    #[arg(long)]
+    keep_config: bool,
+
+    /// Don't ask for confirmation.
+    #[arg(long, short)]
+    yes: bool,
+  },
+
+  /// Print current usage, from the running app if there is one.
+  Status {
+    /// Print the same JSON as `ctl get-json`, see `schema`.
+    #[arg(long)]
+    json: bool,
+  },
+
+  /// Print usage as a single colored line for tmux or starship, from the running app's last fetch.
+  Statusline,
+
+  /// Inspect the configuration.
+  Config {
+    #[command(subcommand)]
+    command: ConfigCommand,
+  },
+
+  /// Print the JSON Schema of `ctl get-json` output, for integrations.
+  Schema,
+
+  /// Send a command to the running app: refresh, get-json, pause, resume or notify-test.
+  Ctl { command: String },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+  /// Print the effective configuration: defaults, then the config file, then `LIMENT_CONFIG_` env vars.
+  /// Values that aren't defaults are followed by a comment naming their source.
+  Show {
+    /// Mask tokens, passwords and request headers, e.g. for bug reports.
+    #[arg(long)]
+    redacted: bool,
+  },
+}
 
-  return config_dir.join("liment").join("config.toml");
+static CONFIG_PATH: LazyLock<Utf8PathBuf> = LazyLock::new(|| {
+  return utils::sandbox::config_dir().join("liment").join("config.toml");
 });
 
 fn main() -> Result<()> {
   // Process CLI arguments.
   let args = CliArgs::parse();
 
-  utils::log::init_logger();
+  // Subcommands run often from scripts and status bars, a log file each would pile up.
+  utils::log::init_logger(args.command.is_none());
   color_eyre::install()?;
 
+  // Before the config is created, so there's nothing new to remove.
+  if let Some(CliCommand::Uninstall { keep_config, yes }) = args.command {
+    return uninstall::run(keep_config, yes);
+  }
+
   Config::ensure_exists()?;
 
+  match args.command {
+    Some(CliCommand::InstallAgent) => return launch_agent::install(),
+    Some(CliCommand::UninstallAgent) => return launch_agent::uninstall(),
+    Some(CliCommand::Status { json }) => return status::print(&load_config()?, json),
+    Some(CliCommand::Statusline) => return statusline::print(&load_config()?),
+    Some(CliCommand::Config {
+      command: ConfigCommand::Show { redacted },
+    }) => {
+      let figment = config_figment();
+      let origin = |key: &str| {
+        let metadata = figment.find_metadata(key)?;
+        return Some(match &metadata.source {
+          Some(source) => format!("{} {source}", metadata.name),
+          None => metadata.name.to_string(),
+        });
+      };
+
+      print!("{}", figment.extract::<Config>()?.to_toml(redacted, origin)?);
+
+      return Ok(());
+    }
+    Some(CliCommand::Schema) => {
+      println!("{}", serde_json::to_string_pretty(&ipc::usage_report_schema())?);
+
+      return Ok(());
+    }
+    Some(CliCommand::Ctl { command }) => {
+      println!("{}", ipc::request(&command)?);
+
+      return Ok(());
+    }
+    Some(CliCommand::Uninstall { .. }) => unreachable!("Handled before the config is created"),
+    None => {}
+  }
+
   if args.open_config {
     edit::edit_file(&*CONFIG_PATH)?;
 
@@ -74,11 +206,19 @@ fn main() -> Result<()> {
     return Ok(());
   }
 
+  if args.trace_http {
+    match utils::http::enable_tracing() {
+      Ok(path) => log::info!("Tracing HTTP requests to {path}"),
+      Err(e) => log::warn!("Failed to enable HTTP tracing: {e:#}"),
+    }
+  }
+
   // Load configuration.
-  let config = Figment::new()
-    .merge(Toml::file(&*CONFIG_PATH))
-    .merge(Env::prefixed("LIMENT_CONFIG_").split("_"))
-    .extract::<Config>()?;
+  let config = load_config()?;
+
+  if args.dry_run {
+    return dry_run::print(&config);
+  }
 
   // Initialize application.
   let mtm = MainThreadMarker::new().context("Failed to create main thread marker")?;
@@ -88,21 +228,57 @@ fn main() -> Result<()> {
 
   log::info!("Selected provider: {:?}", config.provider);
 
+  let experimental = config.experimental.enabled();
+  if !experimental.is_empty() {
+    log::info!("Experimental features enabled: {}", experimental.join(", "));
+  }
+
   let delegate = AppDelegate::new(mtm, config);
 
-  // Watch config file for changes.
+  if let Some(iterations) = args.bench_render {
+    return bench::run(&delegate, iterations);
+  }
+
+  if args.dump_menu {
+    return ui::dump::print(&delegate);
+  }
+
+  // Watch config file for changes, and global hotkeys. Both are kept alive until shutdown.
   let watcher = watch_config(&delegate, mtm).inspect_err(|e| log::warn!("{e:#}")).ok();
+  let hotkey_monitor = hotkeys::monitor(&delegate);
+  delegate.attach_services(watcher, hotkey_monitor);
+
+  // Accept commands from local tools, e.g. refresh requests from Claude Code hooks.
+  if let Err(e) = ipc::listen(&delegate, mtm) {
+    log::warn!("Failed to start IPC listener: {e:#}");
+  }
 
   // Run application.
   let delegate = ProtocolObject::from_ref(&*delegate);
   app.setDelegate(Some(delegate));
   app.run();
 
-  drop(watcher);
-
   return Ok(());
 }
 
+/// Config sources in order of precedence, later ones override earlier ones. There are no CLI flags
+/// for config values.
+fn config_figment() -> Figment {
+  return Figment::new().merge(Toml::file(&*CONFIG_PATH)).merge(Env::prefixed("LIMENT_CONFIG_").split("_"));
+}
+
+/// Loads the config and applies its global settings, so subcommands that fetch or format usage
+/// (e.g. `status` without a running app) follow them like the app does.
+fn load_config() -> Result<Config> {
+  let config = config_figment().extract::<Config>()?;
+
+  utils::json::set_strict_parsing(config.strict_parsing);
+  utils::http::set_fetch_retries(config.fetch_retries);
+  utils::time::set_date_order(config.date_order);
+
+  return Ok(config);
+}
+
 /// Ad-hoc codesigns the current executable (or .app bundle) and relaunches it.
 fn self_sign() -> Result<()> {
   let exe = Utf8PathBuf::try_from(std::env::current_exe().context("Failed to get current exe")?)
@@ -0,0 +1,42 @@
+use jiff::{Timestamp, Zoned, civil::Time};
+
+use crate::{providers::UsageData, utils::time};
+
+const WEEK_SECONDS: i64 = 7 * 86400;
+
+/// The projection runs until this local time ("by tonight").
+const TONIGHT: Time = Time::constant(22, 0, 0, 0);
+
+/// The summary is skipped if the Mac first fetches this many hours after the configured hour, it
+/// wouldn't be a morning summary anymore.
+const LATE_HOURS: i8 = 4;
+
+/// Whether it's time to send the summary configured for `hour`.
+pub fn is_due(now: &Zoned, hour: u8) -> bool {
+  let hour = hour.min(23) as i8;
+  return (hour .. hour + LATE_HOURS).contains(&now.hour());
+}
+
+/// Describes the weekly tray window and where it's headed by tonight at this week's average pace,
+/// e.g. "7d at 58%, projected 83% by tonight". Returns `None` without a weekly window.
+pub fn compose(data: &UsageData) -> Option<String> {
+  let window = data.windows.iter().find(|w| w.short_title.is_some() && w.period_seconds == Some(WEEK_SECONDS))?;
+  let label = window.short_title.as_deref().unwrap_or(&window.title);
+  let resets_at = window.resets_at?;
+
+  let now = Timestamp::now();
+  let tonight = Zoned::now().with().time(TONIGHT).build().ok()?.timestamp();
+
+  if resets_at <= tonight {
+    return Some(format!("{label} at {:.0}%, resets at {}", window.utilization, time::format_until_time(&resets_at)));
+  }
+
+  let elapsed = (WEEK_SECONDS - (resets_at.as_second() - now.as_second())).clamp(1, WEEK_SECONDS) as f64;
+  let per_second = window.utilization / elapsed;
+  let projected = window.utilization + per_second * (tonight.as_second() - now.as_second()).max(0) as f64;
+
+  return Some(format!(
+    "{label} at {:.0}%, projected {:.0}% by tonight at this week's pace",
+    window.utilization, projected
+  ));
+}
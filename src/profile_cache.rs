@@ -6,7 +6,8 @@ use std::{
 
 use crate::providers::{DataProvider, ProviderKind, TierInfo};
 
-const PROFILE_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+/// How long a fetched profile is reused before asking the provider again.
+pub const PROFILE_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
 
 struct CacheEntry {
   tier: TierInfo,
@@ -28,19 +29,13 @@ impl ProfileCache {
     {
       log::debug!("Using cached profile for {} ({}s old)", kind, entry.last.elapsed().as_secs());
 
-      return Some(TierInfo {
-        name: entry.tier.name.clone(),
-        color: entry.tier.color,
-      });
+      return Some(entry.tier.clone());
     }
 
     // Fetch fresh profile from the provider and cache it.
     return provider.fetch_profile().inspect(|profile| {
       self.0.lock().unwrap().insert(kind, CacheEntry {
-        tier: TierInfo {
-          name: profile.name.clone(),
-          color: profile.color,
-        },
+        tier: profile.clone(),
         last: Instant::now(),
       });
     });
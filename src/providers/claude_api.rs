@@ -0,0 +1,289 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use color_eyre::eyre::{Context as _, Result, bail};
+use jiff::{SignedDuration, Timestamp};
+use secrecy::{ExposeSecret, SecretString};
+use security_framework::passwords::get_generic_password;
+use serde::{Deserialize, Serialize};
+use ureq::http::HeaderMap;
+
+use crate::{
+  providers::{
+    DataProvider, ProviderAction, ProviderError, ProviderKind, UsageAmount, UsageData, UsageWindow, claude_code,
+  },
+  utils::http,
+};
+
+/// Keychain service holding workspace API keys, with the workspace name as the account.
+const KEYCHAIN_SERVICE: &str = "liment-claude-api";
+
+/// Model the probe request goes to, unless configured otherwise.
+const DEFAULT_PROBE_MODEL: &str = "claude-haiku-4-5";
+
+/// Seconds a probe is reused for, unless configured otherwise.
+const DEFAULT_PROBE_INTERVAL_SECONDS: u64 = 300;
+
+/// Rate limits are enforced per minute.
+const RATE_LIMIT_PERIOD_SECONDS: i64 = 60;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ClaudeApiSettings {
+  /// Workspaces to show rate limits for, each with its own API key.
+  pub workspaces: Vec<ClaudeApiWorkspace>,
+
+  /// Model the probe request is sent to. Each probe asks it for a single output token, which is billed.
+  pub model: Option<String>,
+
+  /// Minimum seconds between probes of a workspace. Refreshes in between show the last readings.
+  pub probe_interval: Option<u64>,
+
+  /// Anthropic API base URL override.
+  pub api_base_url: Option<String>,
+
+  /// Extra headers added to every request.
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+}
+
+impl ClaudeApiSettings {
+  /// Requests the provider makes, for `--dry-run`.
+  pub fn planned_requests(&self) -> Vec<String> {
+    let base = claude_code::api_base_url(self.api_base_url.as_deref());
+    let model = self.model.as_deref().unwrap_or(DEFAULT_PROBE_MODEL);
+    let interval = self.probe_interval.unwrap_or(DEFAULT_PROBE_INTERVAL_SECONDS);
+
+    return self
+      .workspaces
+      .iter()
+      .map(|workspace| {
+        let key = if workspace.api_key.is_some() { "config" } else { "keychain" };
+        return format!(
+          "POST {base}/v1/messages ({model}, 1 token) for {}, key from {key}, at most every {interval}s",
+          workspace.name
+        );
+      })
+      .collect();
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ClaudeApiWorkspace {
+  /// Name shown in the menu (e.g. "Production").
+  pub name: String,
+
+  /// API key of the workspace. If not set, it's read from the keychain (service
+  /// "liment-claude-api", account = workspace name).
+  pub api_key: Option<String>,
+}
+
+struct Workspace {
+  name: String,
+  api_key: SecretString,
+}
+
+pub struct ClaudeApiProvider {
+  workspaces: Vec<Workspace>,
+  model: String,
+  probe_interval: SignedDuration,
+  api_base_url: String,
+  headers: HashMap<String, String>,
+
+  /// Time and rate limit headers of the last probe of each workspace.
+  last_probes: Mutex<HashMap<String, (Timestamp, HeaderMap)>>,
+}
+
+/// One `anthropic-ratelimit-{name}-*` header group.
+struct RateLimit {
+  limit: f64,
+  remaining: f64,
+  reset: Option<Timestamp>,
+}
+
+impl RateLimit {
+  fn from_headers(headers: &HeaderMap, name: &str) -> Option<Self> {
+    let header = |suffix: &str| {
+      return headers.get(format!("anthropic-ratelimit-{name}-{suffix}"))?.to_str().ok().map(str::to_string);
+    };
+
+    return Some(Self {
+      limit: header("limit")?.parse().ok()?,
+      remaining: header("remaining")?.parse().ok()?,
+      reset: header("reset").and_then(|reset| reset.parse().ok()),
+    });
+  }
+
+  fn into_window(self, title: String, short_title: Option<String>, group: &str, unit: &str) -> UsageWindow {
+    // A reused probe can outlive the window, which has refilled since.
+    let reset = self.reset.filter(|reset| *reset > Timestamp::now());
+    let remaining = if self.reset.is_some() && reset.is_none() { self.limit } else { self.remaining };
+
+    let used = (self.limit - remaining).max(0.0);
+    let utilization = if self.limit > 0.0 { used / self.limit * 100.0 } else { 0.0 };
+
+    return UsageWindow {
+      title,
+      short_title,
+      utilization,
+      resets_at: reset,
+      period_seconds: Some(RATE_LIMIT_PERIOD_SECONDS),
+      group: Some(group.to_string()),
+      amount: Some(UsageAmount {
+        used,
+        total: self.limit,
+        unit: unit.to_string(),
+      }),
+    };
+  }
+}
+
+impl ClaudeApiProvider {
+  pub fn new(settings: &ClaudeApiSettings) -> Result<Self> {
+    log::info!("Initializing Claude API provider");
+
+    if settings.workspaces.is_empty() {
+      bail!("claude_api provider requires at least one workspace in [settings.claude_api]");
+    }
+
+    let workspaces = settings
+      .workspaces
+      .iter()
+      .map(|workspace| {
+        let api_key = match &workspace.api_key {
+          Some(api_key) => api_key.clone(),
+          None => {
+            get_generic_password(KEYCHAIN_SERVICE, &workspace.name)
+              .with_context(|| format!("Failed to find the API key of workspace {:?} in keychain", workspace.name))
+              .and_then(|key| String::from_utf8(key).context("API key is not valid UTF-8"))?
+          }
+        };
+
+        return Ok(Workspace {
+          name: workspace.name.clone(),
+          api_key: SecretString::from(api_key),
+        });
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    return Ok(Self {
+      workspaces,
+      model: settings.model.clone().unwrap_or_else(|| DEFAULT_PROBE_MODEL.to_string()),
+      probe_interval: SignedDuration::from_secs(
+        settings.probe_interval.unwrap_or(DEFAULT_PROBE_INTERVAL_SECONDS) as i64
+      ),
+      api_base_url: claude_code::api_base_url(settings.api_base_url.as_deref()),
+      headers: settings.headers.clone(),
+      last_probes: Mutex::new(HashMap::new()),
+    });
+  }
+
+  /// Sends the cheapest possible message and returns the rate limit headers of the response.
+  /// A 429 still carries them, it just means a limit is used up. There's no unbilled request
+  /// sharing the limits of messages, so a probe is reused until `probe_interval` passes.
+  fn probe(&self, workspace: &Workspace) -> Result<HeaderMap, ProviderError> {
+    if let Some((probed_at, headers)) = self.last_probes.lock().unwrap().get(&workspace.name)
+      && Timestamp::now().duration_since(*probed_at) < self.probe_interval
+    {
+      log::debug!("Reusing the rate limits of workspace {} probed at {probed_at}", workspace.name);
+      return Ok(headers.clone());
+    }
+
+    log::debug!("Probing rate limits of workspace {}", workspace.name);
+
+    let url = format!("{}/v1/messages", self.api_base_url);
+    let body = serde_json::json!({
+      "model": self.model,
+      "max_tokens": 1,
+      "messages": [{ "role": "user", "content": "." }],
+    });
+
+    let response = http::with_retries(|| {
+      let request = ureq::post(&url)
+        .header("x-api-key", workspace.api_key.expose_secret())
+        .header("anthropic-version", "2023-06-01")
+        .header("Content-Type", "application/json")
+        .config()
+        .http_status_as_error(false)
+        .build();
+
+      return http::send(http::with_headers(request, &self.headers), &body.to_string());
+    })
+    .inspect_err(|e| log::error!("Request failed for {}: {}", url, e))?;
+
+    let status = response.status().as_u16();
+    if !response.status().is_success() && status != 429 {
+      log::error!("Rate limit probe for workspace {} returned status {}", workspace.name, status);
+      return Err(ureq::Error::StatusCode(status).into());
+    }
+
+    let headers = response.headers().clone();
+    self.last_probes.lock().unwrap().insert(workspace.name.clone(), (Timestamp::now(), headers.clone()));
+
+    return Ok(headers);
+  }
+}
+
+impl DataProvider for ClaudeApiProvider {
+  fn kind(&self) -> ProviderKind {
+    return ProviderKind::ClaudeApi;
+  }
+
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
+    let mut windows = Vec::new();
+    let mut error = None;
+
+    for (i, workspace) in self.workspaces.iter().enumerate() {
+      let headers = match self.probe(workspace) {
+        Ok(headers) => headers,
+        Err(e) => {
+          error = Some(e);
+          continue;
+        }
+      };
+
+      // Only the first workspace goes to the tray.
+      let short_title = |label: &str| {
+        return if i == 0 { Some(label.to_string()) } else { None };
+      };
+      let name = &workspace.name;
+
+      if let Some(mut requests) = RateLimit::from_headers(&headers, "requests") {
+        // Leave out the probe itself. Its few tokens don't matter next to the token limit.
+        requests.remaining = (requests.remaining + 1.0).min(requests.limit);
+
+        windows.push(requests.into_window(format!("{name} Requests"), short_title("RPM"), name, "requests"));
+      }
+
+      // The combined tokens limit is the most restrictive of the input and output ones.
+      if let Some(tokens) = RateLimit::from_headers(&headers, "tokens") {
+        windows.push(tokens.into_window(format!("{name} Tokens"), short_title("TPM"), name, "tokens"));
+      }
+    }
+
+    if windows.is_empty() {
+      return Err(error.unwrap_or_else(|| ProviderError::Parse("no rate limit headers in the response".to_string())));
+    }
+
+    return Ok(UsageData {
+      api_usage: None,
+      peak_hours: None,
+      windows,
+      fetched_at: Timestamp::now(),
+      source: self.api_base_url.clone(),
+    });
+  }
+
+  fn tray_icon_svg(&self) -> &'static [u8] {
+    return include_bytes!("../../resources/claude.svg");
+  }
+
+  fn actions(&self) -> Vec<ProviderAction> {
+    return vec![ProviderAction {
+      label: "Open Console Limits".to_string(),
+      url: "https://console.anthropic.com/settings/limits".to_string(),
+    }];
+  }
+
+  fn plans_url(&self) -> Option<&'static str> {
+    return Some("https://docs.anthropic.com/en/api/rate-limits");
+  }
+}
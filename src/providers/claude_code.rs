@@ -1,30 +1,82 @@
 use std::{
+  collections::HashMap,
   sync::Mutex,
   time::{Duration, Instant},
 };
 
 use color_eyre::eyre::{ContextCompat as _, Result};
-use jiff::Timestamp;
+use jiff::{SignedDuration, Timestamp};
 use rgb::Rgb;
 use secrecy::{ExposeSecret, SecretString};
-use security_framework::item::{ItemClass, ItemSearchOptions, SearchResult};
+use security_framework::{
+  item::{ItemClass, ItemSearchOptions},
+  passwords::{delete_generic_password, get_generic_password, set_generic_password},
+};
 use serde::{Deserialize, Serialize};
 
-use super::{DataProvider, PeakHoursInfo, ProviderKind, UsageData};
-use crate::providers::{ApiUsage, TierInfo, UsageWindow};
+use super::{AuthStatus, DataProvider, PeakHoursInfo, ProviderAction, ProviderError, ProviderKind, UsageData};
+use crate::{
+  profile_cache::PROFILE_CACHE_TTL,
+  providers::{AccountInfo, ApiUsage, TierInfo, UsageWindow},
+  utils::{http, json, time},
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct ClaudeCodeSettings {
   /// OAuth token override. If not set, reads from keychain.
   pub token: Option<String>,
+
+  /// Anthropic API base URL override (e.g. a regional gateway or relay).
+  pub api_base_url: Option<String>,
+
+  /// Extra headers added to every request (e.g. access service tokens for a relay).
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+}
+
+impl ClaudeCodeSettings {
+  /// Requests the provider makes, for `--dry-run`.
+  pub fn planned_requests(&self) -> Vec<String> {
+    let base = api_base_url(self.api_base_url.as_deref());
+    let token = if self.token.is_some() { "provider settings" } else { "keychain" };
+
+    return vec![
+      format!("GET {base}/api/oauth/usage, every refresh"),
+      format!("GET {base}/api/oauth/profile, at most every {}s", PROFILE_CACHE_TTL.as_secs()),
+      format!(
+        "GET {base}/api/oauth/organizations/{{org}}/overage_credit_grant, at most every {}s",
+        OVERAGE_GRANT_TTL.as_secs()
+      ),
+      format!("Token from {token}"),
+      format!("POST {OAUTH_TOKEN_URL}, only when the keychain token expired and Claude Code hasn't refreshed it"),
+    ];
+  }
+}
+
+/// Default Anthropic API base URL, used unless a provider overrides it.
+pub const DEFAULT_API_BASE_URL: &str = "https://api.anthropic.com";
+
+/// Resolves the configured API base URL, falling back to the default one.
+pub fn api_base_url(setting: Option<&str>) -> String {
+  let url = setting.unwrap_or(DEFAULT_API_BASE_URL).trim_end_matches('/');
+  if url != DEFAULT_API_BASE_URL {
+    log::info!("Using custom API base URL: {}", url);
+  }
+
+  return url.to_string();
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct UsageResponse {
+  #[serde(default, deserialize_with = "json::lenient")]
   pub five_hour: Option<UsageBucket>,
+  #[serde(default, deserialize_with = "json::lenient")]
   pub seven_day: Option<UsageBucket>,
+  #[serde(default, deserialize_with = "json::lenient")]
   pub seven_day_sonnet: Option<UsageBucket>,
+  #[serde(default, deserialize_with = "json::lenient")]
   pub seven_day_opus: Option<UsageBucket>,
+  #[serde(default, deserialize_with = "json::lenient")]
   pub extra_usage: Option<ExtraUsage>,
 }
 
@@ -72,14 +124,14 @@ impl From<UsageResponse> for UsageData {
     });
 
     let mut windows = Vec::new();
-    let buckets: &[(&str, Option<&str>, &Option<UsageBucket>, i64)] = &[
-      ("5h Limit", Some("5h"), &usage.five_hour, 5 * 3600),
-      ("7d Limit", Some("7d"), &usage.seven_day, 7 * 86400),
-      ("7d Sonnet", None, &usage.seven_day_sonnet, 7 * 86400),
-      ("7d Opus", None, &usage.seven_day_opus, 7 * 86400),
+    let buckets: &[(&str, Option<&str>, &Option<UsageBucket>, i64, Option<&str>)] = &[
+      ("5h Limit", Some("5h"), &usage.five_hour, 5 * 3600, None),
+      ("7d Limit", Some("7d"), &usage.seven_day, 7 * 86400, None),
+      ("7d Sonnet", None, &usage.seven_day_sonnet, 7 * 86400, Some("Model limits")),
+      ("7d Opus", None, &usage.seven_day_opus, 7 * 86400, Some("Model limits")),
     ];
 
-    for (title, short_title, bucket, period_secs) in buckets {
+    for (title, short_title, bucket, period_secs, group) in buckets {
       if let Some(b) = bucket {
         windows.push(UsageWindow {
           title: title.to_string(),
@@ -87,6 +139,8 @@ impl From<UsageResponse> for UsageData {
           utilization: b.utilization.unwrap_or(0.0),
           resets_at: b.resets_at,
           period_seconds: Some(*period_secs),
+          group: group.map(|s| s.to_string()),
+          amount: None,
         });
       }
     }
@@ -97,6 +151,8 @@ impl From<UsageResponse> for UsageData {
       // peak_hours: Some(compute_claude_peak_hours()),
       peak_hours: None,
       windows,
+      fetched_at: Timestamp::now(),
+      source: String::new(),
     };
   }
 }
@@ -119,13 +175,44 @@ pub struct ExtraUsage {
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProfileResponse {
+  #[serde(default, deserialize_with = "json::lenient")]
+  pub account: Option<ProfileAccount>,
   pub organization: ProfileOrganization,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct ProfileAccount {
+  #[serde(default)]
+  pub email: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ProfileOrganization {
   pub uuid: String,
+  #[serde(default)]
+  pub name: Option<String>,
   pub rate_limit_tier: SubscriptionTier,
+  /// Next subscription renewal. Not every account's profile reports it.
+  #[serde(default, deserialize_with = "json::lenient")]
+  pub subscription_renews_at: Option<Timestamp>,
+}
+
+impl ProfileResponse {
+  /// Returns the tier badge info along with the account identity (keyed by organization).
+  pub fn tier_info(&self) -> TierInfo {
+    let name = self
+      .account
+      .as_ref()
+      .and_then(|a| a.email.clone())
+      .or_else(|| self.organization.name.clone())
+      .unwrap_or_else(|| self.organization.uuid.clone());
+
+    return TierInfo {
+      account: Some(AccountInfo { id: self.organization.uuid.clone(), name }),
+      renews_at: self.organization.subscription_renews_at,
+      ..self.organization.rate_limit_tier.tier_info()
+    };
+  }
 }
 
 /// Response shape of `/api/oauth/organizations/{uuid}/overage_credit_grant`. Anthropic
@@ -153,16 +240,62 @@ impl OverageCreditGrant {
 
 const OVERAGE_GRANT_TTL: Duration = Duration::from_secs(60 * 60);
 
-#[derive(Debug, Deserialize, Clone, Copy, strum::EnumIter)]
+/// Keychain service Claude Code stores its OAuth credentials under.
+const KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
+
+/// Endpoint and client ID Claude Code refreshes its OAuth token with.
+const OAUTH_TOKEN_URL: &str = "https://console.anthropic.com/v1/oauth/token";
+const OAUTH_CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
+
+/// Keychain service and account of a token entered manually in liment.
+const MANUAL_TOKEN_SERVICE: &str = "liment-claude-token";
+const MANUAL_TOKEN_ACCOUNT: &str = "oauth";
+
+/// `errSecItemNotFound`, returned when deleting a keychain entry that doesn't exist.
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+/// `errSecUserCanceled` and `errSecAuthFailed`, returned when access to another app's keychain
+/// item is denied in the prompt macOS shows, e.g. for a new or re-signed build.
+const ERR_SEC_USER_CANCELED: i32 = -128;
+const ERR_SEC_AUTH_FAILED: i32 = -25293;
+
+/// Explains a keychain error reading Claude Code's item, a denied prompt is only a status code.
+fn keychain_error(error: security_framework::base::Error) -> color_eyre::Report {
+  return match error.code() {
+    ERR_SEC_USER_CANCELED | ERR_SEC_AUTH_FAILED => {
+      color_eyre::eyre::eyre!(
+        "Access to Claude Code's keychain item was denied, choose \"Always Allow\" when macOS asks for it"
+      )
+    }
+    _ => error.into(),
+  };
+}
+
+/// Subscription tier from the profile's `rate_limit_tier`. Tiers this version doesn't know about
+/// are kept as the raw string instead of failing the whole profile.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(from = "String")]
 pub enum SubscriptionTier {
-  #[serde(rename = "default_claude_free")]
   Free,
-  #[serde(rename = "default_claude_pro")]
   Pro,
-  #[serde(rename = "default_claude_max_5x")]
   Max5x,
-  #[serde(rename = "default_claude_max_20x")]
   Max20x,
+  Unknown(String),
+}
+
+impl From<String> for SubscriptionTier {
+  fn from(raw: String) -> Self {
+    return match raw.as_str() {
+      "default_claude_free" => SubscriptionTier::Free,
+      "default_claude_pro" => SubscriptionTier::Pro,
+      "default_claude_max_5x" => SubscriptionTier::Max5x,
+      "default_claude_max_20x" => SubscriptionTier::Max20x,
+      _ => {
+        log::warn!("Unknown subscription tier: {raw}");
+        SubscriptionTier::Unknown(raw)
+      }
+    };
+  }
 }
 
 impl SubscriptionTier {
@@ -174,7 +307,10 @@ impl SubscriptionTier {
         SubscriptionTier::Pro => Rgb::new(90, 145, 210),
         SubscriptionTier::Max5x => Rgb::new(145, 110, 200),
         SubscriptionTier::Max20x => Rgb::new(205, 130, 95),
+        SubscriptionTier::Unknown(_) => Rgb::new(120, 120, 125),
       },
+      account: None,
+      renews_at: None,
     };
   }
 }
@@ -186,13 +322,17 @@ impl std::fmt::Display for SubscriptionTier {
       SubscriptionTier::Pro => write!(f, "Pro"),
       SubscriptionTier::Max5x => write!(f, "Max 5x"),
       SubscriptionTier::Max20x => write!(f, "Max 20x"),
+      SubscriptionTier::Unknown(raw) => write!(f, "Unknown ({raw})"),
     };
   }
 }
 
 pub struct ClaudeCodeProvider {
   token: Mutex<TokenState>,
-  backoff: Mutex<BackoffState>,
+  /// Last failed authentication, kept for token diagnostics.
+  last_auth_error: Mutex<Option<AuthError>>,
+  api_base_url: String,
+  headers: HashMap<String, String>,
   /// Organization UUID, lazily populated from the first profile fetch.
   org_uuid: Mutex<Option<String>>,
   /// Cached overage credit grant info, refreshed at most once per `OVERAGE_GRANT_TTL`.
@@ -203,11 +343,37 @@ struct TokenState {
   secret: SecretString,
   /// Known expiry from the keychain. `None` when the source doesn't provide one (e.g. config override).
   expires_at: Option<Timestamp>,
+  source: TokenSource,
+}
+
+/// Where the token in use was loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenSource {
+  Settings,
+  ManualKeychain,
+  ClaudeCodeKeychain,
+}
+
+impl std::fmt::Display for TokenSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    return match self {
+      TokenSource::Settings => write!(f, "provider settings"),
+      TokenSource::ManualKeychain => write!(f, "keychain (entered in liment)"),
+      TokenSource::ClaudeCodeKeychain => write!(f, "keychain (Claude Code credentials)"),
+    };
+  }
 }
 
-struct BackoffState {
-  retry_after: Option<Instant>,
-  consecutive_failures: u32,
+struct AuthError {
+  at: Timestamp,
+  message: String,
+}
+
+/// Claude Code's keychain item.
+struct KeychainCredentials {
+  /// Account the item is stored under, so updates go to the same item.
+  account: String,
+  data: Vec<u8>,
 }
 
 #[derive(Default)]
@@ -216,20 +382,66 @@ struct OverageGrantCache {
   fetched_at: Option<Instant>,
 }
 
+/// Whether Claude Code credentials exist in the keychain. Only item attributes are read, so
+/// unlike loading the token this never triggers a keychain access prompt.
+pub fn has_keychain_credentials() -> bool {
+  return ItemSearchOptions::new()
+    .class(ItemClass::generic_password())
+    .service(KEYCHAIN_SERVICE)
+    .load_attributes(true)
+    .search()
+    .is_ok_and(|results| !results.is_empty());
+}
+
+/// Whether a token entered in liment is saved in the keychain. Like
+/// [`has_keychain_credentials`], this never triggers a keychain access prompt.
+pub fn has_manual_token() -> bool {
+  return ItemSearchOptions::new()
+    .class(ItemClass::generic_password())
+    .service(MANUAL_TOKEN_SERVICE)
+    .account(MANUAL_TOKEN_ACCOUNT)
+    .load_attributes(true)
+    .search()
+    .is_ok_and(|results| !results.is_empty());
+}
+
+/// Checks a manually entered token against the profile endpoint and saves it to the keychain.
+/// It's used while the Claude Code credentials are missing, can't be read or are rejected.
+/// Performs a blocking HTTP request.
+pub fn save_manual_token(settings: &ClaudeCodeSettings, token: &str) -> Result<()> {
+  let settings = ClaudeCodeSettings {
+    token: Some(token.to_string()),
+    ..settings.clone()
+  };
+
+  ClaudeCodeProvider::new(&settings)?.fetch_profile().context("The token was rejected")?;
+  set_generic_password(MANUAL_TOKEN_SERVICE, MANUAL_TOKEN_ACCOUNT, token.as_bytes())?;
+
+  log::info!("Saved manually entered token to keychain");
+
+  return Ok(());
+}
+
+/// Removes the token saved by [`save_manual_token`] from the keychain. Returns whether there was one.
+pub fn remove_manual_token() -> Result<bool> {
+  return match delete_generic_password(MANUAL_TOKEN_SERVICE, MANUAL_TOKEN_ACCOUNT) {
+    Ok(()) => Ok(true),
+    Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(false),
+    Err(e) => Err(e.into()),
+  };
+}
+
 impl ClaudeCodeProvider {
   pub fn new(settings: &ClaudeCodeSettings) -> Result<Self> {
     log::info!("Initializing Claude Code provider");
 
     let token = Self::fetch_token(settings)?;
 
-    let backoff = Mutex::new(BackoffState {
-      retry_after: None,
-      consecutive_failures: 0,
-    });
-
     return Ok(Self {
       token: Mutex::new(token),
-      backoff,
+      last_auth_error: Mutex::new(None),
+      api_base_url: api_base_url(settings.api_base_url.as_deref()),
+      headers: settings.headers.clone(),
       org_uuid: Mutex::new(None),
       overage_grant: Mutex::new(OverageGrantCache::default()),
     });
@@ -242,31 +454,56 @@ impl ClaudeCodeProvider {
       return Ok(TokenState {
         secret: SecretString::from(token.clone()),
         expires_at: None,
+        source: TokenSource::Settings,
       });
     }
 
     log::debug!("Token not set in config, fetching from keychain");
 
-    return Self::fetch_keychain_token();
+    // Claude Code keeps its credentials up to date, an entered token never is, so it only stands
+    // in while they can't be loaded.
+    let keychain_token = Self::fetch_keychain_token();
+    if keychain_token.is_ok() {
+      return keychain_token;
+    }
+
+    if let Ok(token) = get_generic_password(MANUAL_TOKEN_SERVICE, MANUAL_TOKEN_ACCOUNT) {
+      log::info!("Using manually entered token from keychain");
+
+      return Ok(TokenState {
+        secret: SecretString::from(String::from_utf8(token)?),
+        expires_at: None,
+        source: TokenSource::ManualKeychain,
+      });
+    }
+
+    return keychain_token;
   }
 
-  fn fetch_keychain_token() -> Result<TokenState> {
+  /// Reads the raw credentials Claude Code stores in the keychain, with the account of the item
+  /// they're stored under.
+  fn keychain_credentials() -> Result<KeychainCredentials> {
     let results = ItemSearchOptions::new()
       .class(ItemClass::generic_password())
-      .service("Claude Code-credentials")
-      .load_data(true)
-      .search()?;
-
-    let data = results
-      .into_iter()
-      .find_map(|r| {
-        match r {
-          SearchResult::Data(d) => Some(d),
-          _ => None,
-        }
-      })
+      .service(KEYCHAIN_SERVICE)
+      .load_attributes(true)
+      .search()
+      .map_err(keychain_error)?;
+
+    let account = results
+      .iter()
+      .find_map(|r| r.simplify_dict()?.remove("acct"))
       .context("Failed to find Claude Code credentials in keychain")?;
+    let data = get_generic_password(KEYCHAIN_SERVICE, &account).map_err(keychain_error)?;
 
+    return Ok(KeychainCredentials { account, data });
+  }
+
+  fn fetch_keychain_token() -> Result<TokenState> {
+    return Self::parse_keychain_token(&Self::keychain_credentials()?.data);
+  }
+
+  fn parse_keychain_token(data: &[u8]) -> Result<TokenState> {
     #[derive(Deserialize)]
     struct ClaudeOAuth {
       #[serde(rename = "accessToken")]
@@ -281,34 +518,124 @@ impl ClaudeCodeProvider {
       claude_oauth: ClaudeOAuth,
     }
 
-    let json_str = String::from_utf8(data)?;
-    let value: ClaudeKeychain = serde_json::from_str(&json_str)?;
+    let value: ClaudeKeychain = serde_json::from_slice(data)?;
     let expires_at = value.claude_oauth.expires_at.and_then(|ms| Timestamp::from_millisecond(ms).ok());
     return Ok(TokenState {
       secret: SecretString::from(value.claude_oauth.access_token),
       expires_at,
+      source: TokenSource::ClaudeCodeKeychain,
+    });
+  }
+
+  /// Exchanges the refresh token stored with Claude Code's credentials for a new access token.
+  /// The refresh token rotates, so the new credentials are written back to the same item for
+  /// Claude Code to use, unless Claude Code changed the item in the meantime. Once the old refresh
+  /// token is spent, failing to write back is only logged, the new token is returned regardless.
+  fn refresh_keychain_token(stored: KeychainCredentials) -> Result<TokenState> {
+    #[derive(Deserialize)]
+    struct RefreshResponse {
+      access_token: String,
+      refresh_token: Option<String>,
+      expires_in: Option<i64>,
+    }
+
+    let mut credentials: serde_json::Value = serde_json::from_slice(&stored.data)?;
+    let oauth = credentials.get_mut("claudeAiOauth").context("No OAuth credentials in keychain")?;
+    let refresh_token = oauth
+      .get("refreshToken")
+      .and_then(|token| token.as_str())
+      .context("No refresh token in keychain")?
+      .to_string();
+
+    let body = serde_json::json!({
+      "grant_type": "refresh_token",
+      "refresh_token": refresh_token,
+      "client_id": OAUTH_CLIENT_ID,
+    });
+    // Not retried: the refresh token is single-use, a repeat after a lost response would fail.
+    let request = ureq::post(OAUTH_TOKEN_URL).header("Content-Type", "application/json");
+    let mut response = http::send(request, &body.to_string())?;
+    let response: RefreshResponse = serde_json::from_str(&http::read_body(&mut response)?)?;
+
+    let expires_at = response
+      .expires_in
+      .and_then(|seconds| Timestamp::now().checked_add(SignedDuration::from_secs(seconds)).ok());
+
+    oauth["accessToken"] = response.access_token.clone().into();
+    if let Some(refresh_token) = response.refresh_token {
+      oauth["refreshToken"] = refresh_token.into();
+    }
+    if let Some(expires_at) = expires_at {
+      oauth["expiresAt"] = expires_at.as_millisecond().into();
+    }
+    let credentials = credentials.to_string();
+
+    log::info!("Refreshed Claude Code token with the stored refresh token");
+
+    // Checked right before writing: if Claude Code refreshed on its own meanwhile, its
+    // credentials are newer and must stay.
+    match get_generic_password(KEYCHAIN_SERVICE, &stored.account) {
+      Ok(current) if current != stored.data => {
+        log::warn!("Claude Code credentials changed during the refresh, not writing them back");
+      }
+      Ok(_) => {
+        if let Err(e) = set_generic_password(KEYCHAIN_SERVICE, &stored.account, credentials.as_bytes()) {
+          log::error!("Failed to write refreshed credentials back to keychain: {e}");
+        }
+      }
+      Err(e) => log::error!("Failed to re-read Claude Code credentials, not writing them back: {e}"),
+    }
+
+    return Ok(TokenState {
+      secret: SecretString::from(response.access_token),
+      expires_at,
+      source: TokenSource::ClaudeCodeKeychain,
     });
   }
 
-  fn fetch_usage(&self) -> Option<UsageResponse> {
+  /// Re-reads the keychain in case Claude Code already refreshed the token, and refreshes it
+  /// with the stored refresh token only if the keychain still has the token that failed.
+  fn renewed_token(current: &SecretString) -> Result<TokenState> {
+    let stored = Self::keychain_credentials()?;
+    let state = Self::parse_keychain_token(&stored.data)?;
+    if state.secret.expose_secret() != current.expose_secret() {
+      log::info!("Loaded fresh token from keychain");
+      return Ok(state);
+    }
+
+    log::info!("Keychain still has the token that failed, refreshing it");
+
+    return Self::refresh_keychain_token(stored);
+  }
+
+  fn fetch_usage(&self) -> Result<UsageResponse, ProviderError> {
     log::debug!("Fetching usage data");
 
-    let body = self.get("https://api.anthropic.com/api/oauth/usage")?;
+    let url = format!("{}/api/oauth/usage", self.api_base_url);
+    let body = self.get(&url)?;
 
-    return serde_json::from_str(&body)
+    let usage = serde_json::from_str(&body)
       .inspect(|u: &UsageResponse| log::debug!("Parsed usage: {:?}", u))
-      .inspect_err(|e| log::warn!("Failed to parse usage response: {}", e))
-      .ok();
+      .inspect_err(|e| {
+        log::warn!("Failed to parse usage response: {}", e);
+        http::trace_parse_failure(&url, &body, e);
+      })?;
+
+    return Ok(usage);
   }
 
   fn fetch_profile_response(&self) -> Option<ProfileResponse> {
     log::debug!("Fetching profile data");
 
-    let body = self.get("https://api.anthropic.com/api/oauth/profile")?;
+    let url = format!("{}/api/oauth/profile", self.api_base_url);
+    let body = self.get(&url).ok()?;
 
     let response: Option<ProfileResponse> = serde_json::from_str(&body)
       .inspect(|p: &ProfileResponse| log::debug!("Parsed profile: {:?}", p))
-      .inspect_err(|e| log::warn!("Failed to parse profile response: {}", e))
+      .inspect_err(|e| {
+        log::warn!("Failed to parse profile response: {}", e);
+        http::trace_parse_failure(&url, &body, e);
+      })
       .ok();
 
     // Cache the org UUID for use by other endpoints (e.g. overage credit grant).
@@ -337,12 +664,15 @@ impl ClaudeCodeProvider {
     let org_uuid = self.org_uuid.lock().unwrap().clone()?;
 
     log::debug!("Fetching overage credit grant");
-    let url = format!("https://api.anthropic.com/api/oauth/organizations/{}/overage_credit_grant", org_uuid);
-    let body = self.get(&url)?;
+    let url = format!("{}/api/oauth/organizations/{}/overage_credit_grant", self.api_base_url, org_uuid);
+    let body = self.get(&url).ok()?;
 
     let grant: Option<OverageCreditGrant> = serde_json::from_str(&body)
       .inspect(|g: &OverageCreditGrant| log::debug!("Parsed overage grant: {:?}", g))
-      .inspect_err(|e| log::warn!("Failed to parse overage grant: {}", e))
+      .inspect_err(|e| {
+        log::warn!("Failed to parse overage grant: {}", e);
+        http::trace_parse_failure(&url, &body, e);
+      })
       .ok();
 
     // Update cache regardless of parse outcome so a parse failure doesn't trigger
@@ -354,43 +684,25 @@ impl ClaudeCodeProvider {
     return grant;
   }
 
-  fn get(&self, url: &str) -> Option<String> {
-    // Check if we're in a backoff period
+  fn get(&self, url: &str) -> Result<String, ProviderError> {
+    // Proactive expiry check: if the current token is known to have expired, renew it before
+    // making the request. The lock is held throughout, so concurrent requests refresh it once.
+    // Only Claude Code's own credentials are renewed, a configured or entered token is never
+    // swapped for them.
     {
-      let backoff = self.backoff.lock().unwrap();
-      if let Some(retry_after) = backoff.retry_after
-        && Instant::now() < retry_after
+      let mut token_guard = self.token.lock().unwrap();
+      if token_guard.source == TokenSource::ClaudeCodeKeychain
+        && token_guard.expires_at.is_some_and(|ts| Timestamp::now() >= ts)
       {
-        log::debug!(
-          "Skipping request to {} (rate limit backoff, {}s remaining)",
-          url,
-          (retry_after - Instant::now()).as_secs()
-        );
-        return None;
-      }
-    }
-
-    // Proactive expiry check: if the current token is known to have expired,
-    // re-read the keychain before making the request.
-    let needs_refresh = {
-      let token_guard = self.token.lock().unwrap();
-      token_guard.expires_at.is_some_and(|ts| Timestamp::now() >= ts)
-    };
-    if needs_refresh {
-      log::debug!("Access token expired, re-reading keychain before request");
-      match Self::fetch_keychain_token() {
-        Ok(new_state) => {
-          let mut token_guard = self.token.lock().unwrap();
-          if new_state.secret.expose_secret() == token_guard.secret.expose_secret() {
-            log::warn!("Keychain still has the same expired token, skipping request");
-            return None;
+        log::debug!("Access token expired, renewing it before request");
+        match Self::renewed_token(&token_guard.secret) {
+          Ok(new_state) => *token_guard = new_state,
+          Err(e) => {
+            drop(token_guard);
+            log::error!("Failed to renew expired token: {e:#}");
+            self.record_auth_error(format!("Failed to renew expired token: {e:#}"));
+            return Err(ProviderError::Auth(format!("couldn't renew the expired token: {e:#}")));
           }
-          *token_guard = new_state;
-          log::info!("Loaded fresh token from keychain (proactive refresh)");
-        }
-        Err(e) => {
-          log::error!("Failed to re-read keychain for expired token: {}", e);
-          return None;
         }
       }
     }
@@ -398,64 +710,68 @@ impl ClaudeCodeProvider {
     let mut result = self.get_inner(url);
 
     if let Err(ureq::Error::StatusCode(401)) = &result {
-      log::warn!("Got 401 for {}, refreshing token from keychain", url);
-
-      if let Ok(new_state) = Self::fetch_keychain_token() {
-        {
-          let mut token_guard = self.token.lock().unwrap();
-          if new_state.secret.expose_secret() == token_guard.secret.expose_secret() {
-            log::warn!("Keychain returned the same token, skipping retry (token likely expired)");
-            return None;
-          }
-          *token_guard = new_state;
+      self.record_auth_error(format!("401 for {url}"));
+
+      let source = self.token.lock().unwrap().source;
+      if source == TokenSource::Settings {
+        log::warn!("Got 401 for {}, the token from {} was rejected", url, source);
+        return Err(ProviderError::Auth(format!("the token from {source} was rejected")));
+      }
+
+      let renewed = {
+        let mut token_guard = self.token.lock().unwrap();
+        let new_state = if source == TokenSource::ManualKeychain {
+          log::warn!("Got 401 for {}, the entered token was rejected, loading Claude Code credentials", url);
+          Self::fetch_keychain_token()
         }
+        else {
+          log::warn!("Got 401 for {}, renewing token", url);
+          Self::renewed_token(&token_guard.secret)
+        };
 
-        log::info!("Token refreshed, retrying request");
+        new_state.map(|new_state| *token_guard = new_state)
+      };
 
-        result = self.get_inner(url).inspect_err(|e| log::error!("Retry failed for {}: {}", url, e));
-      }
-      else {
-        log::error!("Failed to refresh token from keychain");
-      }
-    }
+      match renewed {
+        Ok(()) => {
+          log::info!("Token renewed, retrying request");
 
-    if let Err(ureq::Error::StatusCode(429)) = &result {
-      let mut backoff = self.backoff.lock().unwrap();
-      backoff.consecutive_failures += 1;
-      let delay_secs = 60u64 * (1 << backoff.consecutive_failures.min(4));
-      backoff.retry_after = Some(Instant::now() + std::time::Duration::from_secs(delay_secs));
-      log::warn!("Rate limited (429), backing off for {}s", delay_secs);
-      return None;
+          result = self.get_inner(url).inspect_err(|e| log::error!("Retry failed for {}: {}", url, e));
+        }
+        Err(e) => {
+          log::error!("Failed to renew token: {e:#}");
+          self.record_auth_error(format!("Failed to renew token after 401: {e:#}"));
+          return Err(ProviderError::Auth(format!("couldn't renew the token after 401: {e:#}")));
+        }
+      }
     }
 
     if let Err(ref e) = result {
       log::error!("Request failed for {}: {}", url, e);
     }
 
-    // Reset backoff on success
-    if result.is_ok() {
-      let mut backoff = self.backoff.lock().unwrap();
-      if backoff.consecutive_failures > 0 {
-        log::info!("Request succeeded, resetting backoff");
-        backoff.consecutive_failures = 0;
-        backoff.retry_after = None;
-      }
-    }
+    return result.map_err(ProviderError::from);
+  }
 
-    return result.ok();
+  fn record_auth_error(&self, message: String) {
+    *self.last_auth_error.lock().unwrap() = Some(AuthError { at: Timestamp::now(), message });
   }
 
   fn get_inner(&self, url: &str) -> Result<String, ureq::Error> {
     log::debug!("GET {}", url);
 
-    let token = self.token.lock().unwrap();
-    let mut response = ureq::get(url)
-      .header("Authorization", &format!("Bearer {}", token.secret.expose_secret()))
-      .header("anthropic-beta", "oauth-2025-04-20")
-      .header("User-Agent", "claude-code/2.1.71")
-      .call()?;
+    // Not held across the retries, a concurrent request would wait out their delays.
+    let secret = self.token.lock().unwrap().secret.clone();
+    let mut response = http::with_retries(|| {
+      let request = ureq::get(url)
+        .header("Authorization", &format!("Bearer {}", secret.expose_secret()))
+        .header("anthropic-beta", "oauth-2025-04-20")
+        .header("User-Agent", "claude-code/2.1.71");
+
+      return http::call(http::with_headers(request, &self.headers));
+    })?;
 
-    return response.body_mut().read_to_string();
+    return http::read_body(&mut response);
   }
 }
 
@@ -464,8 +780,9 @@ impl DataProvider for ClaudeCodeProvider {
     return ProviderKind::ClaudeCode;
   }
 
-  fn fetch_data(&self) -> Option<UsageData> {
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
     let mut data: UsageData = self.fetch_usage()?.into();
+    data.source = self.api_base_url.clone();
 
     // Only bother fetching the overage grant if there's an extra-usage section to plumb
     // it into. Free credits make no sense for accounts without extra usage in the first place.
@@ -483,14 +800,60 @@ impl DataProvider for ClaudeCodeProvider {
       }
     }
 
-    return Some(data);
+    return Ok(data);
   }
 
   fn fetch_profile(&self) -> Option<TierInfo> {
-    return self.fetch_profile_response().map(|p| p.organization.rate_limit_tier.tier_info());
+    return self.fetch_profile_response().map(|p| p.tier_info());
   }
 
   fn tray_icon_svg(&self) -> &'static [u8] {
     return include_bytes!("../../resources/claude.svg");
   }
+
+  fn actions(&self) -> Vec<ProviderAction> {
+    return vec![ProviderAction {
+      label: "Manage Plan".to_string(),
+      url: "https://claude.ai/settings/billing".to_string(),
+    }];
+  }
+
+  fn plans_url(&self) -> Option<&'static str> {
+    return Some("https://claude.ai/upgrade");
+  }
+
+  fn auth_status(&self) -> Option<AuthStatus> {
+    let token = self.token.lock().unwrap();
+    let last_error = self.last_auth_error.lock().unwrap();
+
+    return Some(AuthStatus {
+      source: token.source.to_string(),
+      expires_at: token.expires_at,
+      last_error: last_error.as_ref().map(|error| (error.at, error.message.clone())),
+    });
+  }
+
+  fn token_diagnostics(&self) -> Option<String> {
+    let token = self.token.lock().unwrap();
+
+    let expiry = match token.expires_at {
+      Some(at) if at <= Timestamp::now() => format!("{at} (expired)"),
+      Some(at) => format!("{at} (in {})", time::format_reset_time(&at)),
+      None => "not provided by the source".to_string(),
+    };
+
+    let last_error = match &*self.last_auth_error.lock().unwrap() {
+      Some(error) => format!("{} ({})", error.message, error.at),
+      None => "none".to_string(),
+    };
+
+    return Some(format!(
+      "API: {}\nToken source: {}\nToken length: {}\nExpires: {}\nLast auth error: {}",
+      self.api_base_url,
+      token.source,
+      token.secret.expose_secret().len(),
+      expiry,
+      last_error,
+    ));
+  }
 }
@@ -4,9 +4,13 @@ use color_eyre::eyre::Result;
 use serde::{Deserialize, Serialize};
 
 use super::CliproxyClient;
-use crate::providers::{
-  DataProvider, ProviderKind, TierInfo, UsageData,
-  claude_code::{ProfileResponse, UsageResponse},
+use crate::{
+  profile_cache::PROFILE_CACHE_TTL,
+  providers::{
+    DataProvider, ProviderAction, ProviderError, ProviderKind, TierInfo, UsageData,
+    claude_code::{self, ProfileResponse, UsageResponse},
+  },
+  utils::http,
 };
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -19,11 +23,32 @@ pub struct CliproxyClaudeSettings {
 
   /// Auth index identifying which CLIProxy account to use.
   pub auth_index: String,
+
+  /// Extra headers added to every request to CLIProxy (e.g. access service tokens).
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+
+  /// Anthropic API base URL override for proxied requests.
+  pub api_base_url: Option<String>,
+}
+
+impl CliproxyClaudeSettings {
+  /// Requests the provider makes, for `--dry-run`.
+  pub fn planned_requests(&self) -> Vec<String> {
+    let base = claude_code::api_base_url(self.api_base_url.as_deref());
+    let proxy = format!("POST {}/v0/management/api-call", self.base_url.trim_end_matches('/'));
+
+    return vec![
+      format!("{proxy} (GET {base}/api/oauth/usage), every refresh"),
+      format!("{proxy} (GET {base}/api/oauth/profile), at most every {}s", PROFILE_CACHE_TTL.as_secs()),
+    ];
+  }
 }
 
 pub struct CliproxyClaudeProvider {
   client: CliproxyClient,
   auth_index: String,
+  api_base_url: String,
 }
 
 impl CliproxyClaudeProvider {
@@ -31,34 +56,44 @@ impl CliproxyClaudeProvider {
     log::info!("Initializing CLIProxy Claude provider");
 
     return Ok(Self {
-      client: CliproxyClient::new(&settings.base_url, &settings.management_token),
+      client: CliproxyClient::new(&settings.base_url, &settings.management_token, &settings.headers),
       auth_index: settings.auth_index.clone(),
+      api_base_url: claude_code::api_base_url(settings.api_base_url.as_deref()),
     });
   }
 
-  fn fetch_usage(&self) -> Option<UsageResponse> {
+  fn fetch_usage(&self) -> Result<UsageResponse, ProviderError> {
     log::debug!("Fetching usage data");
 
-    let body = self.api_get("https://api.anthropic.com/api/oauth/usage")?;
+    let url = format!("{}/api/oauth/usage", self.api_base_url);
+    let body = self.api_get(&url)?;
 
-    return serde_json::from_str(&body)
+    let usage = serde_json::from_str(&body)
       .inspect(|u: &UsageResponse| log::debug!("Parsed usage: {:?}", u))
-      .inspect_err(|e| log::warn!("Failed to parse usage response: {}", e))
-      .ok();
+      .inspect_err(|e| {
+        log::warn!("Failed to parse usage response: {}", e);
+        http::trace_parse_failure(&url, &body, e);
+      })?;
+
+    return Ok(usage);
   }
 
   fn fetch_profile_response(&self) -> Option<ProfileResponse> {
     log::debug!("Fetching profile data");
 
-    let body = self.api_get("https://api.anthropic.com/api/oauth/profile")?;
+    let url = format!("{}/api/oauth/profile", self.api_base_url);
+    let body = self.api_get(&url).ok()?;
 
     return serde_json::from_str(&body)
       .inspect(|p: &ProfileResponse| log::debug!("Parsed profile: {:?}", p))
-      .inspect_err(|e| log::warn!("Failed to parse profile response: {}", e))
+      .inspect_err(|e| {
+        log::warn!("Failed to parse profile response: {}", e);
+        http::trace_parse_failure(&url, &body, e);
+      })
       .ok();
   }
 
-  fn api_get(&self, url: &str) -> Option<String> {
+  fn api_get(&self, url: &str) -> Result<String, ProviderError> {
     let mut headers = HashMap::new();
     headers.insert("Authorization".to_string(), "Bearer $TOKEN$".to_string());
     headers.insert("Anthropic-Beta".to_string(), "oauth-2025-04-20".to_string());
@@ -73,15 +108,22 @@ impl DataProvider for CliproxyClaudeProvider {
     return ProviderKind::CliproxyClaude;
   }
 
-  fn fetch_data(&self) -> Option<UsageData> {
-    return Some(self.fetch_usage()?.into());
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
+    let mut data: UsageData = self.fetch_usage()?.into();
+    data.source = format!("{} via {}", self.api_base_url, self.client.base_url());
+
+    return Ok(data);
   }
 
   fn fetch_profile(&self) -> Option<TierInfo> {
-    return self.fetch_profile_response().map(|p| p.organization.rate_limit_tier.tier_info());
+    return self.fetch_profile_response().map(|p| p.tier_info());
   }
 
   fn tray_icon_svg(&self) -> &'static [u8] {
     return include_bytes!("../../../resources/claude.svg");
   }
+
+  fn actions(&self) -> Vec<ProviderAction> {
+    return vec![self.client.dashboard_action()];
+  }
 }
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 
 use color_eyre::eyre::Result;
-use jiff::Timestamp;
-use rgb::Rgb;
 use serde::{Deserialize, Serialize};
 
 use super::CliproxyClient;
-use crate::providers::{DataProvider, ProviderKind, TierInfo, UsageData, UsageWindow};
+use crate::{
+  profile_cache::PROFILE_CACHE_TTL,
+  providers::{
+    DataProvider, ProviderAction, ProviderError, ProviderKind, TierInfo, UsageData,
+    codex::{CODEX_USER_AGENT, UsageResponse, WHAM_USAGE_URL},
+  },
+  utils::http,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CliproxyCodexSettings {
@@ -18,6 +23,23 @@ pub struct CliproxyCodexSettings {
 
   /// Auth index identifying which CLIProxy account to use.
   pub auth_index: String,
+
+  /// Extra headers added to every request to CLIProxy (e.g. access service tokens).
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+}
+
+impl CliproxyCodexSettings {
+  /// Requests the provider makes, for `--dry-run`.
+  pub fn planned_requests(&self) -> Vec<String> {
+    let base = self.base_url.trim_end_matches('/');
+
+    return vec![
+      format!("GET {base}/v0/management/auth-files, every refresh"),
+      format!("POST {base}/v0/management/api-call (GET {WHAM_USAGE_URL}), every refresh"),
+      format!("Both again for the plan, at most every {}s", PROFILE_CACHE_TTL.as_secs()),
+    ];
+  }
 }
 
 pub struct CliproxyCodexProvider {
@@ -41,113 +63,56 @@ struct AuthIdToken {
   chatgpt_account_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, Copy, strum::EnumIter)]
-#[serde(rename_all = "lowercase")]
-enum SubscriptionTier {
-  Free,
-  Plus,
-  Pro,
-  Team,
-  Enterprise,
-}
-
-impl SubscriptionTier {
-  fn tier_info(&self) -> TierInfo {
-    return TierInfo {
-      name: self.to_string(),
-      color: match self {
-        SubscriptionTier::Free => Rgb::new(140, 140, 155),
-        SubscriptionTier::Plus => Rgb::new(90, 145, 210),
-        SubscriptionTier::Pro => Rgb::new(75, 175, 155),
-        SubscriptionTier::Team => Rgb::new(185, 135, 90),
-        SubscriptionTier::Enterprise => Rgb::new(130, 115, 180),
-      },
-    };
-  }
-}
-
-impl std::fmt::Display for SubscriptionTier {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    return match self {
-      SubscriptionTier::Free => write!(f, "Free"),
-      SubscriptionTier::Plus => write!(f, "Plus"),
-      SubscriptionTier::Pro => write!(f, "Pro"),
-      SubscriptionTier::Team => write!(f, "Team"),
-      SubscriptionTier::Enterprise => write!(f, "Enterprise"),
-    };
-  }
-}
-
-#[derive(Debug, Deserialize)]
-struct UsageResponse {
-  plan_type: Option<SubscriptionTier>,
-  rate_limit: Option<RateLimit>,
-  code_review_rate_limit: Option<RateLimit>,
-}
-
-#[derive(Debug, Deserialize)]
-struct RateLimit {
-  primary_window: Option<UsageBucket>,
-  secondary_window: Option<UsageBucket>,
-}
-
-#[derive(Debug, Deserialize)]
-struct UsageBucket {
-  used_percent: f64,
-  limit_window_seconds: i64,
-  reset_at: i64,
-}
-
 impl CliproxyCodexProvider {
   pub fn new(settings: &CliproxyCodexSettings) -> Result<Self> {
     log::info!("Initializing CLIProxy Codex provider");
 
     return Ok(Self {
-      client: CliproxyClient::new(&settings.base_url, &settings.management_token),
+      client: CliproxyClient::new(&settings.base_url, &settings.management_token, &settings.headers),
       auth_index: settings.auth_index.clone(),
     });
   }
 
-  fn fetch_chatgpt_account_id(&self) -> Option<String> {
+  fn fetch_chatgpt_account_id(&self) -> Result<String, ProviderError> {
     log::debug!("Fetching auth file metadata");
 
     let body = self.client.management_get("/v0/management/auth-files")?;
-    let response: AuthFilesResponse = serde_json::from_str(&body)
-      .inspect_err(|e| log::error!("Failed to parse auth-files response: {}", e))
-      .ok()?;
+    let response: AuthFilesResponse =
+      serde_json::from_str(&body).inspect_err(|e| log::error!("Failed to parse auth-files response: {}", e))?;
 
-    let auth_file = response.files.into_iter().find(|file| file.auth_index == self.auth_index).or_else(|| {
+    let auth_file = response.files.into_iter().find(|file| file.auth_index == self.auth_index).ok_or_else(|| {
       log::error!("No auth file found for auth index {}", self.auth_index);
-      None
+      return ProviderError::Auth(format!("no CLIProxy auth file with index {}", self.auth_index));
     })?;
 
-    return auth_file.id_token.and_then(|t| t.chatgpt_account_id).or_else(|| {
+    return auth_file.id_token.and_then(|t| t.chatgpt_account_id).ok_or_else(|| {
       log::error!("Auth file {} missing id_token.chatgpt_account_id", self.auth_index);
-      None
+      return ProviderError::Auth(format!("CLIProxy auth file {} has no ChatGPT account id", self.auth_index));
     });
   }
 
-  fn fetch_usage(&self) -> Option<UsageResponse> {
+  fn fetch_usage(&self) -> Result<UsageResponse, ProviderError> {
     let chatgpt_account_id = self
       .fetch_chatgpt_account_id()
-      .inspect(|_| log::debug!("Resolved ChatGPT account id for auth index {}", self.auth_index))
-      .or_else(|| {
-        log::error!("No ChatGPT account id found for auth index {}", self.auth_index);
-        None
-      })?;
+      .inspect(|_| log::debug!("Resolved ChatGPT account id for auth index {}", self.auth_index))?;
 
     let mut headers = HashMap::new();
     headers.insert("Authorization".to_string(), "Bearer $TOKEN$".to_string());
     headers.insert("Content-Type".to_string(), "application/json".to_string());
-    headers.insert("User-Agent".to_string(), "codex_cli_rs/0.76.0 (Debian 13.0.0; x86_64) WindowsTerminal".to_string());
+    headers.insert("User-Agent".to_string(), CODEX_USER_AGENT.to_string());
     headers.insert("Chatgpt-Account-Id".to_string(), chatgpt_account_id);
 
-    let body = self.client.api_get(&self.auth_index, "https://chatgpt.com/backend-api/wham/usage", headers)?;
+    let url = WHAM_USAGE_URL;
+    let body = self.client.api_get(&self.auth_index, url, headers)?;
 
-    return serde_json::from_str(&body)
+    let usage = serde_json::from_str(&body)
       .inspect(|u: &UsageResponse| log::debug!("Parsed codex usage: {:?}", u))
-      .inspect_err(|e| log::warn!("Failed to parse codex usage response: {}", e))
-      .ok();
+      .inspect_err(|e| {
+        log::warn!("Failed to parse codex usage response: {}", e);
+        http::trace_parse_failure(url, &body, e);
+      })?;
+
+    return Ok(usage);
   }
 }
 
@@ -156,66 +121,22 @@ impl DataProvider for CliproxyCodexProvider {
     return ProviderKind::CliproxyCodex;
   }
 
-  fn fetch_data(&self) -> Option<UsageData> {
-    let usage = self.fetch_usage()?;
-    let mut windows = Vec::new();
-
-    if let Some(rate_limit) = &usage.rate_limit {
-      if let Some(primary) = &rate_limit.primary_window {
-        windows.push(UsageWindow {
-          title: "5h Limit".to_string(),
-          short_title: Some("5h".to_string()),
-          utilization: primary.used_percent,
-          resets_at: Timestamp::from_second(primary.reset_at).ok(),
-          period_seconds: Some(primary.limit_window_seconds),
-        });
-      }
-
-      if let Some(secondary) = &rate_limit.secondary_window {
-        windows.push(UsageWindow {
-          title: "7d Limit".to_string(),
-          short_title: Some("7d".to_string()),
-          utilization: secondary.used_percent,
-          resets_at: Timestamp::from_second(secondary.reset_at).ok(),
-          period_seconds: Some(secondary.limit_window_seconds),
-        });
-      }
-    }
-
-    if let Some(code_review) = &usage.code_review_rate_limit {
-      if let Some(primary) = &code_review.primary_window {
-        windows.push(UsageWindow {
-          title: "Review 7d".to_string(),
-          short_title: None,
-          utilization: primary.used_percent,
-          resets_at: Timestamp::from_second(primary.reset_at).ok(),
-          period_seconds: Some(primary.limit_window_seconds),
-        });
-      }
-
-      if let Some(secondary) = &code_review.secondary_window {
-        windows.push(UsageWindow {
-          title: "Review 2".to_string(),
-          short_title: None,
-          utilization: secondary.used_percent,
-          resets_at: Timestamp::from_second(secondary.reset_at).ok(),
-          period_seconds: Some(secondary.limit_window_seconds),
-        });
-      }
-    }
-
-    return Some(UsageData {
-      api_usage: None,
-      peak_hours: None,
-      windows,
-    });
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
+    let mut data: UsageData = self.fetch_usage()?.into();
+    data.source = self.client.base_url().to_string();
+
+    return Ok(data);
   }
 
   fn fetch_profile(&self) -> Option<TierInfo> {
-    return self.fetch_usage().and_then(|u| u.plan_type.map(|t| t.tier_info()));
+    return self.fetch_usage().ok().and_then(|u| u.plan_type.map(|t| t.tier_info()));
   }
 
   fn tray_icon_svg(&self) -> &'static [u8] {
     return include_bytes!("../../../resources/codex.svg");
   }
+
+  fn actions(&self) -> Vec<ProviderAction> {
+    return vec![self.client.dashboard_action()];
+  }
 }
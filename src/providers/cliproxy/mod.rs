@@ -8,6 +8,11 @@ pub use codex::{CliproxyCodexProvider, CliproxyCodexSettings};
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+  providers::{ProviderAction, ProviderError},
+  utils::http,
+};
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ApiCallRequest {
@@ -26,33 +31,53 @@ struct ApiCallResponse {
 pub struct CliproxyClient {
   base_url: String,
   management_token: SecretString,
+  headers: HashMap<String, String>,
 }
 
 impl CliproxyClient {
-  pub fn new(base_url: &str, management_token: &str) -> Self {
+  pub fn base_url(&self) -> &str {
+    return &self.base_url;
+  }
+
+  /// Link to the management panel served by CLIProxy.
+  pub fn dashboard_action(&self) -> ProviderAction {
+    return ProviderAction {
+      label: "Open CLIProxy Dashboard".to_string(),
+      url: format!("{}/management.html", self.base_url),
+    };
+  }
+
+  pub fn new(base_url: &str, management_token: &str, headers: &HashMap<String, String>) -> Self {
     return Self {
       base_url: base_url.trim_end_matches('/').to_string(),
       management_token: SecretString::from(management_token.to_string()),
+      headers: headers.clone(),
     };
   }
 
-  pub fn management_get(&self, path: &str) -> Option<String> {
+  pub fn management_get(&self, path: &str) -> Result<String, ProviderError> {
     let endpoint = format!("{}{}", self.base_url, path);
 
-    let mut response = ureq::get(&endpoint)
-      .header("Authorization", &format!("Bearer {}", self.management_token.expose_secret()))
-      .call()
-      .inspect_err(|e| log::error!("Cliproxy management GET failed for {}: {}", path, e))
-      .ok()?;
-
-    return response
-      .body_mut()
-      .read_to_string()
-      .inspect_err(|e| log::error!("Failed to read cliproxy management GET response body: {}", e))
-      .ok();
+    let mut response = http::with_retries(|| {
+      let request = ureq::get(&endpoint) //
+        .header("Authorization", &format!("Bearer {}", self.management_token.expose_secret()));
+
+      return http::call(http::with_headers(request, &self.headers));
+    })
+    .inspect_err(|e| log::error!("Cliproxy management GET failed for {}: {}", path, e))?;
+
+    let body = http::read_body(&mut response)
+      .inspect_err(|e| log::error!("Failed to read cliproxy management GET response body: {}", e))?;
+
+    return Ok(body);
   }
 
-  pub fn api_get(&self, auth_index: &str, url: &str, headers: HashMap<String, String>) -> Option<String> {
+  pub fn api_get(
+    &self,
+    auth_index: &str,
+    url: &str,
+    headers: HashMap<String, String>,
+  ) -> Result<String, ProviderError> {
     log::debug!("Proxied GET {} via cliproxy", url);
 
     let request = ApiCallRequest {
@@ -63,32 +88,31 @@ impl CliproxyClient {
     };
 
     let endpoint = format!("{}/v0/management/api-call", self.base_url);
-    let json_body = serde_json::to_string(&request)
-      .inspect_err(|e| log::error!("Failed to serialize api-call request: {}", e))
-      .ok()?;
-
-    let mut response = ureq::post(&endpoint)
-      .header("Authorization", &format!("Bearer {}", self.management_token.expose_secret()))
-      .header("Content-Type", "application/json")
-      .send(&json_body)
-      .inspect_err(|e| log::error!("Cliproxy request failed for {}: {}", url, e))
-      .ok()?;
-
-    let response_text = response
-      .body_mut()
-      .read_to_string()
-      .inspect_err(|e| log::error!("Failed to read cliproxy response body: {}", e))
-      .ok()?;
-
-    let parsed: ApiCallResponse = serde_json::from_str(&response_text)
-      .inspect_err(|e| log::error!("Failed to parse cliproxy response: {}", e))
-      .ok()?;
+    let json_body =
+      serde_json::to_string(&request).inspect_err(|e| log::error!("Failed to serialize api-call request: {}", e))?;
+
+    let mut response = http::with_retries(|| {
+      let request = ureq::post(&endpoint)
+        .header("Authorization", &format!("Bearer {}", self.management_token.expose_secret()))
+        .header("Content-Type", "application/json");
+
+      return http::send(http::with_headers(request, &self.headers), &json_body);
+    })
+    .inspect_err(|e| log::error!("Cliproxy request failed for {}: {}", url, e))?;
+
+    let response_text =
+      http::read_body(&mut response).inspect_err(|e| log::error!("Failed to read cliproxy response body: {}", e))?;
+
+    let parsed: ApiCallResponse = serde_json::from_str(&response_text).inspect_err(|e| {
+      log::error!("Failed to parse cliproxy response: {}", e);
+      http::trace_parse_failure(&endpoint, &response_text, e);
+    })?;
 
     if parsed.status_code != 200 {
       log::error!("Cliproxy API returned status {}: {}", parsed.status_code, parsed.body);
-      return None;
+      return Err(ureq::Error::StatusCode(parsed.status_code).into());
     }
 
-    return Some(parsed.body);
+    return Ok(parsed.body);
   }
 }
@@ -0,0 +1,324 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use camino::Utf8PathBuf;
+use color_eyre::eyre::{Context as _, ContextCompat as _, Result};
+use jiff::Timestamp;
+use rgb::Rgb;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  profile_cache::PROFILE_CACHE_TTL,
+  providers::{AuthStatus, DataProvider, ProviderError, ProviderKind, TierInfo, UsageData, UsageWindow},
+  utils::{http, json, sandbox},
+};
+
+pub const WHAM_USAGE_URL: &str = "https://chatgpt.com/backend-api/wham/usage";
+
+/// User agent of the Codex CLI, the usage endpoint only answers it.
+pub const CODEX_USER_AGENT: &str = "codex_cli_rs/0.76.0 (Debian 13.0.0; x86_64) WindowsTerminal";
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct CodexSettings {
+  /// Codex CLI auth file override. If not set, reads `$CODEX_HOME/auth.json` or `~/.codex/auth.json`.
+  pub auth_file: Option<Utf8PathBuf>,
+
+  /// Extra headers added to every request.
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+}
+
+impl CodexSettings {
+  /// Requests the provider makes, for `--dry-run`.
+  pub fn planned_requests(&self) -> Vec<String> {
+    let auth_file = match auth_file_path(self) {
+      Ok(path) => path.to_string(),
+      Err(e) => format!("unknown ({e})"),
+    };
+
+    return vec![
+      format!("GET {WHAM_USAGE_URL}, every refresh"),
+      format!("GET {WHAM_USAGE_URL} again for the plan, at most every {}s", PROFILE_CACHE_TTL.as_secs()),
+      format!("Token from {auth_file}"),
+    ];
+  }
+}
+
+/// Resolves the auth file the Codex CLI writes when signing in with ChatGPT.
+fn auth_file_path(settings: &CodexSettings) -> Result<Utf8PathBuf> {
+  if let Some(path) = &settings.auth_file {
+    return Ok(path.clone());
+  }
+
+  if let Ok(codex_home) = std::env::var("CODEX_HOME") {
+    return Ok(Utf8PathBuf::from(codex_home).join("auth.json"));
+  }
+
+  return Ok(sandbox::home_dir()?.join(".codex").join("auth.json"));
+}
+
+/// The parts of `auth.json` liment needs.
+#[derive(Deserialize)]
+struct AuthFile {
+  tokens: Option<AuthTokens>,
+}
+
+#[derive(Deserialize)]
+struct AuthTokens {
+  access_token: String,
+  account_id: Option<String>,
+}
+
+/// ChatGPT plan type. Plans this version doesn't know about are kept as the raw string.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(from = "String")]
+pub enum SubscriptionTier {
+  Free,
+  Plus,
+  Pro,
+  Team,
+  Enterprise,
+  Unknown(String),
+}
+
+impl From<String> for SubscriptionTier {
+  fn from(raw: String) -> Self {
+    return match raw.as_str() {
+      "free" => SubscriptionTier::Free,
+      "plus" => SubscriptionTier::Plus,
+      "pro" => SubscriptionTier::Pro,
+      "team" => SubscriptionTier::Team,
+      "enterprise" => SubscriptionTier::Enterprise,
+      _ => {
+        log::warn!("Unknown plan type: {raw}");
+        SubscriptionTier::Unknown(raw)
+      }
+    };
+  }
+}
+
+impl SubscriptionTier {
+  pub fn tier_info(&self) -> TierInfo {
+    return TierInfo {
+      name: self.to_string(),
+      color: match self {
+        SubscriptionTier::Free => Rgb::new(140, 140, 155),
+        SubscriptionTier::Plus => Rgb::new(90, 145, 210),
+        SubscriptionTier::Pro => Rgb::new(75, 175, 155),
+        SubscriptionTier::Team => Rgb::new(185, 135, 90),
+        SubscriptionTier::Enterprise => Rgb::new(130, 115, 180),
+        SubscriptionTier::Unknown(_) => Rgb::new(120, 120, 125),
+      },
+      account: None,
+      renews_at: None,
+    };
+  }
+}
+
+impl std::fmt::Display for SubscriptionTier {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    return match self {
+      SubscriptionTier::Free => write!(f, "Free"),
+      SubscriptionTier::Plus => write!(f, "Plus"),
+      SubscriptionTier::Pro => write!(f, "Pro"),
+      SubscriptionTier::Team => write!(f, "Team"),
+      SubscriptionTier::Enterprise => write!(f, "Enterprise"),
+      SubscriptionTier::Unknown(raw) => write!(f, "Unknown ({raw})"),
+    };
+  }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageResponse {
+  #[serde(default)]
+  pub plan_type: Option<SubscriptionTier>,
+  #[serde(default, deserialize_with = "json::lenient")]
+  rate_limit: Option<RateLimit>,
+  #[serde(default, deserialize_with = "json::lenient")]
+  code_review_rate_limit: Option<RateLimit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimit {
+  #[serde(default, deserialize_with = "json::lenient")]
+  primary_window: Option<UsageBucket>,
+  #[serde(default, deserialize_with = "json::lenient")]
+  secondary_window: Option<UsageBucket>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageBucket {
+  used_percent: f64,
+  limit_window_seconds: i64,
+  reset_at: i64,
+}
+
+impl From<UsageResponse> for UsageData {
+  fn from(usage: UsageResponse) -> Self {
+    let mut windows = Vec::new();
+
+    if let Some(rate_limit) = &usage.rate_limit {
+      if let Some(primary) = &rate_limit.primary_window {
+        windows.push(UsageWindow {
+          title: "5h Limit".to_string(),
+          short_title: Some("5h".to_string()),
+          utilization: primary.used_percent,
+          resets_at: Timestamp::from_second(primary.reset_at).ok(),
+          period_seconds: Some(primary.limit_window_seconds),
+          group: None,
+          amount: None,
+        });
+      }
+
+      if let Some(secondary) = &rate_limit.secondary_window {
+        windows.push(UsageWindow {
+          title: "7d Limit".to_string(),
+          short_title: Some("7d".to_string()),
+          utilization: secondary.used_percent,
+          resets_at: Timestamp::from_second(secondary.reset_at).ok(),
+          period_seconds: Some(secondary.limit_window_seconds),
+          group: None,
+          amount: None,
+        });
+      }
+    }
+
+    if let Some(code_review) = &usage.code_review_rate_limit {
+      if let Some(primary) = &code_review.primary_window {
+        windows.push(UsageWindow {
+          title: "Review 7d".to_string(),
+          short_title: None,
+          utilization: primary.used_percent,
+          resets_at: Timestamp::from_second(primary.reset_at).ok(),
+          period_seconds: Some(primary.limit_window_seconds),
+          group: Some("Code review".to_string()),
+          amount: None,
+        });
+      }
+
+      if let Some(secondary) = &code_review.secondary_window {
+        windows.push(UsageWindow {
+          title: "Review 2".to_string(),
+          short_title: None,
+          utilization: secondary.used_percent,
+          resets_at: Timestamp::from_second(secondary.reset_at).ok(),
+          period_seconds: Some(secondary.limit_window_seconds),
+          group: Some("Code review".to_string()),
+          amount: None,
+        });
+      }
+    }
+
+    return UsageData {
+      api_usage: None,
+      peak_hours: None,
+      windows,
+      fetched_at: Timestamp::now(),
+      source: WHAM_USAGE_URL.to_string(),
+    };
+  }
+}
+
+/// Reads the Codex CLI sign-in directly, without a proxy.
+pub struct CodexProvider {
+  auth_file: Utf8PathBuf,
+  headers: HashMap<String, String>,
+  last_auth_error: Mutex<Option<(Timestamp, String)>>,
+}
+
+impl CodexProvider {
+  pub fn new(settings: &CodexSettings) -> Result<Self> {
+    log::info!("Initializing Codex provider");
+
+    let auth_file = auth_file_path(settings)?;
+    Self::read_tokens(&auth_file)?;
+
+    return Ok(Self {
+      auth_file,
+      headers: settings.headers.clone(),
+      last_auth_error: Mutex::new(None),
+    });
+  }
+
+  /// Reads the tokens on every request, so ones the Codex CLI refreshed are picked up.
+  fn read_tokens(auth_file: &Utf8PathBuf) -> Result<AuthTokens> {
+    let contents = fs_err::read_to_string(auth_file).context("Codex CLI isn't signed in, run `codex login`")?;
+    let auth: AuthFile = serde_json::from_str(&contents).with_context(|| format!("Failed to parse {auth_file}"))?;
+
+    return auth.tokens.with_context(|| format!("{auth_file} has no ChatGPT sign-in, only an API key"));
+  }
+
+  fn fetch_usage(&self) -> Result<UsageResponse, ProviderError> {
+    log::debug!("Fetching codex usage");
+
+    let tokens = Self::read_tokens(&self.auth_file)
+      .inspect_err(|e| self.record_auth_error(format!("{e:#}")))
+      .map_err(|e| ProviderError::Auth(format!("{e:#}")))?;
+
+    let result = http::with_retries(|| {
+      let mut request = ureq::get(WHAM_USAGE_URL)
+        .header("Authorization", &format!("Bearer {}", tokens.access_token))
+        .header("User-Agent", CODEX_USER_AGENT);
+      if let Some(account_id) = &tokens.account_id {
+        request = request.header("Chatgpt-Account-Id", account_id);
+      }
+
+      return http::call(http::with_headers(request, &self.headers));
+    })
+    .and_then(|mut r| http::read_body(&mut r));
+    let body = match result {
+      Ok(body) => body,
+      Err(ureq::Error::StatusCode(401)) => {
+        log::warn!("Got 401 for {WHAM_USAGE_URL}, the Codex CLI sign-in expired");
+        self.record_auth_error("401, run `codex` to refresh the sign-in".to_string());
+        return Err(ProviderError::Auth("sign-in expired, run `codex` to refresh it".to_string()));
+      }
+      Err(e) => {
+        log::error!("Request failed for {WHAM_USAGE_URL}: {e}");
+        return Err(e.into());
+      }
+    };
+
+    let usage = serde_json::from_str(&body)
+      .inspect(|u: &UsageResponse| log::debug!("Parsed codex usage: {:?}", u))
+      .inspect_err(|e| {
+        log::warn!("Failed to parse codex usage response: {}", e);
+        http::trace_parse_failure(WHAM_USAGE_URL, &body, e);
+      })?;
+
+    return Ok(usage);
+  }
+
+  fn record_auth_error(&self, message: String) {
+    *self.last_auth_error.lock().unwrap() = Some((Timestamp::now(), message));
+  }
+}
+
+impl DataProvider for CodexProvider {
+  fn kind(&self) -> ProviderKind {
+    return ProviderKind::Codex;
+  }
+
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
+    return self.fetch_usage().map(UsageData::from);
+  }
+
+  fn fetch_profile(&self) -> Option<TierInfo> {
+    return self.fetch_usage().ok().and_then(|u| u.plan_type.map(|t| t.tier_info()));
+  }
+
+  fn tray_icon_svg(&self) -> &'static [u8] {
+    return include_bytes!("../../resources/codex.svg");
+  }
+
+  fn plans_url(&self) -> Option<&'static str> {
+    return Some("https://chatgpt.com/pricing");
+  }
+
+  fn auth_status(&self) -> Option<AuthStatus> {
+    return Some(AuthStatus {
+      source: format!("Codex CLI ({})", self.auth_file),
+      expires_at: None,
+      last_error: self.last_auth_error.lock().unwrap().clone(),
+    });
+  }
+}
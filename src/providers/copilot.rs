@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+
+use camino::Utf8PathBuf;
+use color_eyre::eyre::{Context as _, Result, bail};
+use jiff::{Timestamp, ToSpan as _, civil::Date, tz::TimeZone};
+use rgb::Rgb;
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  profile_cache::PROFILE_CACHE_TTL,
+  providers::{
+    DataProvider, ProviderAction, ProviderError, ProviderKind, TierInfo, UsageAmount, UsageData, UsageWindow,
+  },
+  utils::{http, json, sandbox},
+};
+
+pub const COPILOT_USER_URL: &str = "https://api.github.com/copilot_internal/user";
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct CopilotSettings {
+  /// GitHub OAuth token override. If not set, reads the one the Copilot editor plugins store in
+  /// `~/.config/github-copilot/`.
+  pub token: Option<String>,
+
+  /// Extra headers added to every request.
+  #[serde(default)]
+  pub headers: HashMap<String, String>,
+}
+
+impl CopilotSettings {
+  /// Requests the provider makes, for `--dry-run`.
+  pub fn planned_requests(&self) -> Vec<String> {
+    let token = if self.token.is_some() {
+      "provider settings".to_string()
+    }
+    else {
+      copilot_config_dir().to_string()
+    };
+
+    return vec![
+      format!("GET {COPILOT_USER_URL}, every refresh"),
+      format!("GET {COPILOT_USER_URL} again for the plan, at most every {}s", PROFILE_CACHE_TTL.as_secs()),
+      format!("Token from {token}"),
+    ];
+  }
+}
+
+/// Where the Copilot plugins (VS Code, JetBrains, Neovim) store the GitHub sign-in.
+fn copilot_config_dir() -> Utf8PathBuf {
+  return sandbox::config_dir().join("github-copilot");
+}
+
+/// Entry of `apps.json` or the older `hosts.json`, keyed by host (and app ID).
+#[derive(Deserialize)]
+struct CopilotHost {
+  oauth_token: String,
+}
+
+/// Copilot plan. Plans this version doesn't know about are kept as the raw string.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(from = "String")]
+enum CopilotPlan {
+  Free,
+  Individual,
+  IndividualPro,
+  Business,
+  Enterprise,
+  Unknown(String),
+}
+
+impl From<String> for CopilotPlan {
+  fn from(raw: String) -> Self {
+    return match raw.as_str() {
+      "free" => CopilotPlan::Free,
+      "individual" => CopilotPlan::Individual,
+      "individual_pro" => CopilotPlan::IndividualPro,
+      "business" => CopilotPlan::Business,
+      "enterprise" => CopilotPlan::Enterprise,
+      _ => {
+        log::warn!("Unknown Copilot plan: {raw}");
+        CopilotPlan::Unknown(raw)
+      }
+    };
+  }
+}
+
+impl CopilotPlan {
+  fn tier_info(&self) -> TierInfo {
+    return TierInfo {
+      name: self.to_string(),
+      color: match self {
+        CopilotPlan::Free => Rgb::new(140, 140, 155),
+        CopilotPlan::Individual => Rgb::new(90, 145, 210),
+        CopilotPlan::IndividualPro => Rgb::new(145, 110, 200),
+        CopilotPlan::Business => Rgb::new(185, 135, 90),
+        CopilotPlan::Enterprise => Rgb::new(130, 115, 180),
+        CopilotPlan::Unknown(_) => Rgb::new(120, 120, 125),
+      },
+      account: None,
+      renews_at: None,
+    };
+  }
+}
+
+impl std::fmt::Display for CopilotPlan {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    return match self {
+      CopilotPlan::Free => write!(f, "Free"),
+      CopilotPlan::Individual => write!(f, "Pro"),
+      CopilotPlan::IndividualPro => write!(f, "Pro+"),
+      CopilotPlan::Business => write!(f, "Business"),
+      CopilotPlan::Enterprise => write!(f, "Enterprise"),
+      CopilotPlan::Unknown(raw) => write!(f, "Unknown ({raw})"),
+    };
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+  #[serde(default)]
+  copilot_plan: Option<CopilotPlan>,
+
+  /// Day the monthly quotas reset (e.g. "2025-09-01").
+  #[serde(default)]
+  quota_reset_date: Option<Date>,
+
+  #[serde(default, deserialize_with = "json::lenient")]
+  quota_snapshots: Option<QuotaSnapshots>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotaSnapshots {
+  #[serde(default, deserialize_with = "json::lenient")]
+  premium_interactions: Option<QuotaSnapshot>,
+  #[serde(default, deserialize_with = "json::lenient")]
+  chat: Option<QuotaSnapshot>,
+  #[serde(default, deserialize_with = "json::lenient")]
+  completions: Option<QuotaSnapshot>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotaSnapshot {
+  entitlement: f64,
+  remaining: f64,
+  percent_remaining: f64,
+  #[serde(default)]
+  unlimited: bool,
+}
+
+pub struct CopilotProvider {
+  token: SecretString,
+  headers: HashMap<String, String>,
+}
+
+impl CopilotProvider {
+  pub fn new(settings: &CopilotSettings) -> Result<Self> {
+    log::info!("Initializing Copilot provider");
+
+    let token = match &settings.token {
+      Some(token) => token.clone(),
+      None => Self::read_plugin_token()?,
+    };
+
+    return Ok(Self {
+      token: SecretString::from(token),
+      headers: settings.headers.clone(),
+    });
+  }
+
+  /// Reads the github.com token from `apps.json`, or `hosts.json` written by older plugins.
+  fn read_plugin_token() -> Result<String> {
+    let dir = copilot_config_dir();
+
+    for file in ["apps.json", "hosts.json"] {
+      let path = dir.join(file);
+      if !fs_err::exists(&path).unwrap_or(false) {
+        continue;
+      }
+
+      let hosts: HashMap<String, CopilotHost> =
+        serde_json::from_str(&fs_err::read_to_string(&path)?).with_context(|| format!("Failed to parse {path}"))?;
+
+      if let Some((_, host)) = hosts.into_iter().find(|(key, _)| key.starts_with("github.com")) {
+        log::info!("Using Copilot token from {path}");
+        return Ok(host.oauth_token);
+      }
+    }
+
+    bail!("No Copilot sign-in found in {dir}, sign in to Copilot in your editor");
+  }
+
+  fn fetch_user(&self) -> Result<UserResponse, ProviderError> {
+    log::debug!("Fetching Copilot quotas");
+
+    let body = http::with_retries(|| {
+      let request = ureq::get(COPILOT_USER_URL)
+        .header("Authorization", &format!("token {}", self.token.expose_secret()))
+        .header("Accept", "application/json")
+        .header("User-Agent", "liment");
+
+      return http::call(http::with_headers(request, &self.headers));
+    })
+    .and_then(|mut response| http::read_body(&mut response))
+    .inspect_err(|e| log::error!("Request failed for {COPILOT_USER_URL}: {e}"))?;
+
+    let user = serde_json::from_str(&body)
+      .inspect(|u: &UserResponse| log::debug!("Parsed Copilot user: {:?}", u))
+      .inspect_err(|e| {
+        log::warn!("Failed to parse Copilot user response: {}", e);
+        http::trace_parse_failure(COPILOT_USER_URL, &body, e);
+      })?;
+
+    return Ok(user);
+  }
+}
+
+/// Window of a monthly quota, or `None` if it's unlimited.
+fn quota_window(
+  title: &str,
+  short_title: Option<&str>,
+  unit: &str,
+  quota: &QuotaSnapshot,
+  resets_at: Option<Timestamp>,
+) -> Option<UsageWindow> {
+  if quota.unlimited {
+    return None;
+  }
+
+  // A month, from the same day of the previous month.
+  let period_seconds = resets_at.and_then(|resets_at| {
+    let resets_at = resets_at.to_zoned(TimeZone::UTC);
+    let start = resets_at.checked_sub(1.month()).ok()?;
+    return Some(resets_at.timestamp().as_second() - start.timestamp().as_second());
+  });
+
+  return Some(UsageWindow {
+    title: title.to_string(),
+    short_title: short_title.map(str::to_string),
+    utilization: 100.0 - quota.percent_remaining,
+    resets_at,
+    period_seconds,
+    group: None,
+    amount: Some(UsageAmount {
+      used: (quota.entitlement - quota.remaining).max(0.0),
+      total: quota.entitlement,
+      unit: unit.to_string(),
+    }),
+  });
+}
+
+impl DataProvider for CopilotProvider {
+  fn kind(&self) -> ProviderKind {
+    return ProviderKind::Copilot;
+  }
+
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
+    let user = self.fetch_user()?;
+
+    // Quotas reset at the start of the day, UTC.
+    let resets_at = user.quota_reset_date.and_then(|date| date.to_zoned(TimeZone::UTC).ok()).map(|z| z.timestamp());
+
+    let mut windows = Vec::new();
+    if let Some(quotas) = &user.quota_snapshots {
+      let quotas = [
+        ("Premium Requests", Some("req"), "requests", &quotas.premium_interactions),
+        ("Chat", None, "messages", &quotas.chat),
+        ("Completions", None, "completions", &quotas.completions),
+      ];
+
+      for (title, short_title, unit, quota) in quotas {
+        if let Some(window) = quota.as_ref().and_then(|q| quota_window(title, short_title, unit, q, resets_at)) {
+          windows.push(window);
+        }
+      }
+    }
+
+    return Ok(UsageData {
+      api_usage: None,
+      peak_hours: None,
+      windows,
+      fetched_at: Timestamp::now(),
+      source: COPILOT_USER_URL.to_string(),
+    });
+  }
+
+  fn fetch_profile(&self) -> Option<TierInfo> {
+    return self.fetch_user().ok().and_then(|u| u.copilot_plan.map(|p| p.tier_info()));
+  }
+
+  fn tray_icon_svg(&self) -> &'static [u8] {
+    return include_bytes!("../../resources/copilot.svg");
+  }
+
+  fn actions(&self) -> Vec<ProviderAction> {
+    return vec![ProviderAction {
+      label: "Open Copilot Settings".to_string(),
+      url: "https://github.com/settings/copilot".to_string(),
+    }];
+  }
+
+  fn plans_url(&self) -> Option<&'static str> {
+    return Some("https://github.com/features/copilot/plans");
+  }
+}
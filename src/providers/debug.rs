@@ -4,8 +4,12 @@ use jiff::Timestamp;
 use rgb::Rgb;
 
 use crate::{
+  config::DisplayMode,
   constants::*,
-  providers::{ApiUsage, DataProvider, PeakHoursInfo, ProviderKind, TierInfo, UsageData},
+  providers::{
+    ApiUsage, AuthStatus, DataProvider, PeakHoursInfo, ProviderAction, ProviderError, ProviderKind, TierInfo,
+    TrayDescriptor, TrayLine, TrayValue, UsageAmount, UsageData,
+  },
 };
 
 /// Wraps another provider and overrides its data with values from environment variables.
@@ -16,6 +20,8 @@ pub struct DebugProvider {
   resets_in: Option<i64>,
   extra_usage: Option<ApiUsage>,
   peak_hours: Option<bool>,
+  amount: Option<(f64, String)>,
+  tray_spend: bool,
 }
 
 impl DebugProvider {
@@ -26,14 +32,25 @@ impl DebugProvider {
     let tier = std::env::var(LIMENT_DEBUG_TIER).ok().and_then(|v| parse_tier(&v));
     let extra_usage = std::env::var(LIMENT_DEBUG_EXTRA_USAGE).ok().and_then(|v| parse_extra_usage(&v));
     let peak_hours = std::env::var(LIMENT_DEBUG_PEAK_HOURS).ok().and_then(|v| parse_bool(&v));
-
-    if utilization.is_none() && resets_in.is_none() && tier.is_none() && extra_usage.is_none() && peak_hours.is_none() {
+    let amount = std::env::var(LIMENT_DEBUG_AMOUNT).ok().and_then(|v| parse_amount(&v));
+    let tray_spend = std::env::var(LIMENT_DEBUG_TRAY_SPEND).ok().and_then(|v| parse_bool(&v)).unwrap_or(false);
+
+    if { true }
+      && utilization.is_none()
+      && resets_in.is_none()
+      && tier.is_none()
+      && extra_usage.is_none()
+      && peak_hours.is_none()
+      && amount.is_none()
+      && !tray_spend
+    {
       return None;
     }
 
     log::info!(
       "Debug overrides active: utilization={utilization:?}, resets_in={resets_in:?}, \
-       tier={}, extra_usage={}, peak_hours={peak_hours:?}",
+       tier={}, extra_usage={}, peak_hours={peak_hours:?}, amount={amount:?}, \
+       tray_spend={tray_spend}",
       tier.is_some(),
       extra_usage.is_some(),
     );
@@ -45,6 +62,8 @@ impl DebugProvider {
       tier,
       extra_usage,
       peak_hours,
+      amount,
+      tray_spend,
     });
   }
 }
@@ -69,6 +88,8 @@ fn parse_tier(s: &str) -> Option<TierInfo> {
   return Some(TierInfo {
     name: name.to_string(),
     color: Rgb::new(r, g, b),
+    account: None,
+    renews_at: None,
   });
 }
 
@@ -96,13 +117,21 @@ fn parse_extra_usage(s: &str) -> Option<ApiUsage> {
   });
 }
 
+/// Parses "total" or "total:unit" (unit defaults to "requests").
+fn parse_amount(s: &str) -> Option<(f64, String)> {
+  let (total, unit) = s.split_once(':').unwrap_or((s, "requests"));
+
+  return Some((total.trim().parse().ok()?, unit.trim().to_string()));
+}
+
 impl DataProvider for DebugProvider {
   fn kind(&self) -> ProviderKind {
     return self.inner.kind();
   }
 
-  fn fetch_data(&self) -> Option<UsageData> {
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
     let mut data = self.inner.fetch_data()?;
+    data.source = format!("{} (debug overrides)", data.source);
 
     for window in &mut data.windows {
       if let Some(utilization) = self.utilization {
@@ -112,6 +141,14 @@ impl DataProvider for DebugProvider {
       if let Some(resets_in) = self.resets_in {
         window.resets_at = Some(Timestamp::now().checked_add(jiff::SignedDuration::from_secs(resets_in)).unwrap());
       }
+
+      if let Some((total, unit)) = &self.amount {
+        window.amount = Some(UsageAmount {
+          used: total * window.utilization / 100.0,
+          total: *total,
+          unit: unit.clone(),
+        });
+      }
     }
 
     if let Some(ref extra_usage) = self.extra_usage {
@@ -132,15 +169,12 @@ impl DataProvider for DebugProvider {
       data.peak_hours = Some(PeakHoursInfo { is_peak, ends_at });
     }
 
-    return Some(data);
+    return Ok(data);
   }
 
   fn fetch_profile(&self) -> Option<TierInfo> {
     if let Some(ref tier) = self.tier {
-      return Some(TierInfo {
-        name: tier.name.clone(),
-        color: tier.color,
-      });
+      return Some(tier.clone());
     }
 
     return self.inner.fetch_profile();
@@ -149,4 +183,39 @@ impl DataProvider for DebugProvider {
   fn tray_icon_svg(&self) -> &'static [u8] {
     return self.inner.tray_icon_svg();
   }
+
+  fn tray_descriptor(&self, data: &UsageData, display_mode: DisplayMode) -> TrayDescriptor {
+    let mut descriptor = self.inner.tray_descriptor(data, display_mode);
+
+    if let Some(api_usage) = data.api_usage.as_ref().filter(|_| self.tray_spend) {
+      let utilization = api_usage.max_paid_usd.filter(|max| *max > 0.0).map(|max| api_usage.usage_usd / max);
+
+      descriptor.lines.truncate(1);
+      descriptor.lines.push(TrayLine {
+        label: "ex".to_string(),
+        value: TrayValue::Dollars(api_usage.usage_usd),
+        precision: 1,
+        utilization: utilization.unwrap_or(0.0),
+        pacing_warning: false,
+      });
+    }
+
+    return descriptor;
+  }
+
+  fn actions(&self) -> Vec<ProviderAction> {
+    return self.inner.actions();
+  }
+
+  fn plans_url(&self) -> Option<&'static str> {
+    return self.inner.plans_url();
+  }
+
+  fn auth_status(&self) -> Option<AuthStatus> {
+    return self.inner.auth_status();
+  }
+
+  fn token_diagnostics(&self) -> Option<String> {
+    return self.inner.token_diagnostics();
+  }
 }
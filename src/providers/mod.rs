@@ -6,15 +6,22 @@ use rgb::Rgb;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+  config::DisplayMode,
   providers::{
+    claude_api::{ClaudeApiProvider, ClaudeApiSettings},
     claude_code::{ClaudeCodeProvider, ClaudeCodeSettings},
     cliproxy::{CliproxyClaudeProvider, CliproxyClaudeSettings, CliproxyCodexProvider, CliproxyCodexSettings},
+    codex::{CodexProvider, CodexSettings},
+    copilot::{CopilotProvider, CopilotSettings},
   },
-  utils::notification,
+  utils::{notification, time},
 };
 
+pub mod claude_api;
 pub mod claude_code;
 pub mod cliproxy;
+pub mod codex;
+pub mod copilot;
 pub mod debug;
 
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, Hash, PartialEq, Eq, strum::Display, strum::EnumIter)]
@@ -26,6 +33,11 @@ pub enum ProviderKind {
   CliproxyClaude,
   #[strum(to_string = "Cliproxy Codex")]
   CliproxyCodex,
+  #[strum(to_string = "Claude API")]
+  ClaudeApi,
+  Codex,
+  #[strum(to_string = "GitHub Copilot")]
+  Copilot,
   #[serde(other)]
   Unknown,
 }
@@ -35,11 +47,93 @@ pub struct ProviderSettings {
   pub claude_code: Option<ClaudeCodeSettings>,
   pub cliproxy_claude: Option<CliproxyClaudeSettings>,
   pub cliproxy_codex: Option<CliproxyCodexSettings>,
+  pub claude_api: Option<ClaudeApiSettings>,
+  pub codex: Option<CodexSettings>,
+  pub copilot: Option<CopilotSettings>,
 }
 
+#[derive(Clone)]
 pub struct TierInfo {
   pub name: String,
   pub color: Rgb<u8>,
+
+  /// Account the data belongs to, if the provider can identify it.
+  pub account: Option<AccountInfo>,
+
+  /// When the subscription renews, if the provider reports it.
+  pub renews_at: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccountInfo {
+  /// Stable identifier (e.g. organization UUID).
+  pub id: String,
+
+  /// Human-readable name (e.g. organization name or email).
+  pub name: String,
+}
+
+/// State of the credentials a provider uses, for the "Authentication" submenu.
+pub struct AuthStatus {
+  /// Where the credentials were loaded from (e.g. "keychain (Claude Code credentials)").
+  pub source: String,
+
+  /// When the credentials expire, if the source says.
+  pub expires_at: Option<Timestamp>,
+
+  /// Last authentication failure and when it happened.
+  pub last_error: Option<(Timestamp, String)>,
+}
+
+/// Why fetching usage failed, shown in the menu instead of the usage.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum ProviderError {
+  /// No response, e.g. offline or timed out.
+  Network(String),
+
+  /// The server responded with an error status.
+  Server(u16),
+
+  /// The credentials are missing, expired or were rejected.
+  Auth(String),
+
+  /// The response didn't have the expected shape.
+  Parse(String),
+
+  /// The API is rate limiting requests, until the given time if known.
+  RateLimited(Option<Timestamp>),
+}
+
+impl std::fmt::Display for ProviderError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    return match self {
+      ProviderError::Network(reason) => write!(f, "Network error: {reason}"),
+      ProviderError::Server(status) => write!(f, "Server error: responded with {status}"),
+      ProviderError::Auth(reason) => write!(f, "Authentication failed: {reason}"),
+      ProviderError::Parse(reason) => write!(f, "Unexpected response: {reason}"),
+      ProviderError::RateLimited(Some(until)) => write!(f, "Rate limited until {}", time::format_until_time(until)),
+      ProviderError::RateLimited(None) => write!(f, "Rate limited"),
+    };
+  }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<ureq::Error> for ProviderError {
+  fn from(error: ureq::Error) -> Self {
+    return match error {
+      ureq::Error::StatusCode(status @ (401 | 403)) => ProviderError::Auth(format!("server responded with {status}")),
+      ureq::Error::StatusCode(429) => ProviderError::RateLimited(None),
+      ureq::Error::StatusCode(status) => ProviderError::Server(status),
+      error => ProviderError::Network(error.to_string()),
+    };
+  }
+}
+
+impl From<serde_json::Error> for ProviderError {
+  fn from(error: serde_json::Error) -> Self {
+    return ProviderError::Parse(error.to_string());
+  }
 }
 
 pub struct PeakHoursInfo {
@@ -57,6 +151,12 @@ pub struct UsageData {
 
   /// Usage windows (e.g. 5h limit, 7d limit).
   pub windows: Vec<UsageWindow>,
+
+  /// When the data was fetched.
+  pub fetched_at: Timestamp,
+
+  /// Where the data came from (e.g. the endpoint base URL), set by the provider.
+  pub source: String,
 }
 
 pub struct ApiUsage {
@@ -91,9 +191,32 @@ pub struct UsageWindow {
 
   /// Total period duration in seconds (e.g. 18000 for 5h, 604800 for 7d).
   pub period_seconds: Option<i64>,
+
+  /// Category shown as a sub-header above the window (e.g. "Model limits"). None = ungrouped.
+  pub group: Option<String>,
+
+  /// Absolute usage, for providers that count (e.g. requests) rather than only report a percentage.
+  pub amount: Option<UsageAmount>,
+}
+
+pub struct UsageAmount {
+  /// Amount used in the current period.
+  pub used: f64,
+
+  /// Amount allowed per period.
+  pub total: f64,
+
+  /// Unit of the amounts, plural (e.g. "requests").
+  pub unit: String,
 }
 
 impl UsageWindow {
+  /// Whether usage went past the limit. Some responses report this as utilization above 100%
+  /// while overage is being used.
+  pub fn is_over_limit(&self) -> bool {
+    return self.utilization > 100.0;
+  }
+
   /// Returns true if the current utilization is outpacing elapsed time for this bucket's period.
   pub fn is_pacing_warning(&self) -> bool {
     let Some(resets_at) = &self.resets_at
@@ -117,12 +240,87 @@ impl UsageWindow {
   }
 }
 
+/// Provider-specific link shown at the bottom of the provider's section in the menu.
+pub struct ProviderAction {
+  pub label: String,
+  pub url: String,
+}
+
+/// What the tray shows for a provider, up to two lines.
+pub struct TrayDescriptor {
+  pub lines: Vec<TrayLine>,
+}
+
+pub struct TrayLine {
+  /// Short label (e.g. "5h").
+  pub label: String,
+
+  pub value: TrayValue,
+
+  /// Decimal places of the value.
+  pub precision: usize,
+
+  /// Utilization (0–1, above 1 when over the limit) used for the line color.
+  pub utilization: f64,
+
+  /// Whether usage is outpacing elapsed time.
+  pub pacing_warning: bool,
+}
+
+pub enum TrayValue {
+  Percent(f64),
+  Dollars(f64),
+}
+
+impl TrayLine {
+  /// Formats the value with its unit, e.g. "42%" or "$12.4".
+  pub fn formatted_value(&self) -> String {
+    let precision = self.precision;
+
+    return match self.value {
+      TrayValue::Percent(pct) => format!("{pct:.precision$}%"),
+      TrayValue::Dollars(usd) => format!("${usd:.precision$}"),
+    };
+  }
+}
+
+impl TrayDescriptor {
+  /// The first two windows with a short title, in whole percent used or remaining.
+  pub fn from_windows(data: &UsageData, display_mode: DisplayMode) -> Self {
+    let lines = data
+      .windows
+      .iter()
+      .filter_map(|window| {
+        let label = window.short_title.clone()?;
+
+        // Over the limit shows as 100% used, the line color tells it apart.
+        let used = window.utilization.min(100.0);
+        let pct = match display_mode {
+          DisplayMode::Usage => used,
+          DisplayMode::Remaining => 100.0 - used,
+        };
+
+        return Some(TrayLine {
+          label,
+          value: TrayValue::Percent(pct.trunc()),
+          precision: 0,
+          utilization: window.utilization / 100.0,
+          pacing_warning: window.is_pacing_warning(),
+        });
+      })
+      .take(2)
+      .collect();
+
+    return Self { lines };
+  }
+}
+
 pub trait DataProvider: Send + Sync {
   /// Returns the kind of this provider.
   fn kind(&self) -> ProviderKind;
 
-  /// Fetches usage data for the provider.
-  fn fetch_data(&self) -> Option<UsageData>;
+  /// Fetches usage data for the provider, or why it couldn't.
+  fn fetch_data(&self) -> Result<UsageData, ProviderError>;
 
   /// Fetches the account tier info. Returns `None` if the provider doesn't support it.
   fn fetch_profile(&self) -> Option<TierInfo> {
@@ -131,6 +329,34 @@ pub trait DataProvider: Send + Sync {
 
   /// Returns SVG bytes for tray icon.
   fn tray_icon_svg(&self) -> &'static [u8];
+
+  /// Describes what the tray shows for the data. Providers reporting spend rather than
+  /// windows can override this to show dollars.
+  fn tray_descriptor(&self, data: &UsageData, display_mode: DisplayMode) -> TrayDescriptor {
+    return TrayDescriptor::from_windows(data, display_mode);
+  }
+
+  /// Extra menu actions for this provider (e.g. a dashboard link).
+  fn actions(&self) -> Vec<ProviderAction> {
+    return Vec::new();
+  }
+
+  /// Page comparing plans or limit tiers, linked from the hint shown when a limit keeps being hit.
+  fn plans_url(&self) -> Option<&'static str> {
+    return None;
+  }
+
+  /// Where the credentials come from and when they expire. Returns `None` if the provider
+  /// doesn't support it.
+  fn auth_status(&self) -> Option<AuthStatus> {
+    return None;
+  }
+
+  /// Describes the credentials in use without revealing them (source, length, expiry, last
+  /// auth error). Returns `None` if the provider doesn't support it.
+  fn token_diagnostics(&self) -> Option<String> {
+    return None;
+  }
 }
 
 /// No-op provider used when the configured provider is unknown or unavailable.
@@ -141,8 +367,8 @@ impl DataProvider for NullProvider {
     return ProviderKind::Unknown;
   }
 
-  fn fetch_data(&self) -> Option<UsageData> {
-    return None;
+  fn fetch_data(&self) -> Result<UsageData, ProviderError> {
+    return Err(ProviderError::Auth("provider isn't set up".to_string()));
   }
 
   fn tray_icon_svg(&self) -> &'static [u8] {
@@ -151,6 +377,54 @@ impl DataProvider for NullProvider {
 }
 
 impl ProviderKind {
+  /// Requests the provider would make with these settings, without creating it (which may read
+  /// the keychain). Fails like `into_provider` when its settings are missing.
+  pub fn planned_requests(self, settings: &ProviderSettings) -> color_eyre::eyre::Result<Vec<String>> {
+    match self {
+      ProviderKind::ClaudeCode => {
+        return Ok(settings.claude_code.clone().unwrap_or_default().planned_requests());
+      }
+
+      ProviderKind::CliproxyClaude => {
+        let settings = settings
+          .cliproxy_claude
+          .as_ref()
+          .context("cliproxy_claude provider requires [settings.cliproxy_claude] in config")?;
+
+        return Ok(settings.planned_requests());
+      }
+
+      ProviderKind::CliproxyCodex => {
+        let settings = settings
+          .cliproxy_codex
+          .as_ref()
+          .context("cliproxy_codex provider requires [settings.cliproxy_codex] in config")?;
+
+        return Ok(settings.planned_requests());
+      }
+
+      ProviderKind::ClaudeApi => {
+        let settings = settings
+          .claude_api
+          .as_ref()
+          .filter(|settings| !settings.workspaces.is_empty())
+          .context("claude_api provider requires at least one workspace in [settings.claude_api]")?;
+
+        return Ok(settings.planned_requests());
+      }
+
+      ProviderKind::Codex => {
+        return Ok(settings.codex.clone().unwrap_or_default().planned_requests());
+      }
+
+      ProviderKind::Copilot => {
+        return Ok(settings.copilot.clone().unwrap_or_default().planned_requests());
+      }
+
+      ProviderKind::Unknown => return Ok(Vec::new()),
+    }
+  }
+
   pub fn into_provider(self, settings: &ProviderSettings) -> color_eyre::eyre::Result<Arc<dyn DataProvider>> {
     match self {
       ProviderKind::ClaudeCode => {
@@ -177,6 +451,27 @@ impl ProviderKind {
         return Ok(Arc::new(CliproxyCodexProvider::new(settings)?));
       }
 
+      ProviderKind::ClaudeApi => {
+        let settings = settings
+          .claude_api
+          .as_ref()
+          .context("claude_api provider requires [settings.claude_api] in config")?;
+
+        return Ok(Arc::new(ClaudeApiProvider::new(settings)?));
+      }
+
+      ProviderKind::Codex => {
+        let settings = settings.codex.clone().unwrap_or_default();
+
+        return Ok(Arc::new(CodexProvider::new(&settings)?));
+      }
+
+      ProviderKind::Copilot => {
+        let settings = settings.copilot.clone().unwrap_or_default();
+
+        return Ok(Arc::new(CopilotProvider::new(&settings)?));
+      }
+
       ProviderKind::Unknown => {
         let msg = "Unknown provider in config, falling back to null provider";
         log::error!("{msg}");
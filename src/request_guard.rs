@@ -0,0 +1,54 @@
+use std::{
+  collections::{HashMap, VecDeque},
+  time::{Duration, Instant},
+};
+
+use crate::providers::ProviderKind;
+
+const WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Caps fetches per provider over a sliding hour, protecting provider endpoints from short
+/// intervals or refresh loops.
+#[derive(Default)]
+pub struct RequestGuard {
+  attempts: HashMap<ProviderKind, VecDeque<Instant>>,
+}
+
+impl RequestGuard {
+  /// Records a fetch attempt for the provider. Returns `false` without recording if the
+  /// hourly cap is reached. A cap of `0` disables the guard.
+  pub fn try_acquire(&mut self, kind: ProviderKind, cap: u32) -> bool {
+    if cap == 0 {
+      return true;
+    }
+
+    let attempts = self.attempts.entry(kind).or_default();
+    while attempts.front().is_some_and(|at| at.elapsed() >= WINDOW) {
+      attempts.pop_front();
+    }
+
+    if attempts.len() >= cap as usize {
+      return false;
+    }
+
+    attempts.push_back(Instant::now());
+
+    return true;
+  }
+
+  /// Returns how long until the provider may fetch again, if it's currently throttled.
+  pub fn throttled_for(&self, kind: ProviderKind, cap: u32) -> Option<Duration> {
+    if cap == 0 {
+      return None;
+    }
+
+    let attempts = self.attempts.get(&kind)?;
+    let recent = attempts.iter().filter(|at| at.elapsed() < WINDOW).collect::<Vec<_>>();
+    if recent.len() < cap as usize {
+      return None;
+    }
+
+    // The oldest attempt within the window frees up the next slot.
+    return recent.first().map(|at| WINDOW.saturating_sub(at.elapsed()));
+  }
+}
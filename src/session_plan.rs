@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use jiff::{SignedDuration, Timestamp};
+
+use crate::providers::{UsageData, UsageWindow};
+
+/// How far back samples are kept for the burn rate.
+const RATE_WINDOW: SignedDuration = SignedDuration::from_hours(2);
+
+/// Minimum span between the oldest and newest sample before the burn rate is trusted.
+const MIN_RATE_SPAN: SignedDuration = SignedDuration::from_mins(15);
+
+/// Utilization a planned session should have left, in percent, to not get a warning.
+pub const MIN_SESSION_HEADROOM: f64 = 50.0;
+
+/// The window a heavy session would eat into: the first one shown in the tray (e.g. 5h).
+pub fn session_window(data: &UsageData) -> Option<&UsageWindow> {
+  return data.windows.iter().find(|w| w.short_title.is_some());
+}
+
+struct UsageSample {
+  at: Timestamp,
+  utilization: f64,
+}
+
+/// Samples the session window's utilization to estimate how fast it's being used.
+#[derive(Default)]
+pub struct BurnRate {
+  samples: VecDeque<UsageSample>,
+}
+
+impl BurnRate {
+  pub fn record(&mut self, window: &UsageWindow) {
+    let now = Timestamp::now();
+
+    // Utilization going down means the window reset, older samples belong to the previous period.
+    if self.samples.back().is_some_and(|s| window.utilization < s.utilization) {
+      self.samples.clear();
+    }
+
+    self.samples.push_back(UsageSample { at: now, utilization: window.utilization });
+
+    while self.samples.front().is_some_and(|s| now.duration_since(s.at) > RATE_WINDOW) {
+      self.samples.pop_front();
+    }
+  }
+
+  /// Recent utilization growth in percent per hour. `None` until enough samples have been collected.
+  pub fn per_hour(&self) -> Option<f64> {
+    let (oldest, latest) = (self.samples.front()?, self.samples.back()?);
+
+    let span = latest.at.duration_since(oldest.at);
+    if span < MIN_RATE_SPAN {
+      return None;
+    }
+
+    return Some((latest.utilization - oldest.utilization) / (span.as_secs_f64() / 3600.0));
+  }
+}
+
+/// Projected session window utilization at the start of a planned session.
+pub struct SessionForecast {
+  /// When the session is planned to start.
+  pub at: Timestamp,
+
+  /// Projected utilization at that time (0–100).
+  pub utilization: f64,
+}
+
+impl SessionForecast {
+  /// Extends the current burn rate up to `at`. If the window resets before then, the projection
+  /// starts over from zero at the reset.
+  pub fn compute(at: Timestamp, window: &UsageWindow, rate_per_hour: Option<f64>) -> Self {
+    let rate = rate_per_hour.unwrap_or(0.0).max(0.0);
+    let now = Timestamp::now();
+
+    let utilization = match window.resets_at {
+      Some(resets_at) if resets_at <= at => rate * at.duration_since(resets_at).as_secs_f64() / 3600.0,
+      _ => window.utilization + rate * at.duration_since(now).as_secs_f64().max(0.0) / 3600.0,
+    };
+
+    return Self { at, utilization: utilization.min(100.0) };
+  }
+
+  /// Whether the window is projected to have enough room left for a heavy session.
+  pub fn has_headroom(&self) -> bool {
+    return 100.0 - self.utilization >= MIN_SESSION_HEADROOM;
+  }
+}
@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+
+use crate::providers::UsageData;
+
+/// Rescales windows that have a personal soft limit so the limit reads as 100%. Windows are
+/// matched by tray label (e.g. "5h"), or by title when they have none. Everything downstream
+/// (tray, menu, colors and alerts) then works against the soft limit.
+pub fn apply(data: &mut UsageData, limits: &HashMap<String, f64>) {
+  for window in &mut data.windows {
+    let key = window.short_title.as_ref().unwrap_or(&window.title);
+    let Some(&limit) = limits.get(key)
+    else {
+      continue;
+    };
+
+    if limit <= 0.0 || limit >= 100.0 {
+      log::warn!("Ignoring soft limit {limit}% for {key}, it must be between 0 and 100");
+      continue;
+    }
+
+    window.utilization = window.utilization / limit * 100.0;
+    window.title = format!("{} (soft {limit:.0}%)", window.title);
+
+    if let Some(amount) = &mut window.amount {
+      amount.total = amount.total * limit / 100.0;
+    }
+  }
+}
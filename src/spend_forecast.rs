@@ -0,0 +1,89 @@
+use std::collections::VecDeque;
+
+use jiff::{SignedDuration, Timestamp, Zoned};
+
+/// How far back samples are kept for the recent-trend model.
+const TREND_WINDOW: SignedDuration = SignedDuration::from_hours(24);
+
+/// Minimum span between the oldest and newest sample before the trend model is trusted.
+const MIN_TREND_SPAN: SignedDuration = SignedDuration::from_hours(1);
+
+struct SpendSample {
+  at: Timestamp,
+  usage_usd: f64,
+}
+
+/// Projected end-of-month extra usage, in USD.
+pub struct SpendForecast {
+  /// Month-to-date spend extrapolated linearly over the whole month.
+  pub linear_usd: f64,
+
+  /// Current spend plus the recent spending rate applied to the rest of the month.
+  /// `None` until enough samples have been collected.
+  pub trend_usd: Option<f64>,
+}
+
+impl SpendForecast {
+  /// Returns the more pessimistic of the two projections.
+  pub fn worst_usd(&self) -> f64 {
+    return self.trend_usd.map_or(self.linear_usd, |t| t.max(self.linear_usd));
+  }
+}
+
+/// Samples extra usage spend over time and projects the monthly bill.
+#[derive(Default)]
+pub struct SpendForecaster {
+  samples: VecDeque<SpendSample>,
+}
+
+impl SpendForecaster {
+  /// Records the current month-to-date spend.
+  pub fn record(&mut self, usage_usd: f64) {
+    let now = Timestamp::now();
+
+    // Spend going down means the billing month rolled over, old samples are meaningless now.
+    if self.samples.back().is_some_and(|s| usage_usd < s.usage_usd) {
+      log::debug!("Extra usage decreased, resetting spend samples");
+      self.samples.clear();
+    }
+
+    self.samples.push_back(SpendSample { at: now, usage_usd });
+
+    while self.samples.front().is_some_and(|s| now.duration_since(s.at) > TREND_WINDOW) {
+      self.samples.pop_front();
+    }
+  }
+
+  /// Projects the end-of-month spend from the latest sample. The billing month is assumed to
+  /// follow the local calendar month.
+  pub fn forecast(&self) -> Option<SpendForecast> {
+    let latest = self.samples.back()?;
+
+    let now = Zoned::now();
+    let month_start = now.first_of_month().ok()?.start_of_day().ok()?;
+    let month_end = month_start.checked_add(jiff::Span::new().months(1)).ok()?;
+
+    let elapsed = now.duration_since(&month_start).as_secs_f64();
+    let total = month_end.duration_since(&month_start).as_secs_f64();
+    let remaining = month_end.duration_since(&now).as_secs_f64().max(0.0);
+
+    if elapsed <= 0.0 || total <= 0.0 {
+      return None;
+    }
+
+    let linear_usd = latest.usage_usd * total / elapsed;
+
+    let trend_usd = self.samples.front().and_then(|oldest| {
+      let span = latest.at.duration_since(oldest.at);
+      if span < MIN_TREND_SPAN {
+        return None;
+      }
+
+      let rate = (latest.usage_usd - oldest.usage_usd) / span.as_secs_f64();
+
+      return Some(latest.usage_usd + rate * remaining);
+    });
+
+    return Some(SpendForecast { linear_usd, trend_usd });
+  }
+}
@@ -0,0 +1,52 @@
+use color_eyre::eyre::{ContextCompat as _, Result, WrapErr as _};
+
+use crate::{
+  config::Config,
+  ipc::{self, UsageReport},
+  utils::time,
+};
+
+/// Prints the current usage, as text or as JSON. Asks the running app when there is one, so it
+/// answers instantly and doesn't add to the request volume, and only fetches itself otherwise.
+pub fn print(config: &Config, json: bool) -> Result<()> {
+  let report = match ipc::request("get-json") {
+    Ok(json) => serde_json::from_str::<Option<UsageReport>>(&json)?.context("Running instance sent no data")?,
+    Err(e) => {
+      log::debug!("No running instance, fetching directly: {e:#}");
+
+      let provider = config.provider.into_provider(&config.settings)?;
+      let data = provider.fetch_data().wrap_err("Failed to fetch usage data")?;
+
+      UsageReport::from_data(provider.kind(), false, Some(&data))
+    }
+  };
+
+  if json {
+    println!("{}", serde_json::to_string(&report)?);
+
+    return Ok(());
+  }
+
+  let updated = match &report.fetched_at {
+    Some(fetched_at) => format!("updated {}", time::format_past_time(fetched_at)),
+    None => "no data yet".to_string(),
+  };
+  let paused = if report.paused { ", paused" } else { "" };
+  println!("{} ({updated}{paused})", report.provider);
+
+  let width = report.windows.iter().map(|w| w.title.chars().count()).max().unwrap_or(0);
+  for window in &report.windows {
+    let resets = match &window.resets_at {
+      Some(resets_at) => format!("  resets in {}", time::format_reset_time(resets_at)),
+      None => String::new(),
+    };
+
+    println!("{:<width$}  {:>3.0}%{resets}", window.title, window.utilization);
+  }
+
+  if let Some(usd) = report.extra_usage_usd {
+    println!("{:<width$}  ${usd:.2}", "Extra usage");
+  }
+
+  return Ok(());
+}
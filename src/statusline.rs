@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use color_eyre::eyre::{Result, WrapErr as _};
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  config::{ColorScale, Config},
+  ipc,
+  providers::TrayDescriptor,
+  utils::cache,
+};
+
+const SNAPSHOT_CACHE_FILE: &str = "statusline.json";
+
+/// Tray lines as of the last fetch, written by the running app for `liment statusline`.
+#[derive(Deserialize, Serialize)]
+pub struct Snapshot {
+  fetched_at: Timestamp,
+  lines: Vec<SnapshotLine>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct SnapshotLine {
+  label: String,
+  value: String,
+  utilization: f64,
+  pacing_warning: bool,
+}
+
+impl Snapshot {
+  pub fn new(descriptor: &TrayDescriptor, fetched_at: Timestamp) -> Self {
+    let lines = descriptor
+      .lines
+      .iter()
+      .map(|line| {
+        return SnapshotLine {
+          label: line.label.clone(),
+          value: line.formatted_value(),
+          utilization: line.utilization,
+          pacing_warning: line.pacing_warning,
+        };
+      })
+      .collect();
+
+    return Self { fetched_at, lines };
+  }
+
+  pub fn store(&self) {
+    cache::store(SNAPSHOT_CACHE_FILE, self);
+  }
+
+  /// Formats the snapshot as a single line, e.g. "⚡5h 42% · 7d 61%". A `stale` snapshot is
+  /// marked as such, dimmed.
+  fn format(&self, color_scale: ColorScale, stale: bool) -> String {
+    let lines = self
+      .lines
+      .iter()
+      .map(|line| {
+        let text = format!("{} {}", line.label, line.value);
+        let text = match ansi_color(color_scale, line.utilization) {
+          Some(code) => format!("\x1b[{code}m{text}\x1b[0m"),
+          None => text,
+        };
+
+        if line.pacing_warning {
+          return format!("{text}\x1b[{}m⚠\x1b[0m", ansi_warning_color(color_scale));
+        }
+
+        return text;
+      })
+      .collect::<Vec<_>>();
+
+    if stale {
+      return format!("⚡{} \x1b[2m(stale)\x1b[0m", lines.join(" · "));
+    }
+
+    return format!("⚡{}", lines.join(" · "));
+  }
+}
+
+/// ANSI color code for a utilization level (0–1), following the tray colors. `None` keeps the
+/// terminal's default color.
+fn ansi_color(color_scale: ColorScale, pct: f64) -> Option<&'static str> {
+  return match color_scale {
+    ColorScale::Default => {
+      match pct {
+        p if p < 0.5 => None,
+        p if p < 0.75 => Some("33"),
+        p if p < 0.90 => Some("38;5;208"),
+        p if p <= 1.0 => Some("31"),
+        _ => Some("95"),
+      }
+    }
+    ColorScale::BluePurple => {
+      match pct {
+        p if p < 0.5 => None,
+        p if p < 0.75 => Some("36"),
+        p if p < 0.90 => Some("34"),
+        p if p <= 1.0 => Some("35"),
+        _ => Some("95"),
+      }
+    }
+  };
+}
+
+fn ansi_warning_color(color_scale: ColorScale) -> &'static str {
+  return match color_scale {
+    ColorScale::Default => "33",
+    ColorScale::BluePurple => "36",
+  };
+}
+
+/// Prints the status line. Uses the snapshot the running app keeps up to date, and only fetches
+/// when the app isn't running and the snapshot is missing or older than the refetch interval.
+/// While the app runs, an old snapshot is printed marked stale: the app may be paused, backing
+/// off or rate limited, and fetching here would get around that.
+pub fn print(config: &Config) -> Result<()> {
+  let max_age = Duration::from_secs(config.refetch_interval as u64);
+
+  let snapshot = cache::load::<Snapshot>(SNAPSHOT_CACHE_FILE);
+  let is_fresh = |snapshot: &Snapshot| {
+    return Timestamp::now().duration_since(snapshot.fetched_at).unsigned_abs() <= max_age;
+  };
+
+  let (snapshot, stale) = match snapshot {
+    Some(snapshot) if is_fresh(&snapshot) => (snapshot, false),
+    Some(snapshot) if ipc::is_running() => (snapshot, true),
+    None if ipc::is_running() => {
+      println!("⚡ no data yet");
+
+      return Ok(());
+    }
+    _ => {
+      let provider = config.provider.into_provider(&config.settings)?;
+      let data = provider.fetch_data().wrap_err("Failed to fetch usage data")?;
+
+      let snapshot = Snapshot::new(&provider.tray_descriptor(&data, config.display_mode), data.fetched_at);
+      snapshot.store();
+      (snapshot, false)
+    }
+  };
+
+  println!("{}", snapshot.format(config.color_scale, stale));
+
+  return Ok(());
+}
@@ -0,0 +1,84 @@
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{providers::UsageData, utils::http};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TeamSettings {
+  /// Sync endpoint of the self-hosted team server.
+  pub url: String,
+
+  /// Name shown to teammates. Nothing else identifying the account is sent.
+  pub member: String,
+
+  /// Bearer token for the sync endpoint, if it requires one.
+  pub token: Option<String>,
+}
+
+/// Anonymized 5h status pushed to the team server.
+#[derive(Serialize)]
+struct StatusReport<'a> {
+  member: &'a str,
+  utilization: f64,
+  resets_at: Option<Timestamp>,
+}
+
+#[derive(Deserialize)]
+struct TeamResponse {
+  members: Vec<TeamMember>,
+}
+
+/// A teammate's last reported 5h status.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TeamMember {
+  pub member: String,
+  pub utilization: f64,
+  #[serde(default)]
+  pub resets_at: Option<Timestamp>,
+}
+
+/// Pushes our 5h utilization and returns the statuses of the other team members.
+/// Performs a blocking HTTP request.
+pub fn sync(settings: &TeamSettings, data: &UsageData) -> Option<Vec<TeamMember>> {
+  let Some(window) = data.windows.iter().find(|w| w.short_title.as_deref() == Some("5h"))
+  else {
+    log::debug!("No 5h window to report to the team server");
+    return None;
+  };
+
+  let report = StatusReport {
+    member: &settings.member,
+    utilization: window.utilization,
+    resets_at: window.resets_at,
+  };
+
+  let json_body = serde_json::to_string(&report)
+    .inspect_err(|e| log::error!("Failed to serialize team status: {}", e))
+    .ok()?;
+
+  let mut response = http::with_retries(|| {
+    let mut request = ureq::post(&settings.url).header("Content-Type", "application/json");
+    if let Some(token) = &settings.token {
+      request = request.header("Authorization", &format!("Bearer {}", token));
+    }
+
+    return http::send(request, &json_body);
+  })
+  .inspect_err(|e| log::warn!("Team sync failed: {}", e))
+  .ok()?;
+
+  let body = http::read_body(&mut response)
+    .inspect_err(|e| log::warn!("Failed to read team sync response: {}", e))
+    .ok()?;
+
+  let response: TeamResponse = serde_json::from_str(&body)
+    .inspect_err(|e| {
+      log::warn!("Failed to parse team sync response: {}", e);
+      http::trace_parse_failure(&settings.url, &body, e);
+    })
+    .ok()?;
+
+  let teammates = response.members.into_iter().filter(|m| m.member != settings.member).collect();
+
+  return Some(teammates);
+}
@@ -1,15 +1,15 @@
 use objc2::{AnyThread, MainThreadMarker, MainThreadOnly, Message, define_class, rc::Retained, sel};
 use objc2_app_kit::{
   NSButton, NSColor, NSCursor, NSFont, NSFontAttributeName, NSFontWeightRegular, NSForegroundColorAttributeName,
-  NSImage, NSImageView, NSLayoutAttribute, NSLayoutConstraint, NSStackView, NSTextAlignment, NSTextField,
-  NSUserInterfaceLayoutOrientation, NSView, NSVisualEffectBlendingMode, NSVisualEffectMaterial, NSVisualEffectView,
-  NSWindow, NSWindowStyleMask,
+  NSImage, NSImageView, NSLayoutAttribute, NSLayoutConstraint, NSLineBreakMode, NSStackView, NSTextAlignment,
+  NSTextField, NSUserInterfaceLayoutOrientation, NSView, NSVisualEffectBlendingMode, NSVisualEffectMaterial,
+  NSVisualEffectView, NSWindow, NSWindowStyleMask,
 };
 use objc2_core_foundation::CGPoint;
 use objc2_foundation::{NSArray, NSMutableAttributedString, NSObjectProtocol, NSRange, NSRect, NSSize, NSString};
 
 use super::components::font_weight_semibold;
-use crate::{delegate::AppDelegate, utils::macos::NSViewExt};
+use crate::{CONFIG_PATH, delegate::AppDelegate, utils::macos::NSViewExt};
 
 fn activate(constraints: &[&NSLayoutConstraint]) {
   let array = NSArray::from_retained_slice(&constraints.iter().map(|c| c.retain()).collect::<Vec<_>>());
@@ -84,12 +84,23 @@ pub fn build_about_window(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<
   info.addArrangedSubview(&property_row(mtm, "Commit", env!("GIT_COMMIT_SHORT")));
   stack.addArrangedSubview(&info);
 
+  // Config file location, truncated in the middle so both the home and file name stay visible.
+  let config_path = NSTextField::labelWithString(&NSString::from_str(CONFIG_PATH.as_str()), mtm);
+  config_path.noAutoresize();
+  config_path.setSelectable(true);
+  config_path.setAlignment(NSTextAlignment::Center);
+  config_path.setLineBreakMode(NSLineBreakMode::ByTruncatingMiddle);
+  config_path.setFont(Some(&NSFont::monospacedSystemFontOfSize_weight(10.0, unsafe { NSFontWeightRegular })));
+  config_path.setTextColor(Some(&NSColor::tertiaryLabelColor()));
+  stack.addArrangedSubview(&config_path);
+
   // Links.
   let links = NSStackView::initWithFrame(mtm.alloc::<NSStackView>(), NSRect::ZERO);
   links.setOrientation(NSUserInterfaceLayoutOrientation::Horizontal);
   links.setSpacing(8.0);
   links.addArrangedSubview(&link_button(mtm, "Issues", app, sel!(onOpenIssues:)));
   links.addArrangedSubview(&link_button(mtm, "Source Code", app, sel!(onOpenSource:)));
+  links.addArrangedSubview(&link_button(mtm, "Logs", app, sel!(onOpenLogs:)));
   stack.addArrangedSubview(&links);
 
   // Stack constraints.
@@ -99,11 +110,12 @@ pub fn build_about_window(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<
     &effect.bottomAnchor().constraintEqualToAnchor_constant(&stack.bottomAnchor(), 36.0),
     &icon_view.widthAnchor().constraintEqualToConstant(64.0),
     &icon_view.heightAnchor().constraintEqualToConstant(64.0),
+    &config_path.widthAnchor().constraintLessThanOrEqualToConstant(220.0),
   ]);
 
   // Custom spacing between sections.
   stack.setCustomSpacing_afterView(24.0, &desc);
-  stack.setCustomSpacing_afterView(24.0, &info);
+  stack.setCustomSpacing_afterView(24.0, &config_path);
 
   window.center();
 
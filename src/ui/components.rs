@@ -1,14 +1,16 @@
+use block2::RcBlock;
 use jiff::Timestamp;
-use objc2::{MainThreadMarker, Message, rc::Retained};
+use objc2::{MainThreadMarker, Message, rc::Retained, runtime::Bool};
 use objc2_app_kit::{
-  NSColor, NSFont, NSLayoutConstraint, NSMenuItem, NSProgressIndicator, NSProgressIndicatorStyle, NSTextField, NSView,
+  NSBezierPath, NSColor, NSFont, NSImage, NSImageView, NSLayoutConstraint, NSMenuItem, NSProgressIndicator,
+  NSProgressIndicatorStyle, NSTextField, NSView,
 };
-use objc2_core_foundation::CGFloat;
-use objc2_foundation::{NSArray, NSString};
+use objc2_core_foundation::{CGFloat, CGPoint};
+use objc2_foundation::{NSArray, NSRect, NSSize, NSString};
 
 use crate::{
-  config::{DateTimeFormat, DisplayMode},
-  providers::{PeakHoursInfo, TierInfo},
+  config::{ColorScale, DateTimeFormat, DisplayMode, TrayFontWeight},
+  providers::{PeakHoursInfo, TierInfo, UsageAmount},
   utils::{
     macos::NSViewExt,
     time::{format_absolute_time, format_reset_time, format_until_time},
@@ -17,6 +19,7 @@ use crate::{
 
 const MENU_WIDTH: CGFloat = 256.0;
 const H_PADDING: CGFloat = 14.0;
+const HEADER_LOGO_SIZE: CGFloat = 14.0;
 
 fn font_weight_regular() -> CGFloat {
   return unsafe { objc2_app_kit::NSFontWeightRegular };
@@ -34,6 +37,21 @@ fn font_weight_light() -> CGFloat {
   return unsafe { objc2_app_kit::NSFontWeightLight };
 }
 
+fn font_weight_bold() -> CGFloat {
+  return unsafe { objc2_app_kit::NSFontWeightBold };
+}
+
+impl TrayFontWeight {
+  pub fn ns_weight(self) -> CGFloat {
+    return match self {
+      TrayFontWeight::Regular => font_weight_regular(),
+      TrayFontWeight::Medium => font_weight_medium(),
+      TrayFontWeight::Semibold => font_weight_semibold(),
+      TrayFontWeight::Bold => font_weight_bold(),
+    };
+  }
+}
+
 fn activate(constraints: &[&NSLayoutConstraint]) {
   let array = NSArray::from_retained_slice(&constraints.iter().map(|c| c.retain()).collect::<Vec<_>>());
 
@@ -55,6 +73,10 @@ pub struct BucketRowParams<'a> {
   pub show_pacing_warning: bool,
   pub reset_time_format: DateTimeFormat,
   pub display_mode: DisplayMode,
+  pub color_scale: ColorScale,
+  /// Tier badge shown next to the label, for the compact single-window layout without a header.
+  pub tier: Option<&'a TierInfo>,
+  pub amount: Option<&'a UsageAmount>,
 }
 
 pub fn bucket_row(mtm: MainThreadMarker, params: &BucketRowParams) -> Retained<NSMenuItem> {
@@ -103,32 +125,80 @@ pub fn bucket_row(mtm: MainThreadMarker, params: &BucketRowParams) -> Retained<N
     return reset_str;
   });
 
-  let utilization = if params.display_mode == DisplayMode::Remaining {
-    100.0 - params.utilization
+  // Over the limit shows as a full bar, with the label marked and colored instead.
+  let used = params.utilization.min(100.0);
+  let utilization = if params.display_mode == DisplayMode::Remaining { 100.0 - used } else { used };
+  let over_limit_color = if params.utilization > 100.0 {
+    Some(params.color_scale.utilization_color(params.utilization / 100.0))
   }
   else {
-    params.utilization
+    None
   };
-  let reset_color = if pacing_warning { Some(NSColor::systemYellowColor()) } else { None };
-  let view = progress_row(mtm, params.label, utilization, reset_str.as_deref(), reset_color.as_deref());
+  let reset_color = if pacing_warning { Some(params.color_scale.warning_color()) } else { None };
+
+  // Absolute amounts under the bar: "1 250 / 2 000 requests".
+  let amount_str = params.amount.map(|amount| {
+    match params.display_mode {
+      DisplayMode::Usage => format!("{} / {} {}", format_count(amount.used), format_count(amount.total), amount.unit),
+      DisplayMode::Remaining => {
+        let left = (amount.total - amount.used).max(0.0);
+        format!("{} / {} {} left", format_count(left), format_count(amount.total), amount.unit)
+      }
+    }
+  });
+
+  let view = progress_row(
+    mtm,
+    params.label,
+    utilization,
+    reset_str.as_deref(),
+    reset_color.as_deref(),
+    over_limit_color.as_deref(),
+    params.tier,
+    amount_str.as_deref(),
+  );
   let item = NSMenuItem::new(mtm);
   item.setView(Some(&view));
 
   return item;
 }
 
+/// Formats a count with space-separated thousands (1250 → "1 250").
+fn format_count(value: f64) -> String {
+  let digits = (value.max(0.0).round() as u64).to_string();
+  let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+
+  for (i, c) in digits.chars().enumerate() {
+    if i > 0 && (digits.len() - i) % 3 == 0 {
+      result.push(' ');
+    }
+    result.push(c);
+  }
+
+  return result;
+}
+
 pub fn progress_row(
   mtm: MainThreadMarker,
   label: &str,
   utilization: f64,
   reset_str: Option<&str>,
   reset_color: Option<&NSColor>,
+  over_limit_color: Option<&NSColor>,
+  tier: Option<&TierInfo>,
+  detail: Option<&str>,
 ) -> Retained<NSView> {
   let container = NSView::init(mtm.alloc::<NSView>());
 
-  // Label: "5h Limit  8%".
-  let label_text = format!("{}  {}%", label, utilization as i64);
+  // Label: "5h Limit  8%", or "5h Limit  100% · over limit" in the given color.
+  let label_text = match over_limit_color {
+    Some(_) => format!("{}  {}% · over limit", label, utilization as i64),
+    None => format!("{}  {}%", label, utilization as i64),
+  };
   let label_field = NSTextField::labelWithString(&NSString::from_str(&label_text), mtm);
+  if let Some(color) = over_limit_color {
+    label_field.setTextColor(Some(color));
+  }
   label_field.noAutoresize();
   label_field.setEditable(false);
   label_field.setBezeled(false);
@@ -157,11 +227,29 @@ pub fn progress_row(
     activate(&[
       // Reset label: same row as label, right-aligned.
       &reset_field.topAnchor().constraintEqualToAnchor(&label_field.topAnchor()),
-      &reset_field.leadingAnchor().constraintEqualToAnchor(&label_field.leadingAnchor()),
-      &reset_field.trailingAnchor().constraintEqualToAnchor(&label_field.trailingAnchor()),
+      &reset_field.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
+      &reset_field
+        .trailingAnchor()
+        .constraintEqualToAnchor_constant(&container.trailingAnchor(), -H_PADDING),
     ]);
   }
 
+  // Tier badge right after the label, which then keeps its intrinsic width.
+  if let Some(tier) = tier {
+    let badge_view = tier_badge(mtm, tier);
+    container.addSubview(&badge_view);
+
+    activate(&[
+      &badge_view.leadingAnchor().constraintEqualToAnchor_constant(&label_field.trailingAnchor(), 8.0),
+      &badge_view.centerYAnchor().constraintEqualToAnchor(&label_field.centerYAnchor()),
+    ]);
+  }
+  else {
+    activate(&[&label_field
+      .trailingAnchor()
+      .constraintEqualToAnchor_constant(&container.trailingAnchor(), -H_PADDING)]);
+  }
+
   // Progress bar.
   let progress = NSProgressIndicator::init(mtm.alloc::<NSProgressIndicator>());
   progress.noAutoresize();
@@ -175,19 +263,91 @@ pub fn progress_row(
   activate(&[
     // Container width.
     &container.widthAnchor().constraintEqualToConstant(MENU_WIDTH),
-    // Label row: top, leading, trailing.
+    // Label row: top, leading.
     &label_field.topAnchor().constraintEqualToAnchor_constant(&container.topAnchor(), 6.0),
     &label_field.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
-    &label_field
-      .trailingAnchor()
-      .constraintEqualToAnchor_constant(&container.trailingAnchor(), -H_PADDING),
     // Progress bar: below label, pinned to sides.
     &progress.topAnchor().constraintEqualToAnchor_constant(&label_field.bottomAnchor(), 2.0),
     &progress.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
     &progress.trailingAnchor().constraintEqualToAnchor_constant(&container.trailingAnchor(), -H_PADDING),
     &progress.heightAnchor().constraintEqualToConstant(H_PADDING),
-    // Container bottom.
-    &container.bottomAnchor().constraintEqualToAnchor_constant(&progress.bottomAnchor(), 2.0),
+  ]);
+
+  // Detail line under the bar, if any.
+  let bottom_anchor = match detail {
+    Some(detail) => {
+      let detail_field = NSTextField::labelWithString(&NSString::from_str(detail), mtm);
+      detail_field.noAutoresize();
+      detail_field.setEditable(false);
+      detail_field.setBezeled(false);
+      detail_field.setDrawsBackground(false);
+      detail_field.setFont(Some(&NSFont::systemFontOfSize_weight(10.0, font_weight_light())));
+      detail_field.setTextColor(Some(&NSColor::secondaryLabelColor()));
+      container.addSubview(&detail_field);
+
+      activate(&[
+        &detail_field.topAnchor().constraintEqualToAnchor_constant(&progress.bottomAnchor(), 1.0),
+        &detail_field.leadingAnchor().constraintEqualToAnchor(&progress.leadingAnchor()),
+      ]);
+
+      detail_field.bottomAnchor()
+    }
+    None => progress.bottomAnchor(),
+  };
+
+  // Container bottom.
+  activate(&[&container.bottomAnchor().constraintEqualToAnchor_constant(&bottom_anchor, 2.0)]);
+
+  layout(&container);
+
+  return container;
+}
+
+/// Height of the usage history line under a bar.
+const SPARKLINE_HEIGHT: CGFloat = 16.0;
+
+/// Line of a window's utilization over time, drawn under its bar. Points are (position in the
+/// span 0–1, utilization 0–100), oldest first.
+pub fn sparkline_row(mtm: MainThreadMarker, points: &[(f64, f64)], color: &NSColor) -> Retained<NSView> {
+  let container = NSView::init(mtm.alloc::<NSView>());
+
+  let points = points.to_vec();
+  let color = color.retain();
+  let block = RcBlock::new(move |rect: NSRect| -> Bool {
+    // Inset by half the line width, so a flat line at 0% or 100% isn't clipped.
+    let path = NSBezierPath::bezierPath();
+    for (i, (x, utilization)) in points.iter().enumerate() {
+      let y = 1.0 + utilization.clamp(0.0, 100.0) / 100.0 * (rect.size.height - 2.0);
+      let point = CGPoint::new(x * rect.size.width, y);
+
+      if i == 0 {
+        path.moveToPoint(point);
+      }
+      else {
+        path.lineToPoint(point);
+      }
+    }
+
+    path.setLineWidth(1.5);
+    color.setStroke();
+    path.stroke();
+
+    return Bool::YES;
+  });
+
+  let width = MENU_WIDTH - 2.0 * H_PADDING;
+  let image = NSImage::imageWithSize_flipped_drawingHandler(NSSize::new(width, SPARKLINE_HEIGHT), false, &block);
+  let image_view = NSImageView::imageViewWithImage(&image, mtm);
+  image_view.noAutoresize();
+  container.addSubview(&image_view);
+
+  activate(&[
+    &container.widthAnchor().constraintEqualToConstant(MENU_WIDTH),
+    &image_view.topAnchor().constraintEqualToAnchor_constant(&container.topAnchor(), 1.0),
+    &image_view.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
+    &image_view.widthAnchor().constraintEqualToConstant(width),
+    &image_view.heightAnchor().constraintEqualToConstant(SPARKLINE_HEIGHT),
+    &container.bottomAnchor().constraintEqualToAnchor_constant(&image_view.bottomAnchor(), 4.0),
   ]);
 
   layout(&container);
@@ -197,6 +357,7 @@ pub fn progress_row(
 
 pub fn header_row(
   mtm: MainThreadMarker,
+  logo: &NSImage,
   title: &str,
   tier: &Option<&TierInfo>,
   version: Option<&str>,
@@ -214,9 +375,18 @@ pub fn header_row(
   field.setFont(Some(&font));
   container.addSubview(&field);
 
+  // Provider logo before the title.
+  let logo_view = NSImageView::imageViewWithImage(logo, mtm);
+  logo_view.noAutoresize();
+  container.addSubview(&logo_view);
+
   activate(&[
     &container.widthAnchor().constraintEqualToConstant(MENU_WIDTH),
-    &field.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
+    &logo_view.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
+    &logo_view.centerYAnchor().constraintEqualToAnchor(&field.centerYAnchor()),
+    &logo_view.widthAnchor().constraintEqualToConstant(HEADER_LOGO_SIZE),
+    &logo_view.heightAnchor().constraintEqualToConstant(HEADER_LOGO_SIZE),
+    &field.leadingAnchor().constraintEqualToAnchor_constant(&logo_view.trailingAnchor(), 6.0),
     &field.topAnchor().constraintEqualToAnchor_constant(&container.topAnchor(), 4.0),
     &container.bottomAnchor().constraintEqualToAnchor_constant(&field.bottomAnchor(), 2.0),
   ]);
@@ -243,49 +413,29 @@ pub fn header_row(
     ]);
   }
 
-  // Tier badge.
+  // Tier badge, next to the title and vertically centered.
   if let Some(tier) = tier {
-    let badge_font = NSFont::systemFontOfSize_weight(10.0, font_weight_medium());
-
-    let badge_view = NSView::init(mtm.alloc::<NSView>());
-    badge_view.noAutoresize();
-    badge_view.setWantsLayer(true);
-
+    let badge_view = tier_badge(mtm, tier);
     container.addSubview(&badge_view);
 
-    let badge_label = NSTextField::labelWithString(&NSString::from_str(&tier.name), mtm);
-    badge_label.noAutoresize();
-    badge_label.setEditable(false);
-    badge_label.setBezeled(false);
-    badge_label.setDrawsBackground(false);
-    badge_label.setFont(Some(&badge_font));
-    badge_label.setTextColor(Some(&NSColor::whiteColor()));
-    badge_label.setAlignment(objc2_app_kit::NSTextAlignment::Center);
-    badge_view.addSubview(&badge_label);
-
-    // Badge height is derived from the label's intrinsic height.
     activate(&[
-      // Badge view: next to title, vertically centered.
       &badge_view.leadingAnchor().constraintEqualToAnchor_constant(&field.trailingAnchor(), 8.0),
       &badge_view.centerYAnchor().constraintEqualToAnchor(&field.centerYAnchor()),
-      // Badge label fills badge view with padding; badge height wraps label.
-      &badge_label.topAnchor().constraintEqualToAnchor_constant(&badge_view.topAnchor(), 1.0),
-      &badge_label.bottomAnchor().constraintEqualToAnchor_constant(&badge_view.bottomAnchor(), -1.0),
-      &badge_label.leadingAnchor().constraintEqualToAnchor_constant(&badge_view.leadingAnchor(), 6.0),
-      &badge_label.trailingAnchor().constraintEqualToAnchor_constant(&badge_view.trailingAnchor(), -6.0),
     ]);
 
-    // Round corners based on resolved height.
-    badge_view.layoutSubtreeIfNeeded();
-
-    if let Some(layer) = badge_view.layer() {
-      let r = tier.color.r as f64 / 255.0;
-      let g = tier.color.g as f64 / 255.0;
-      let b = tier.color.b as f64 / 255.0;
-      let color = NSColor::colorWithSRGBRed_green_blue_alpha(r, g, b, 1.0);
-
-      layer.setBackgroundColor(Some(&color.CGColor()));
-      layer.setCornerRadius(badge_view.fittingSize().height / 2.0);
+    // Renewal date after the badge, small and dim.
+    if let Some(renews_at) = &tier.renews_at {
+      let text = format!("renews in {}", format_reset_time(renews_at));
+      let renewal_field = NSTextField::labelWithString(&NSString::from_str(&text), mtm);
+      renewal_field.noAutoresize();
+      renewal_field.setFont(Some(&NSFont::systemFontOfSize_weight(10.0, font_weight_light())));
+      renewal_field.setTextColor(Some(&NSColor::secondaryLabelColor()));
+      container.addSubview(&renewal_field);
+
+      activate(&[
+        &renewal_field.leadingAnchor().constraintEqualToAnchor_constant(&badge_view.trailingAnchor(), 6.0),
+        &renewal_field.centerYAnchor().constraintEqualToAnchor(&field.centerYAnchor()),
+      ]);
     }
   }
 
@@ -294,6 +444,49 @@ pub fn header_row(
   return container;
 }
 
+/// Rounded badge with the tier name on the tier color. The caller positions it.
+fn tier_badge(mtm: MainThreadMarker, tier: &TierInfo) -> Retained<NSView> {
+  let badge_font = NSFont::systemFontOfSize_weight(10.0, font_weight_medium());
+
+  let badge_view = NSView::init(mtm.alloc::<NSView>());
+  badge_view.noAutoresize();
+  badge_view.setWantsLayer(true);
+
+  let badge_label = NSTextField::labelWithString(&NSString::from_str(&tier.name), mtm);
+  badge_label.noAutoresize();
+  badge_label.setEditable(false);
+  badge_label.setBezeled(false);
+  badge_label.setDrawsBackground(false);
+  badge_label.setFont(Some(&badge_font));
+  badge_label.setTextColor(Some(&NSColor::whiteColor()));
+  badge_label.setAlignment(objc2_app_kit::NSTextAlignment::Center);
+  badge_view.addSubview(&badge_label);
+
+  // Badge height is derived from the label's intrinsic height.
+  activate(&[
+    // Badge label fills badge view with padding; badge height wraps label.
+    &badge_label.topAnchor().constraintEqualToAnchor_constant(&badge_view.topAnchor(), 1.0),
+    &badge_label.bottomAnchor().constraintEqualToAnchor_constant(&badge_view.bottomAnchor(), -1.0),
+    &badge_label.leadingAnchor().constraintEqualToAnchor_constant(&badge_view.leadingAnchor(), 6.0),
+    &badge_label.trailingAnchor().constraintEqualToAnchor_constant(&badge_view.trailingAnchor(), -6.0),
+  ]);
+
+  // Round corners based on resolved height.
+  badge_view.layoutSubtreeIfNeeded();
+
+  if let Some(layer) = badge_view.layer() {
+    let r = tier.color.r as f64 / 255.0;
+    let g = tier.color.g as f64 / 255.0;
+    let b = tier.color.b as f64 / 255.0;
+    let color = NSColor::colorWithSRGBRed_green_blue_alpha(r, g, b, 1.0);
+
+    layer.setBackgroundColor(Some(&color.CGColor()));
+    layer.setCornerRadius(badge_view.fittingSize().height / 2.0);
+  }
+
+  return badge_view;
+}
+
 pub fn peak_hours_row(mtm: MainThreadMarker, info: &PeakHoursInfo) -> Retained<NSView> {
   let container = NSView::init(mtm.alloc::<NSView>());
 
@@ -363,6 +556,113 @@ pub fn peak_hours_row(mtm: MainThreadMarker, info: &PeakHoursInfo) -> Retained<N
   return container;
 }
 
+/// Row with a colored status dot and a short message, for transient notices.
+pub fn notice_row(mtm: MainThreadMarker, text: &str, color: &NSColor) -> Retained<NSView> {
+  let container = NSView::init(mtm.alloc::<NSView>());
+
+  // Status dot.
+  const DOT_SIZE: CGFloat = 7.0;
+  let dot = NSView::init(mtm.alloc::<NSView>());
+  dot.noAutoresize();
+  dot.setWantsLayer(true);
+
+  if let Some(layer) = dot.layer() {
+    layer.setBackgroundColor(Some(&color.CGColor()));
+    layer.setCornerRadius(DOT_SIZE / 2.0);
+  }
+  container.addSubview(&dot);
+
+  let field = NSTextField::labelWithString(&NSString::from_str(text), mtm);
+  field.noAutoresize();
+  field.setEditable(false);
+  field.setBezeled(false);
+  field.setDrawsBackground(false);
+  field.setFont(Some(&NSFont::systemFontOfSize_weight(11.0, font_weight_medium())));
+  field.setTextColor(Some(color));
+  field.setLineBreakMode(objc2_app_kit::NSLineBreakMode::ByTruncatingMiddle);
+  container.addSubview(&field);
+
+  activate(&[
+    &container.widthAnchor().constraintEqualToConstant(MENU_WIDTH),
+    // Dot: leading, centered on the label.
+    &dot.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
+    &dot.centerYAnchor().constraintEqualToAnchor(&field.centerYAnchor()),
+    &dot.widthAnchor().constraintEqualToConstant(DOT_SIZE),
+    &dot.heightAnchor().constraintEqualToConstant(DOT_SIZE),
+    // Label: right after the dot, truncated at the trailing edge.
+    &field.leadingAnchor().constraintEqualToAnchor_constant(&dot.trailingAnchor(), 6.0),
+    &field
+      .trailingAnchor()
+      .constraintLessThanOrEqualToAnchor_constant(&container.trailingAnchor(), -H_PADDING),
+    &field.topAnchor().constraintEqualToAnchor_constant(&container.topAnchor(), 3.0),
+    &container.bottomAnchor().constraintEqualToAnchor_constant(&field.bottomAnchor(), 3.0),
+  ]);
+
+  layout(&container);
+
+  return container;
+}
+
+/// Notice row for a failed fetch, with the reason underneath in secondary text, wrapped to a few lines.
+pub fn error_row(mtm: MainThreadMarker, title: &str, reason: &str) -> Retained<NSView> {
+  let container = NSView::init(mtm.alloc::<NSView>());
+  let color = NSColor::systemOrangeColor();
+
+  // Status dot, as in the notice row.
+  const DOT_SIZE: CGFloat = 7.0;
+  let dot = NSView::init(mtm.alloc::<NSView>());
+  dot.noAutoresize();
+  dot.setWantsLayer(true);
+
+  if let Some(layer) = dot.layer() {
+    layer.setBackgroundColor(Some(&color.CGColor()));
+    layer.setCornerRadius(DOT_SIZE / 2.0);
+  }
+  container.addSubview(&dot);
+
+  let title_field = NSTextField::labelWithString(&NSString::from_str(title), mtm);
+  title_field.noAutoresize();
+  title_field.setFont(Some(&NSFont::systemFontOfSize_weight(11.0, font_weight_medium())));
+  title_field.setTextColor(Some(&color));
+  title_field.setLineBreakMode(objc2_app_kit::NSLineBreakMode::ByTruncatingMiddle);
+  container.addSubview(&title_field);
+
+  let reason_field = NSTextField::wrappingLabelWithString(&NSString::from_str(reason), mtm);
+  reason_field.noAutoresize();
+  reason_field.setSelectable(false);
+  reason_field.setFont(Some(&NSFont::systemFontOfSize(11.0)));
+  reason_field.setTextColor(Some(&NSColor::secondaryLabelColor()));
+  reason_field.setMaximumNumberOfLines(3);
+  reason_field.setPreferredMaxLayoutWidth(MENU_WIDTH - H_PADDING * 2.0 - DOT_SIZE - 6.0);
+  container.addSubview(&reason_field);
+
+  activate(&[
+    &container.widthAnchor().constraintEqualToConstant(MENU_WIDTH),
+    // Dot: leading, centered on the title.
+    &dot.leadingAnchor().constraintEqualToAnchor_constant(&container.leadingAnchor(), H_PADDING),
+    &dot.centerYAnchor().constraintEqualToAnchor(&title_field.centerYAnchor()),
+    &dot.widthAnchor().constraintEqualToConstant(DOT_SIZE),
+    &dot.heightAnchor().constraintEqualToConstant(DOT_SIZE),
+    // Title: right after the dot.
+    &title_field.leadingAnchor().constraintEqualToAnchor_constant(&dot.trailingAnchor(), 6.0),
+    &title_field
+      .trailingAnchor()
+      .constraintLessThanOrEqualToAnchor_constant(&container.trailingAnchor(), -H_PADDING),
+    &title_field.topAnchor().constraintEqualToAnchor_constant(&container.topAnchor(), 3.0),
+    // Reason: under the title, aligned with it.
+    &reason_field.leadingAnchor().constraintEqualToAnchor(&title_field.leadingAnchor()),
+    &reason_field
+      .trailingAnchor()
+      .constraintLessThanOrEqualToAnchor_constant(&container.trailingAnchor(), -H_PADDING),
+    &reason_field.topAnchor().constraintEqualToAnchor_constant(&title_field.bottomAnchor(), 2.0),
+    &container.bottomAnchor().constraintEqualToAnchor_constant(&reason_field.bottomAnchor(), 3.0),
+  ]);
+
+  layout(&container);
+
+  return container;
+}
+
 pub fn label_row(mtm: MainThreadMarker, text: &str, bold: bool) -> Retained<NSView> {
   let container = NSView::init(mtm.alloc::<NSView>());
 
@@ -391,7 +691,7 @@ pub fn label_row(mtm: MainThreadMarker, text: &str, bold: bool) -> Retained<NSVi
   return container;
 }
 
-pub fn key_value_row(mtm: MainThreadMarker, key: &str, value: &str) -> Retained<NSView> {
+pub fn key_value_row(mtm: MainThreadMarker, key: &str, value: &str, value_color: Option<&NSColor>) -> Retained<NSView> {
   let container = NSView::init(mtm.alloc::<NSView>());
 
   let key_field = NSTextField::labelWithString(&NSString::from_str(key), mtm);
@@ -411,7 +711,8 @@ pub fn key_value_row(mtm: MainThreadMarker, key: &str, value: &str) -> Retained<
   value_field.setDrawsBackground(false);
   value_field.setFont(Some(&font));
   value_field.setAlignment(objc2_app_kit::NSTextAlignment::Right);
-  value_field.setTextColor(Some(&NSColor::secondaryLabelColor()));
+  let default_color = NSColor::secondaryLabelColor();
+  value_field.setTextColor(Some(value_color.unwrap_or(&default_color)));
   container.addSubview(&value_field);
 
   activate(&[
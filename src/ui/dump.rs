@@ -0,0 +1,71 @@
+use color_eyre::eyre::Result;
+use objc2_app_kit::{NSControlStateValueOn, NSMenu, NSProgressIndicator, NSTextField, NSView};
+
+use crate::{bench, delegate::AppDelegate};
+
+/// Builds the menu from the same made-up usage as `--bench-render` and prints it as a text tree,
+/// so it can be diffed against a saved copy after changing the layout.
+pub fn print(delegate: &AppDelegate) -> Result<()> {
+  delegate.load_fetch(bench::sample_fetch(60.0));
+  print!("{}", menu_tree(&delegate.build_menu()));
+
+  return Ok(());
+}
+
+/// Describes the menu one item per line, in order: the title of plain items, or the texts of a
+/// custom row's labels and bars, with submenus indented below their item.
+pub fn menu_tree(menu: &NSMenu) -> String {
+  let mut out = String::new();
+  write_menu(menu, 0, &mut out);
+
+  return out;
+}
+
+fn write_menu(menu: &NSMenu, depth: usize, out: &mut String) {
+  let indent = "  ".repeat(depth);
+
+  for item in menu.itemArray().iter() {
+    if item.isSeparatorItem() {
+      out.push_str(&format!("{indent}---\n"));
+      continue;
+    }
+
+    let text = match item.view() {
+      Some(view) => {
+        let mut parts = Vec::new();
+        collect_view_text(&view, &mut parts);
+        parts.join(" | ")
+      }
+      None => item.title().to_string(),
+    };
+
+    let check = if item.state() == NSControlStateValueOn { "✓ " } else { "" };
+    let key = item.keyEquivalent().to_string();
+    let key = if key.is_empty() { String::new() } else { format!(" [{key}]") };
+    let alternate = if item.isAlternate() { " (alternate)" } else { "" };
+    let disabled = if item.isEnabled() { "" } else { " (disabled)" };
+
+    out.push_str(&format!("{indent}{check}{text}{key}{alternate}{disabled}\n"));
+
+    if let Some(submenu) = item.submenu() {
+      write_menu(&submenu, depth + 1, out);
+    }
+  }
+}
+
+/// Collects label texts and progress bar values of a custom row, depth first in subview order.
+fn collect_view_text(view: &NSView, parts: &mut Vec<String>) {
+  if let Some(field) = view.downcast_ref::<NSTextField>() {
+    let text = field.stringValue().to_string();
+    if !text.is_empty() {
+      parts.push(text);
+    }
+  }
+  else if let Some(progress) = view.downcast_ref::<NSProgressIndicator>() {
+    parts.push(format!("[{:.0}%]", progress.doubleValue()));
+  }
+
+  for subview in view.subviews().iter() {
+    collect_view_text(&subview, parts);
+  }
+}
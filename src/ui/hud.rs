@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use dispatch2::{DispatchQueue, DispatchTime, MainThreadBound};
+use objc2::{MainThreadMarker, rc::Retained};
+use objc2_app_kit::{
+  NSColor, NSFont, NSScreen, NSStatusWindowLevel, NSTextAlignment, NSTextField, NSVisualEffectBlendingMode,
+  NSVisualEffectMaterial, NSVisualEffectState, NSVisualEffectView, NSWindow, NSWindowStyleMask,
+};
+use objc2_core_foundation::CGPoint;
+use objc2_foundation::{NSRect, NSSize, NSString};
+
+use super::components::font_weight_semibold;
+
+/// How long the HUD stays on screen.
+const HUD_DURATION: Duration = Duration::from_millis(1200);
+
+/// Shows a short message in a translucent panel near the bottom of the main screen, like the
+/// system volume HUD, and hides it after a moment. Returns the window so the caller can hide it
+/// early when showing another one.
+pub fn show(mtm: MainThreadMarker, text: &str) -> Retained<NSWindow> {
+  let label = NSTextField::labelWithString(&NSString::from_str(text), mtm);
+  label.setFont(Some(&NSFont::systemFontOfSize_weight(15.0, font_weight_semibold())));
+  label.setAlignment(NSTextAlignment::Center);
+  label.sizeToFit();
+
+  let label_size = label.frame().size;
+  let size = NSSize::new(label_size.width + 40.0, label_size.height + 24.0);
+  label.setFrameOrigin(CGPoint::new(20.0, 12.0));
+
+  // Centered horizontally, in the lower third of the screen.
+  let origin = match NSScreen::mainScreen(mtm) {
+    Some(screen) => {
+      let frame = screen.visibleFrame();
+      CGPoint::new(frame.origin.x + (frame.size.width - size.width) / 2.0, frame.origin.y + frame.size.height / 6.0)
+    }
+    None => CGPoint::new(0.0, 0.0),
+  };
+
+  let window = unsafe {
+    NSWindow::initWithContentRect_styleMask_backing_defer(
+      mtm.alloc::<NSWindow>(),
+      NSRect::new(origin, size),
+      NSWindowStyleMask::Borderless,
+      objc2_app_kit::NSBackingStoreType(2), // NSBackingStoreBuffered
+      false,
+    )
+  };
+
+  window.setOpaque(false);
+  window.setBackgroundColor(Some(&NSColor::clearColor()));
+  window.setHasShadow(true);
+  window.setIgnoresMouseEvents(true);
+  window.setLevel(NSStatusWindowLevel);
+  unsafe { window.setReleasedWhenClosed(false) };
+
+  let effect = NSVisualEffectView::initWithFrame(mtm.alloc::<NSVisualEffectView>(), NSRect::new(CGPoint::ZERO, size));
+  effect.setMaterial(NSVisualEffectMaterial::HUDWindow);
+  effect.setBlendingMode(NSVisualEffectBlendingMode::BehindWindow);
+  effect.setState(NSVisualEffectState::Active);
+  effect.setWantsLayer(true);
+  if let Some(layer) = effect.layer() {
+    layer.setCornerRadius(12.0);
+    layer.setMasksToBounds(true);
+  }
+
+  effect.addSubview(&label);
+  window.setContentView(Some(&effect));
+  window.orderFrontRegardless();
+
+  let hide = MainThreadBound::new(window.clone(), mtm);
+  let when = DispatchTime::try_from(HUD_DURATION).unwrap_or(DispatchTime::NOW);
+  let _ = DispatchQueue::main().after(when, move || {
+    let mtm = MainThreadMarker::new().expect("Must be on main thread");
+    hide.get(mtm).orderOut(None);
+  });
+
+  return window;
+}
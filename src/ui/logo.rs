@@ -0,0 +1,41 @@
+use std::ffi::c_void;
+
+use objc2::{AnyThread as _, rc::Retained};
+use objc2_app_kit::NSImage;
+use objc2_foundation::NSData;
+
+use crate::providers::DataProvider;
+
+/// Logos shipped with the app, by the name used in the `icons` option.
+const BUNDLED_ICONS: &[(&str, &[u8])] = &[
+  ("claude", include_bytes!("../../resources/claude.svg")),
+  ("codex", include_bytes!("../../resources/codex.svg")),
+  ("copilot", include_bytes!("../../resources/copilot.svg")),
+];
+
+/// Logo of the provider for the tray and menu header: the configured icon if set and loadable,
+/// otherwise the provider's own. `icon` is a bundled icon name or a path to an SVG file.
+pub fn provider_logo(provider: &dyn DataProvider, icon: Option<&str>) -> Retained<NSImage> {
+  if let Some(icon) = icon {
+    match load_icon(icon) {
+      Some(image) => return image,
+      None => log::warn!("Failed to load icon {icon:?}, using the provider's"),
+    }
+  }
+
+  return image_from_svg(provider.tray_icon_svg()).expect("failed to load provider logo");
+}
+
+fn load_icon(icon: &str) -> Option<Retained<NSImage>> {
+  if let Some((_, svg)) = BUNDLED_ICONS.iter().find(|(name, _)| *name == icon) {
+    return image_from_svg(svg);
+  }
+
+  let svg = fs_err::read(icon).inspect_err(|e| log::warn!("Failed to read icon: {e}")).ok()?;
+  return image_from_svg(&svg);
+}
+
+fn image_from_svg(svg: &[u8]) -> Option<Retained<NSImage>> {
+  let data = unsafe { NSData::dataWithBytes_length(svg.as_ptr() as *const c_void, svg.len()) };
+  return NSImage::initWithData(NSImage::alloc(), &data);
+}
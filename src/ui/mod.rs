@@ -1,3 +1,9 @@
 pub mod about;
 pub mod components;
+pub mod dump;
+pub mod hud;
+pub mod logo;
+pub mod palette;
+pub mod snapshot;
+pub mod token_dialog;
 pub mod views;
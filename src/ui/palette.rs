@@ -0,0 +1,103 @@
+use std::ptr::NonNull;
+
+use block2::RcBlock;
+use objc2::rc::Retained;
+use objc2_app_kit::{NSAppearance, NSAppearanceNameAqua, NSAppearanceNameDarkAqua, NSColor, NSWorkspace};
+use objc2_foundation::NSArray;
+
+use crate::config::ColorScale;
+
+// Catalog colors throughout, so macOS vibrancy compositing properly dims them on inactive displays. The opt-in
+// contrast tones trade that for readability on wallpapers that wash the catalog colors out.
+impl ColorScale {
+  /// Returns the color for a utilization level (0–1). Over the limit (above 1) gets a color of its own.
+  pub fn utilization_color(self, pct: f64) -> Retained<NSColor> {
+    return match self {
+      ColorScale::Default => {
+        match pct {
+          p if p < 0.5 => NSColor::controlTextColor(),
+          p if p < 0.75 => NSColor::yellowColor(),
+          p if p < 0.90 => NSColor::orangeColor(),
+          p if p <= 1.0 => NSColor::redColor(),
+          _ => NSColor::systemPinkColor(),
+        }
+      }
+      ColorScale::BluePurple => {
+        match pct {
+          p if p < 0.5 => NSColor::controlTextColor(),
+          p if p < 0.75 => NSColor::systemCyanColor(),
+          p if p < 0.90 => NSColor::systemBlueColor(),
+          p if p <= 1.0 => NSColor::systemPurpleColor(),
+          _ => NSColor::systemPinkColor(),
+        }
+      }
+    };
+  }
+
+  /// Like `utilization_color`, but with tones that keep their contrast against the menu bar when a
+  /// bright or dark wallpaper shows through it. The color is resolved against the menu bar appearance
+  /// each time the tray is drawn. With reduced transparency the menu bar is opaque, and the catalog
+  /// colors already read well on it.
+  pub fn contrast_utilization_color(self, pct: f64) -> Retained<NSColor> {
+    let level = match pct {
+      p if p < 0.5 => return NSColor::controlTextColor(),
+      p if p < 0.75 => 0,
+      p if p < 0.90 => 1,
+      p if p <= 1.0 => 2,
+      _ => 3,
+    };
+
+    if NSWorkspace::sharedWorkspace().accessibilityDisplayShouldReduceTransparency() {
+      return self.utilization_color(pct);
+    }
+
+    let provider = RcBlock::new(move |appearance: NonNull<NSAppearance>| -> NonNull<NSColor> {
+      let (r, g, b) = self.contrast_tone(level, is_dark(unsafe { appearance.as_ref() }));
+      let color = NSColor::colorWithSRGBRed_green_blue_alpha(r, g, b, 1.0);
+      return NonNull::new(Retained::autorelease_ptr(color)).expect("color is not null");
+    });
+
+    return unsafe { NSColor::colorWithName_dynamicProvider(None, &provider) };
+  }
+
+  /// sRGB tone for a highlighted utilization level (0 = lowest), darker on a light menu bar and
+  /// brighter on a dark one.
+  fn contrast_tone(self, level: usize, dark: bool) -> (f64, f64, f64) {
+    // Over the limit is pink on both scales.
+    if level >= 3 {
+      return if dark { (1.0, 0.45, 0.70) } else { (0.78, 0.05, 0.42) };
+    }
+
+    let tones = match (self, dark) {
+      (ColorScale::Default, false) => [(0.62, 0.47, 0.0), (0.80, 0.36, 0.0), (0.78, 0.08, 0.08)],
+      (ColorScale::Default, true) => [(1.0, 0.90, 0.30), (1.0, 0.66, 0.26), (1.0, 0.42, 0.40)],
+      (ColorScale::BluePurple, false) => [(0.0, 0.47, 0.60), (0.05, 0.33, 0.82), (0.52, 0.18, 0.72)],
+      (ColorScale::BluePurple, true) => [(0.45, 0.88, 1.0), (0.48, 0.66, 1.0), (0.82, 0.56, 1.0)],
+    };
+
+    return tones[level];
+  }
+
+  /// Color of the pacing warning indicators.
+  pub fn warning_color(self) -> Retained<NSColor> {
+    return match self {
+      ColorScale::Default => NSColor::systemYellowColor(),
+      ColorScale::BluePurple => NSColor::systemCyanColor(),
+    };
+  }
+
+  /// Color of values that went over a goal or budget.
+  pub fn over_color(self) -> Retained<NSColor> {
+    return match self {
+      ColorScale::Default => NSColor::systemOrangeColor(),
+      ColorScale::BluePurple => NSColor::systemPurpleColor(),
+    };
+  }
+}
+
+fn is_dark(appearance: &NSAppearance) -> bool {
+  let (aqua, dark_aqua) = unsafe { (NSAppearanceNameAqua, NSAppearanceNameDarkAqua) };
+  let best = appearance.bestMatchFromAppearancesWithNames(&NSArray::from_slice(&[aqua, dark_aqua]));
+
+  return best.is_some_and(|name| &*name == dark_aqua);
+}
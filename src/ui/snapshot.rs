@@ -0,0 +1,50 @@
+use block2::RcBlock;
+use objc2::{rc::Retained, runtime::Bool};
+use objc2_app_kit::{
+  NSBitmapImageFileType, NSBitmapImageRep, NSColor, NSCompositingOperation, NSImage, NSMenu, NSRectFillUsingOperation,
+};
+use objc2_core_foundation::CGPoint;
+use objc2_foundation::{NSData, NSDictionary, NSRect, NSSize};
+
+/// Renders the menu's custom rows (header, usage bars, extra usage) stacked top to bottom on the
+/// window background, and encodes the result as PNG. Plain menu items are skipped.
+pub fn render_menu_png(menu: &NSMenu) -> Option<Retained<NSData>> {
+  let rows = menu
+    .itemArray()
+    .iter()
+    .filter_map(|item| item.view())
+    .filter_map(|view| {
+      let bounds = view.bounds();
+      let rep = view.bitmapImageRepForCachingDisplayInRect(bounds)?;
+      view.cacheDisplayInRect_toBitmapImageRep(bounds, &rep);
+
+      return Some((rep, bounds.size));
+    })
+    .collect::<Vec<_>>();
+
+  if rows.is_empty() {
+    return None;
+  }
+
+  let width = rows.iter().map(|(_, size)| size.width).fold(0.0, f64::max);
+  let height = rows.iter().map(|(_, size)| size.height).sum::<f64>();
+
+  let block = RcBlock::new(move |rect: NSRect| -> Bool {
+    NSColor::windowBackgroundColor().setFill();
+    NSRectFillUsingOperation(rect, NSCompositingOperation::Copy);
+
+    // Not flipped, so rows are placed from the top down.
+    let mut y = height;
+    for (rep, size) in &rows {
+      y -= size.height;
+      rep.drawInRect(NSRect::new(CGPoint::new(0.0, y), *size));
+    }
+
+    return Bool::YES;
+  });
+
+  let image = NSImage::imageWithSize_flipped_drawingHandler(NSSize::new(width, height), false, &block);
+  let bitmap = NSBitmapImageRep::imageRepWithData(&image.TIFFRepresentation()?)?;
+
+  return unsafe { bitmap.representationUsingType_properties(NSBitmapImageFileType::PNG, &NSDictionary::new()) };
+}
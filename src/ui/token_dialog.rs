@@ -0,0 +1,41 @@
+use objc2::{MainThreadMarker, rc::Retained};
+use objc2_app_kit::{NSAlert, NSAlertFirstButtonReturn, NSApplication, NSSecureTextField};
+use objc2_core_foundation::CGPoint;
+use objc2_foundation::{NSRect, NSSize, NSString};
+
+/// Asks for a Claude Code OAuth token in a modal alert with a secure text field. `error` is shown
+/// when asking again after a rejected token. Returns `None` if cancelled or left empty.
+pub fn prompt_token(mtm: MainThreadMarker, error: Option<&str>) -> Option<String> {
+  let alert = NSAlert::new(mtm);
+  alert.setMessageText(&NSString::from_str("Enter Claude Code Token"));
+
+  let info = error.unwrap_or(
+    "No Claude Code credentials were found in the keychain. Paste an OAuth token (sk-ant-oat…), \
+     it will be checked and saved to the keychain.",
+  );
+  alert.setInformativeText(&NSString::from_str(info));
+  alert.addButtonWithTitle(&NSString::from_str("Save"));
+  alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+
+  let field = NSSecureTextField::initWithFrame(
+    mtm.alloc::<NSSecureTextField>(),
+    NSRect::new(CGPoint::new(0.0, 0.0), NSSize::new(300.0, 24.0)),
+  );
+  field.setPlaceholderString(Some(&NSString::from_str("sk-ant-oat…")));
+  alert.setAccessoryView(Some(&field));
+  alert.window().setInitialFirstResponder(Some(&field));
+
+  #[allow(deprecated)]
+  NSApplication::sharedApplication(mtm).activateIgnoringOtherApps(true);
+
+  if alert.runModal() != NSAlertFirstButtonReturn {
+    return None;
+  }
+
+  let token = field.stringValue().to_string().trim().to_string();
+  if token.is_empty() {
+    return None;
+  }
+
+  return Some(token);
+}
@@ -1,55 +1,197 @@
+use jiff::{SignedDuration, Timestamp, Zoned};
 use objc2::{DefinedClass, MainThreadMarker, rc::Retained, sel};
-use objc2_app_kit::{NSControlStateValueOff, NSControlStateValueOn, NSMenu, NSMenuItem};
-use objc2_foundation::NSString;
+use objc2_app_kit::{
+  NSColor, NSControlStateValueOff, NSControlStateValueOn, NSEventModifierFlags, NSImage, NSMenu, NSMenuItem,
+};
+use objc2_foundation::{NSCopying as _, NSString};
 use strum::IntoEnumIterator as _;
-use tap::Tap as _;
 
 use crate::{
+  app_state::{SecondaryFetch, ViewState},
+  config::{ColorScale, Config, DisplayMode},
   delegate::AppDelegate,
-  providers::{ApiUsage, ProviderKind, TierInfo, UsageData},
+  exchange_rate::{ExchangeRate, format_usd},
+  headroom::EffectiveHeadroom,
+  history,
+  providers::{ApiUsage, ProviderError, ProviderKind, TierInfo, UsageData, UsageWindow, claude_code},
+  spend_forecast::SpendForecast,
+  team_sync::TeamMember,
   ui::components,
   updater::UpdateState,
+  utils::{sandbox, time},
+  weekly_goal::GoalProgress,
 };
 
-pub fn loading_menu(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenu> {
-  return NSMenu::new(mtm).tap(|menu| {
-    let loading_item = NSMenuItem::new(mtm);
-    loading_item.setTitle(&NSString::from_str("Loading..."));
-    loading_item.setEnabled(false);
-    menu.addItem(&loading_item);
-
-    let current_provider = app.ivars().provider().kind();
-
-    menu.addItem(&NSMenuItem::separatorItem(mtm));
-    menu.addItem(&refresh_item(mtm, app));
-    menu.addItem(&provider_item(mtm, app, current_provider));
-    menu.addItem(&update_item(mtm, app, &UpdateState::Unchecked));
-    menu.addItem(&about_item(mtm, app));
-    menu.addItem(&NSMenuItem::separatorItem(mtm));
-    menu.addItem(&open_config_item(mtm, app));
-    menu.addItem(&open_logs_item(mtm, app));
-    menu.addItem(&quit_item(mtm, app));
+/// Fills the menu for the states without data (loading, failed, no provider), keeping the usual
+/// actions so the app stays usable.
+pub fn populate_status_menu(menu: &NSMenu, mtm: MainThreadMarker, app: &AppDelegate, state: &ViewState) {
+  menu.removeAllItems();
+
+  let configured_provider = app.ivars().config().provider;
+  if let ViewState::Error { failed_at, error } = state {
+    let title = format!("Couldn't fetch usage at {}", time::format_past_time(failed_at));
+    add_error_rows(menu, mtm, app, &title, error);
+  }
+  else {
+    let (text, color) = match state {
+      ViewState::Loading => ("Loading…".to_string(), NSColor::secondaryLabelColor()),
+      ViewState::NoProvider if configured_provider == ProviderKind::ClaudeCode => {
+        ("Waiting for Claude Code sign-in".to_string(), NSColor::secondaryLabelColor())
+      }
+      ViewState::NoProvider => {
+        let text = format!("{configured_provider} couldn't start, check the config and logs");
+        (text, NSColor::systemOrangeColor())
+      }
+      ViewState::Data { .. } | ViewState::Stale { .. } | ViewState::Error { .. } => {
+        ("No data".to_string(), NSColor::secondaryLabelColor())
+      }
+    };
+    add_notice_row(menu, mtm, &text, &color);
+  }
+  add_polling_notices(menu, mtm, app);
+
+  let current_provider = app.ivars().provider().kind();
+
+  // Claude Code couldn't start, most likely without credentials: offer to enter a token.
+  if current_provider == ProviderKind::Unknown && configured_provider == ProviderKind::ClaudeCode {
+    menu.addItem(&enter_token_item(mtm, app));
+  }
+
+  menu.addItem(&NSMenuItem::separatorItem(mtm));
+  menu.addItem(&refresh_item(mtm, app));
+  menu.addItem(&display_mode_item(mtm, app));
+  menu.addItem(&interval_item(mtm, app));
+  menu.addItem(&provider_item(mtm, app, current_provider));
+  if let Some(item) = authentication_item(mtm, app) {
+    menu.addItem(&item);
+  }
+  if !sandbox::is_sandboxed() {
+    menu.addItem(&update_item(mtm, app, &app.ivars().update_state()));
+  }
+  menu.addItem(&about_item(mtm, app));
+  menu.addItem(&NSMenuItem::separatorItem(mtm));
+  menu.addItem(&open_config_item(mtm, app));
+  menu.addItem(&open_logs_item(mtm, app));
+  menu.addItem(&token_diagnostics_item(mtm, app));
+  menu.addItem(&quit_item(mtm, app));
+}
+
+/// Copies a freshly built menu into the open one item by item, so it updates without jumping or
+/// closing. Returns `false`, leaving `menu` untouched, if the two differ in structure (item count,
+/// separators, actions or which items have custom views), in which case the caller rebuilds it
+/// once it closes.
+pub fn patch_open_menu(menu: &NSMenu, fresh: &NSMenu) -> bool {
+  let (items, fresh_items) = (menu.itemArray(), fresh.itemArray());
+  if items.len() != fresh_items.len() {
+    return false;
+  }
+
+  let same_structure = items.iter().zip(fresh_items.iter()).all(|(item, fresh)| {
+    return { true }
+      && item.isSeparatorItem() == fresh.isSeparatorItem()
+      && item.action() == fresh.action()
+      && item.view().is_some() == fresh.view().is_some()
+      && item.submenu().is_some() == fresh.submenu().is_some();
   });
+  if !same_structure {
+    return false;
+  }
+
+  for (item, fresh) in items.iter().zip(fresh_items.iter()) {
+    if item.isSeparatorItem() {
+      continue;
+    }
+
+    // Views and submenus can only belong to one item, so they're detached from the fresh one first.
+    if let Some(view) = fresh.view() {
+      fresh.setView(None);
+      item.setView(Some(&view));
+    }
+    if let Some(submenu) = fresh.submenu() {
+      fresh.setSubmenu(None);
+      item.setSubmenu(Some(&submenu));
+    }
+
+    item.setTitle(&fresh.title());
+    item.setState(fresh.state());
+    item.setEnabled(fresh.isEnabled());
+    item.setTag(fresh.tag());
+  }
+
+  return true;
+}
+
+/// Notices about why periodic fetches aren't happening.
+fn add_polling_notices(menu: &NSMenu, mtm: MainThreadMarker, app: &AppDelegate) {
+  if app.ivars().is_paused() {
+    add_notice_row(menu, mtm, "Paused: run `liment ctl resume` to continue", &NSColor::secondaryLabelColor());
+  }
+
+  if let Some(wait) = app.ivars().throttled_for() {
+    let text = format!("Throttled: next fetch in {}m", wait.as_secs().div_ceil(60));
+    add_notice_row(menu, mtm, &text, &NSColor::systemOrangeColor());
+  }
 }
 
 pub fn populate_menu(
   menu: &NSMenu,
   mtm: MainThreadMarker,
   app: &AppDelegate,
+  logo: &NSImage,
   data: &UsageData,
   profile: Option<&TierInfo>,
+  exchange_rate: Option<&ExchangeRate>,
+  team: Option<&[TeamMember]>,
+  secondary: Option<&SecondaryFetch>,
 ) {
   menu.removeAllItems();
 
+  // A single window is shown as one compact row, with the tier badge next to its label instead
+  // of in a separate header.
+  let compact = data.windows.len() == 1;
+
   // Header with tier badge.
   let config = app.ivars().config();
-  let version = if config.show_version { Some(concat!("v", env!("CARGO_PKG_VERSION"))) } else { None };
-  let header_item = NSMenuItem::new(mtm);
-  let header_view = components::header_row(mtm, "Usage", &profile, version);
-  header_item.setView(Some(&header_view));
-  menu.addItem(&header_item);
+  if !compact {
+    let version = if config.show_version { Some(concat!("v", env!("CARGO_PKG_VERSION"))) } else { None };
+    // Tinted like the text when the tray logo is monochrome.
+    let logo = logo.copy();
+    logo.setTemplate(config.monochrome_icon);
 
+    let header_item = NSMenuItem::new(mtm);
+    let header_view = components::header_row(mtm, &logo, "Usage", &profile, version);
+    header_item.setView(Some(&header_view));
+    menu.addItem(&header_item);
+  }
+
+  if let Some(account) = app.ivars().state().changed_account() {
+    let text = format!("Account changed: now monitoring {}", account.name);
+    add_notice_row(menu, mtm, &text, &NSColor::systemOrangeColor());
+  }
+
+  for reset in app.ivars().state().recent_resets() {
+    let text = format!("{} reset at {}", reset.title, time::format_past_time(&reset.reset_at));
+    add_notice_row(menu, mtm, &text, &NSColor::secondaryLabelColor());
+  }
+
+  let kind = app.ivars().provider().kind();
+  if let ViewState::Stale { failed_at, error, .. } = app.ivars().state().view_state(kind) {
+    let title = format!("Couldn't fetch usage at {}, showing earlier data", time::format_past_time(&failed_at));
+    add_error_rows(menu, mtm, app, &title, error);
+  }
+
+  add_polling_notices(menu, mtm, app);
+
+  let mut group = None;
   for window in &data.windows {
+    // Sub-header whenever a new group starts.
+    if let Some(title) = window.group.as_deref()
+      && group != Some(title)
+    {
+      group = Some(title);
+      add_group_header(menu, mtm, title);
+    }
+
     menu.addItem(&components::bucket_row(mtm, &components::BucketRowParams {
       label: &window.title,
       utilization: window.utilization,
@@ -59,7 +201,70 @@ pub fn populate_menu(
       show_pacing_warning: config.show_pacing_warning,
       reset_time_format: config.reset_time_format,
       display_mode: config.display_mode,
+      color_scale: config.color_scale,
+      tier: profile.filter(|_| compact),
+      amount: window.amount.as_ref(),
     }));
+
+    if config.experimental.sparklines {
+      add_sparkline_row(menu, mtm, app, window, &config);
+    }
+  }
+
+  if config.show_effective_headroom
+    && let Some(effective) = EffectiveHeadroom::compute(data)
+  {
+    let value = format!("{:.0}% ({})", effective.headroom, effective.window);
+    let color = config.color_scale.utilization_color(1.0 - effective.headroom / 100.0);
+    add_kv_row(menu, mtm, "Effective Headroom", &value, Some(&color));
+  }
+
+  if config.show_trend {
+    let state = app.ivars().state();
+    let trend = state.trend();
+    if !trend.is_empty() {
+      let value = trend
+        .iter()
+        .map(|(label, utilization)| format!("{label} {utilization:.0}%"))
+        .collect::<Vec<_>>()
+        .join(" · ");
+      add_kv_row(menu, mtm, "An Hour Ago", &value, None);
+    }
+  }
+
+  if let Some(progress) = config.weekly_goal.and_then(|goal| GoalProgress::compute(goal, data)) {
+    let value = format!("{:.0}% of {:.0}%", progress.utilization, progress.goal);
+    let color = if progress.is_over_pace() { Some(config.color_scale.over_color()) } else { None };
+    add_kv_row(menu, mtm, "Weekly Goal", &value, color.as_deref());
+  }
+
+  if let Some(first_day) = config.budget_week_start {
+    let state = app.ivars().state();
+    let week = state.budget_week();
+    let key = format!("Since {first_day}");
+    let (value, color) = match config.weekly_goal {
+      Some(goal) => {
+        let over = week.used() > goal * week.elapsed();
+        (format!("{:.0}% of {:.0}%", week.used(), goal), over.then(|| config.color_scale.over_color()))
+      }
+      None => (format!("{:.0}%", week.used()), None),
+    };
+    add_kv_row(menu, mtm, &key, &value, color.as_deref());
+  }
+
+  if let Some(forecast) = app.ivars().state().session_forecast() {
+    let key = format!("Session at {}", time::format_until_time(&forecast.at));
+    let value = format!("{:.0}% projected", forecast.utilization);
+    let color = if forecast.has_headroom() { None } else { Some(config.color_scale.over_color()) };
+    add_kv_row(menu, mtm, &key, &value, color.as_deref());
+  }
+
+  if config.plan_hints {
+    let has_plans = app.ivars().provider().plans_url().is_some();
+    for (window, count) in app.ivars().state().limit_history().frequent() {
+      let text = format!("Hit {window} limit {count}× this week");
+      menu.addItem(&plan_hint_item(mtm, app, &text, has_plans));
+    }
   }
 
   // Peak hours indicator (under all usages, above the separator).
@@ -72,7 +277,25 @@ pub fn populate_menu(
 
   // API / extra usage.
   if let Some(api_usage) = &data.api_usage {
-    extra_usage_section(menu, mtm, api_usage);
+    let forecast = app.ivars().state().spend_forecast().forecast();
+    extra_usage_section(
+      menu,
+      mtm,
+      api_usage,
+      forecast.as_ref(),
+      config.extra_usage_budget,
+      exchange_rate,
+      config.color_scale,
+    );
+  }
+
+  // Provider-specific actions, at the bottom of its section.
+  for (i, action) in app.ivars().provider().actions().iter().enumerate() {
+    menu.addItem(&provider_action_item(mtm, app, i, &action.label));
+  }
+
+  if let Some(secondary) = secondary {
+    add_secondary_section(menu, mtm, secondary, &config);
   }
 
   // Separator + actions + utilities.
@@ -80,15 +303,85 @@ pub fn populate_menu(
   let current_provider = app.ivars().provider().kind();
   menu.addItem(&NSMenuItem::separatorItem(mtm));
   menu.addItem(&refresh_item(mtm, app));
+  menu.addItem(&display_mode_item(mtm, app));
+  menu.addItem(&interval_item(mtm, app));
   menu.addItem(&provider_item(mtm, app, current_provider));
-  menu.addItem(&update_item(mtm, app, &update_state));
+  menu.addItem(&provider_status_item(mtm, app, current_provider, data));
+  if let Some(item) = authentication_item(mtm, app) {
+    menu.addItem(&item);
+  }
+  menu.addItem(&upcoming_resets_item(mtm, data));
+  menu.addItem(&plan_session_item(mtm, app));
+  if let Some(team) = team {
+    menu.addItem(&team_item(mtm, team));
+  }
+  menu.addItem(&save_snapshot_item(mtm, app));
+  menu.addItem(&test_notification_item(mtm, app));
+  if !sandbox::is_sandboxed() {
+    menu.addItem(&update_item(mtm, app, &update_state));
+  }
   menu.addItem(&about_item(mtm, app));
   menu.addItem(&NSMenuItem::separatorItem(mtm));
   menu.addItem(&open_config_item(mtm, app));
   menu.addItem(&open_logs_item(mtm, app));
+  menu.addItem(&token_diagnostics_item(mtm, app));
   menu.addItem(&quit_item(mtm, app));
 }
 
+/// Builds the tray button tooltip: provider and account on the first line, then one line per window.
+pub fn tray_tooltip(provider: ProviderKind, state: &ViewState, display_mode: DisplayMode) -> String {
+  let (profile, data, failure) = match state {
+    ViewState::Data { fetch, data } => (fetch.profile.as_ref(), Some(*data), None),
+    ViewState::Stale { fetch, data, failed_at, error } => {
+      (fetch.profile.as_ref(), Some(*data), Some((failed_at, error)))
+    }
+    _ => (None, None, None),
+  };
+
+  let mut header = provider.to_string();
+  if let Some(profile) = profile {
+    header.push_str(&format!(" · {}", profile.name));
+
+    if let Some(account) = &profile.account {
+      header.push_str(&format!(" · {}", account.name));
+    }
+  }
+
+  let Some(data) = data
+  else {
+    let status = match state {
+      ViewState::Loading => "Loading…".to_string(),
+      ViewState::NoProvider => "Not set up".to_string(),
+      ViewState::Error { error, .. } => format!("Couldn't fetch usage. {error}"),
+      _ => "Couldn't fetch usage".to_string(),
+    };
+    return format!("{header}\n{status}");
+  };
+
+  let mut lines = vec![header];
+  for window in &data.windows {
+    let pct = match display_mode {
+      _ if window.is_over_limit() => "over limit".to_string(),
+      DisplayMode::Usage => format!("{:.0}% used", window.utilization),
+      DisplayMode::Remaining => format!("{:.0}% left", 100.0 - window.utilization),
+    };
+
+    let line = match &window.resets_at {
+      Some(resets_at) => format!("{}: {pct}, resets in {}", window.title, time::format_reset_time(resets_at)),
+      None => format!("{}: {pct}", window.title),
+    };
+
+    lines.push(line);
+  }
+
+  lines.push(format!("Updated {}", time::format_past_time(&data.fetched_at)));
+  if let Some((failed_at, error)) = failure {
+    lines.push(format!("Last fetch failed at {}. {error}", time::format_past_time(failed_at)));
+  }
+
+  return lines.join("\n");
+}
+
 /// Renders the "Extra Usage" section.
 ///
 /// When there's no free overage grant, shows a single row:
@@ -103,7 +396,20 @@ pub fn populate_menu(
 ///   - `out_of_pocket = max(usage − grant, 0)` is what the user has actually been billed
 ///   - `effective_cap = max(max_paid − grant, 0)` is the paid cap with the grant subtracted,
 ///     so it represents the user's true out-of-pocket budget
-fn extra_usage_section(menu: &NSMenu, mtm: MainThreadMarker, api_usage: &ApiUsage) {
+///
+/// Below that, a `Projected` row shows the estimated end-of-month bill, colored orange when it
+/// exceeds the configured budget (or the effective cap if no budget is set).
+///
+/// All amounts are converted to the display currency if one is configured; budgets stay in USD.
+fn extra_usage_section(
+  menu: &NSMenu,
+  mtm: MainThreadMarker,
+  api_usage: &ApiUsage,
+  forecast: Option<&SpendForecast>,
+  budget: Option<f64>,
+  exchange_rate: Option<&ExchangeRate>,
+  color_scale: ColorScale,
+) {
   menu.addItem(&NSMenuItem::separatorItem(mtm));
 
   let header_view = components::label_row(mtm, "Extra Usage", true);
@@ -111,40 +417,157 @@ fn extra_usage_section(menu: &NSMenu, mtm: MainThreadMarker, api_usage: &ApiUsag
   header_item.setView(Some(&header_view));
   menu.addItem(&header_item);
 
+  let money = |usd: f64| format_usd(usd, exchange_rate);
+
   let max_paid_text = |amount: f64| -> String {
     if !api_usage.is_enabled {
-      return "$0.00".to_string();
+      return money(0.0);
     }
     if api_usage.max_paid_usd.is_none() {
       return "unlimited".to_string();
     }
-    return format!("${:.2}", amount);
+    return money(amount);
   };
 
   match api_usage.free_credits_usd {
     None => {
       // No grant — single "Spent" row, same shape as before.
       let cap = api_usage.max_paid_usd.unwrap_or(0.0);
-      let value = format!("${:.2} / {}", api_usage.usage_usd, max_paid_text(cap));
-      add_kv_row(menu, mtm, "Spent", &value);
+      let value = format!("{} / {}", money(api_usage.usage_usd), max_paid_text(cap));
+      add_kv_row(menu, mtm, "Spent", &value, None);
     }
     Some(free) => {
       // Free row: how much of the grant has been consumed.
       let consumed = api_usage.usage_usd.min(free);
-      let free_value = format!("${:.2} / ${:.2}", consumed, free);
-      add_kv_row(menu, mtm, "Free", &free_value);
+      let free_value = format!("{} / {}", money(consumed), money(free));
+      add_kv_row(menu, mtm, "Free", &free_value, None);
 
       // Paid row: out-of-pocket against the cap minus the grant (the user's true budget).
       let out_of_pocket = (api_usage.usage_usd - free).max(0.0);
       let effective_cap = api_usage.max_paid_usd.map(|cap| (cap - free).max(0.0)).unwrap_or(0.0);
-      let paid_value = format!("${:.2} / {}", out_of_pocket, max_paid_text(effective_cap));
-      add_kv_row(menu, mtm, "Paid", &paid_value);
+      let paid_value = format!("{} / {}", money(out_of_pocket), max_paid_text(effective_cap));
+      add_kv_row(menu, mtm, "Paid", &paid_value, None);
     }
   }
+
+  // Projected bill: only what's paid out of pocket, so the free grant is subtracted.
+  if let Some(forecast) = forecast
+    && api_usage.is_enabled
+  {
+    let free = api_usage.free_credits_usd.unwrap_or(0.0);
+    let bill = |usd: f64| (usd - free).max(0.0);
+
+    let value = match forecast.trend_usd {
+      Some(trend) => format!("{} · trend {}", money(bill(forecast.linear_usd)), money(bill(trend))),
+      None => money(bill(forecast.linear_usd)),
+    };
+
+    let budget = budget.or(api_usage.max_paid_usd.map(|cap| (cap - free).max(0.0)));
+    let over_budget = budget.is_some_and(|b| bill(forecast.worst_usd()) > b);
+    let color = if over_budget { Some(color_scale.over_color()) } else { None };
+
+    add_kv_row(menu, mtm, "Projected", &value, color.as_deref());
+  }
+}
+
+/// Read-only usage of the secondary provider: its windows, or why they couldn't be fetched.
+fn add_secondary_section(menu: &NSMenu, mtm: MainThreadMarker, secondary: &SecondaryFetch, config: &Config) {
+  menu.addItem(&NSMenuItem::separatorItem(mtm));
+  add_group_header(menu, mtm, &secondary.kind.to_string());
+
+  let Some(data) = &secondary.data
+  else {
+    let text = match &secondary.error {
+      Some(error) => error.to_string(),
+      None => "No data".to_string(),
+    };
+    add_notice_row(menu, mtm, &text, &NSColor::systemOrangeColor());
+    return;
+  };
+
+  for window in &data.windows {
+    menu.addItem(&components::bucket_row(mtm, &components::BucketRowParams {
+      label: &window.title,
+      utilization: window.utilization,
+      resets_at: window.resets_at.as_ref(),
+      period_seconds: window.period_seconds,
+      show_period_percentage: config.show_period_percentage,
+      show_pacing_warning: config.show_pacing_warning,
+      reset_time_format: config.reset_time_format,
+      display_mode: config.display_mode,
+      color_scale: config.color_scale,
+      tier: None,
+      amount: window.amount.as_ref(),
+    }));
+  }
+}
+
+fn add_group_header(menu: &NSMenu, mtm: MainThreadMarker, title: &str) {
+  let view = components::label_row(mtm, title, true);
+  let item = NSMenuItem::new(mtm);
+  item.setView(Some(&view));
+  menu.addItem(&item);
+}
+
+fn add_notice_row(menu: &NSMenu, mtm: MainThreadMarker, text: &str, color: &NSColor) {
+  let view = components::notice_row(mtm, text, color);
+  let item = NSMenuItem::new(mtm);
+  item.setView(Some(&view));
+  menu.addItem(&item);
+}
+
+/// Shows why the last fetch failed, followed by an item to fetch again right away.
+fn add_error_rows(menu: &NSMenu, mtm: MainThreadMarker, app: &AppDelegate, title: &str, error: &ProviderError) {
+  let view = components::error_row(mtm, title, &error.to_string());
+  let item = NSMenuItem::new(mtm);
+  item.setView(Some(&view));
+  menu.addItem(&item);
+
+  let retry = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str("Retry"),
+      Some(sel!(onRefresh:)),
+      &NSString::from_str(""),
+    )
+  };
+  unsafe { retry.setTarget(Some(app)) };
+  menu.addItem(&retry);
 }
 
-fn add_kv_row(menu: &NSMenu, mtm: MainThreadMarker, key: &str, value: &str) {
-  let view = components::key_value_row(mtm, key, value);
+fn add_kv_row(menu: &NSMenu, mtm: MainThreadMarker, key: &str, value: &str, value_color: Option<&NSColor>) {
+  let view = components::key_value_row(mtm, key, value, value_color);
+  let item = NSMenuItem::new(mtm);
+  item.setView(Some(&view));
+  menu.addItem(&item);
+}
+
+/// Adds the window's utilization over the last day under its bar, in the current color of the
+/// bar. Skipped until the history has at least two snapshots of it.
+fn add_sparkline_row(menu: &NSMenu, mtm: MainThreadMarker, app: &AppDelegate, window: &UsageWindow, config: &Config) {
+  let kind = app.ivars().provider().kind();
+  let series = app.ivars().state().usage_series(kind, &window.title);
+  if series.len() < 2 {
+    return;
+  }
+
+  let now = Timestamp::now();
+  let span = history::RECENT_SPAN.as_secs_f64();
+  let points: Vec<(f64, f64)> = series
+    .iter()
+    .map(|(at, utilization)| {
+      let x = 1.0 - now.duration_since(*at).as_secs_f64() / span;
+      let y = match config.display_mode {
+        DisplayMode::Usage => *utilization,
+        DisplayMode::Remaining => 100.0 - utilization,
+      };
+
+      return (x.clamp(0.0, 1.0), y);
+    })
+    .collect();
+
+  let color = config.color_scale.utilization_color(window.utilization / 100.0);
+  let view = components::sparkline_row(mtm, &points, &color);
   let item = NSMenuItem::new(mtm);
   item.setView(Some(&view));
   menu.addItem(&item);
@@ -152,6 +575,13 @@ fn add_kv_row(menu: &NSMenu, mtm: MainThreadMarker, key: &str, value: &str) {
 
 const UPDATE_ITEM_TAG: isize = 9001;
 const PROVIDER_ITEM_TAG: isize = 9002;
+const DISPLAY_MODE_ITEM_TAG: isize = 9003;
+
+/// Refetch intervals offered in the "Refresh Every" submenu, in seconds.
+const REFETCH_INTERVALS: &[(u32, &str)] = &[(30, "30s"), (60, "1m"), (300, "5m"), (900, "15m")];
+
+/// How many upcoming full hours the "Plan Heavy Session" submenu offers.
+const PLAN_SESSION_HOURS: i64 = 8;
 
 fn update_item(mtm: MainThreadMarker, app: &AppDelegate, state: &UpdateState) -> Retained<NSMenuItem> {
   let (title, action, enabled) = match state {
@@ -202,20 +632,126 @@ fn refresh_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem
       mtm.alloc::<NSMenuItem>(),
       &NSString::from_str("Refresh"),
       Some(sel!(onRefresh:)),
-      &NSString::from_str("r"),
+      &NSString::from_str(&app.ivars().config().shortcuts.refresh),
     )
   };
   unsafe { item.setTarget(Some(app)) };
   return item;
 }
 
+fn display_mode_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
+  let config = app.ivars().config();
+  let item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str("Show Remaining"),
+      Some(sel!(onToggleDisplayMode:)),
+      &NSString::from_str(&config.shortcuts.toggle_display_mode),
+    )
+  };
+  unsafe { item.setTarget(Some(app)) };
+
+  let state = if config.display_mode == DisplayMode::Remaining {
+    NSControlStateValueOn
+  }
+  else {
+    NSControlStateValueOff
+  };
+  item.setState(state);
+  item.setTag(DISPLAY_MODE_ITEM_TAG);
+
+  return item;
+}
+
+/// Replaces the display mode menu item in-place without rebuilding the entire menu.
+pub fn update_display_mode_item(menu: &NSMenu, mtm: MainThreadMarker, app: &AppDelegate) {
+  if let Some(old_item) = menu.itemWithTag(DISPLAY_MODE_ITEM_TAG) {
+    let index = menu.indexOfItem(&old_item);
+    menu.removeItem(&old_item);
+    let new_item = display_mode_item(mtm, app);
+    menu.insertItem_atIndex(&new_item, index);
+  }
+}
+
 fn open_config_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
   let item = unsafe {
     NSMenuItem::initWithTitle_action_keyEquivalent(
       mtm.alloc::<NSMenuItem>(),
       &NSString::from_str("Open Config…"),
       Some(sel!(onOpenConfig:)),
-      &NSString::from_str(","),
+      &NSString::from_str(&app.ivars().config().shortcuts.open_config),
+    )
+  };
+  unsafe { item.setTarget(Some(app)) };
+  return item;
+}
+
+fn save_snapshot_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
+  let item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str("Save Snapshot…"),
+      Some(sel!(onSaveSnapshot:)),
+      &NSString::new(),
+    )
+  };
+  unsafe { item.setTarget(Some(app)) };
+  return item;
+}
+
+fn test_notification_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
+  let item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str("Send Test Notification"),
+      Some(sel!(onSendTestNotification:)),
+      &NSString::new(),
+    )
+  };
+  unsafe { item.setTarget(Some(app)) };
+  return item;
+}
+
+fn enter_token_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
+  let item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str("Enter Token…"),
+      Some(sel!(onEnterToken:)),
+      &NSString::new(),
+    )
+  };
+  unsafe { item.setTarget(Some(app)) };
+  return item;
+}
+
+fn provider_action_item(mtm: MainThreadMarker, app: &AppDelegate, index: usize, label: &str) -> Retained<NSMenuItem> {
+  let item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str(label),
+      Some(sel!(onProviderAction:)),
+      &NSString::new(),
+    )
+  };
+
+  unsafe { item.setTarget(Some(app)) };
+  item.setTag(index as isize);
+
+  return item;
+}
+
+/// Hint about a limit that keeps being hit, opening the provider's plans page if it has one.
+fn plan_hint_item(mtm: MainThreadMarker, app: &AppDelegate, text: &str, has_plans: bool) -> Retained<NSMenuItem> {
+  let title = if has_plans { format!("{text}: Compare Plans…") } else { text.to_string() };
+  let action = if has_plans { Some(sel!(onOpenPlans:)) } else { None };
+
+  let item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str(&title),
+      action,
+      &NSString::new(),
     )
   };
   unsafe { item.setTarget(Some(app)) };
@@ -235,6 +771,53 @@ fn open_logs_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuIt
   return item;
 }
 
+/// Takes the place of "Open Logs…" while Option is held.
+fn token_diagnostics_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
+  let item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str("Copy Token Diagnostics"),
+      Some(sel!(onCopyTokenDiagnostics:)),
+      &NSString::from_str("l"),
+    )
+  };
+  unsafe { item.setTarget(Some(app)) };
+  item.setKeyEquivalentModifierMask(NSEventModifierFlags::Command | NSEventModifierFlags::Option);
+  item.setAlternate(true);
+  return item;
+}
+
+fn interval_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
+  let item = NSMenuItem::new(mtm);
+  item.setTitle(&NSString::from_str("Refresh Every"));
+
+  let current = app.ivars().config().refetch_interval;
+
+  let submenu = NSMenu::new(mtm);
+  for &(seconds, title) in REFETCH_INTERVALS {
+    let sub_item = unsafe {
+      NSMenuItem::initWithTitle_action_keyEquivalent(
+        mtm.alloc::<NSMenuItem>(),
+        &NSString::from_str(title),
+        Some(sel!(onChangeInterval:)),
+        &NSString::new(),
+      )
+    };
+
+    unsafe { sub_item.setTarget(Some(app)) };
+    sub_item.setTag(seconds as isize);
+
+    let state = if seconds == current { NSControlStateValueOn } else { NSControlStateValueOff };
+    sub_item.setState(state);
+
+    submenu.addItem(&sub_item);
+  }
+
+  item.setSubmenu(Some(&submenu));
+
+  return item;
+}
+
 fn provider_item(mtm: MainThreadMarker, app: &AppDelegate, current: ProviderKind) -> Retained<NSMenuItem> {
   let item = NSMenuItem::new(mtm);
   item.setTitle(&NSString::from_str("Change Provider"));
@@ -275,6 +858,181 @@ pub fn update_provider_item(menu: &NSMenu, mtm: MainThreadMarker, app: &AppDeleg
   }
 }
 
+/// Read-only submenu with fetch counters and latency for the current provider.
+fn provider_status_item(
+  mtm: MainThreadMarker,
+  app: &AppDelegate,
+  current: ProviderKind,
+  data: &UsageData,
+) -> Retained<NSMenuItem> {
+  let mut lines = vec![
+    format!("Source: {}", data.source),
+    format!("Updated: {}", time::format_past_time(&data.fetched_at)),
+  ];
+
+  match app.ivars().fetch_metrics().get(current) {
+    Some(metrics) => {
+      lines.extend([
+        format!("Fetches: {} ({} failed)", metrics.fetches, metrics.failures),
+        format!("Last latency: {} ms", metrics.last_latency.as_millis()),
+        format!("Average latency: {} ms", metrics.average_latency.as_millis()),
+      ])
+    }
+    None => lines.push("No fetches yet".to_string()),
+  }
+
+  return info_submenu_item(mtm, "Provider Status", &lines);
+}
+
+/// Read-only submenu with teammates' last reported 5h utilization.
+fn team_item(mtm: MainThreadMarker, team: &[TeamMember]) -> Retained<NSMenuItem> {
+  let lines = if team.is_empty() {
+    vec!["No teammates reporting".to_string()]
+  }
+  else {
+    team
+      .iter()
+      .map(|m| {
+        match &m.resets_at {
+          Some(resets_at) => {
+            format!("{} — 5h {:.0}%, resets in {}", m.member, m.utilization, time::format_reset_time(resets_at))
+          }
+          None => format!("{} — 5h {:.0}%", m.member, m.utilization),
+        }
+      })
+      .collect()
+  };
+
+  return info_submenu_item(mtm, "Team", &lines);
+}
+
+/// Read-only submenu listing usage windows by reset time, soonest first.
+fn upcoming_resets_item(mtm: MainThreadMarker, data: &UsageData) -> Retained<NSMenuItem> {
+  let mut windows = data.windows.iter().filter_map(|w| Some((w, w.resets_at?))).collect::<Vec<_>>();
+  windows.sort_by_key(|(_, resets_at)| *resets_at);
+
+  let lines = if windows.is_empty() {
+    vec!["No scheduled resets".to_string()]
+  }
+  else {
+    windows
+      .iter()
+      .map(|(w, resets_at)| {
+        let at = time::format_until_time(resets_at);
+        format!("{} — {} (in {})", w.title, at, time::format_reset_time(resets_at))
+      })
+      .collect()
+  };
+
+  return info_submenu_item(mtm, "Upcoming Resets", &lines);
+}
+
+/// Submenu to plan a heavy session at one of the next full hours, checked against the burn rate.
+fn plan_session_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
+  let item = NSMenuItem::new(mtm);
+  item.setTitle(&NSString::from_str("Plan Heavy Session"));
+
+  let planned = app.ivars().state().planned_session();
+
+  let now = Zoned::now();
+  let hour = now.with().minute(0).second(0).subsec_nanosecond(0).build().unwrap_or(now);
+  let times =
+    (1 ..= PLAN_SESSION_HOURS).filter_map(|i| hour.timestamp().checked_add(SignedDuration::from_hours(i)).ok());
+
+  let mut options = vec![("None".to_string(), None)];
+  options.extend(times.map(|at| (time::format_until_time(&at), Some(at))));
+
+  let submenu = NSMenu::new(mtm);
+  for (title, at) in options {
+    let sub_item = unsafe {
+      NSMenuItem::initWithTitle_action_keyEquivalent(
+        mtm.alloc::<NSMenuItem>(),
+        &NSString::from_str(&title),
+        Some(sel!(onPlanSession:)),
+        &NSString::new(),
+      )
+    };
+
+    unsafe { sub_item.setTarget(Some(app)) };
+    sub_item.setTag(at.map_or(0, |at| at.as_second() as isize));
+
+    let state = if at == planned { NSControlStateValueOn } else { NSControlStateValueOff };
+    sub_item.setState(state);
+
+    submenu.addItem(&sub_item);
+  }
+
+  item.setSubmenu(Some(&submenu));
+
+  return item;
+}
+
+/// Submenu item with a list of disabled, informational lines.
+/// Submenu with where the provider's credentials come from, when they expire and the last auth
+/// error, plus an action to load them again. `None` if the provider doesn't report them.
+fn authentication_item(mtm: MainThreadMarker, app: &AppDelegate) -> Option<Retained<NSMenuItem>> {
+  let status = app.ivars().provider().auth_status()?;
+
+  let expiry = match status.expires_at {
+    Some(at) if at <= Timestamp::now() => format!("Expired at {}", time::format_past_time(&at)),
+    Some(at) => format!("Expires in {}", time::format_reset_time(&at)),
+    None => "Expiry unknown".to_string(),
+  };
+  let last_error = match &status.last_error {
+    Some((at, message)) => format!("Last error at {}: {}", time::format_past_time(at), message),
+    None => "No errors".to_string(),
+  };
+
+  let item = info_submenu_item(mtm, "Authentication", &[format!("Source: {}", status.source), expiry, last_error]);
+
+  let reauth_item = unsafe {
+    NSMenuItem::initWithTitle_action_keyEquivalent(
+      mtm.alloc::<NSMenuItem>(),
+      &NSString::from_str("Re-authenticate"),
+      Some(sel!(onReauthenticate:)),
+      &NSString::new(),
+    )
+  };
+  unsafe { reauth_item.setTarget(Some(app)) };
+
+  if let Some(submenu) = item.submenu() {
+    submenu.addItem(&NSMenuItem::separatorItem(mtm));
+    submenu.addItem(&reauth_item);
+
+    if app.ivars().provider().kind() == ProviderKind::ClaudeCode && claude_code::has_manual_token() {
+      let forget_item = unsafe {
+        NSMenuItem::initWithTitle_action_keyEquivalent(
+          mtm.alloc::<NSMenuItem>(),
+          &NSString::from_str("Forget Entered Token"),
+          Some(sel!(onForgetToken:)),
+          &NSString::new(),
+        )
+      };
+      unsafe { forget_item.setTarget(Some(app)) };
+      submenu.addItem(&forget_item);
+    }
+  }
+
+  return Some(item);
+}
+
+fn info_submenu_item(mtm: MainThreadMarker, title: &str, lines: &[String]) -> Retained<NSMenuItem> {
+  let item = NSMenuItem::new(mtm);
+  item.setTitle(&NSString::from_str(title));
+
+  let submenu = NSMenu::new(mtm);
+  for line in lines {
+    let sub_item = NSMenuItem::new(mtm);
+    sub_item.setTitle(&NSString::from_str(line));
+    sub_item.setEnabled(false);
+    submenu.addItem(&sub_item);
+  }
+
+  item.setSubmenu(Some(&submenu));
+
+  return item;
+}
+
 fn about_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
   let item = unsafe {
     NSMenuItem::initWithTitle_action_keyEquivalent(
@@ -294,7 +1052,7 @@ fn quit_item(mtm: MainThreadMarker, app: &AppDelegate) -> Retained<NSMenuItem> {
       mtm.alloc::<NSMenuItem>(),
       &NSString::from_str("Quit"),
       Some(sel!(onQuit:)),
-      &NSString::from_str("q"),
+      &NSString::from_str(&app.ivars().config().shortcuts.quit),
     )
   };
   unsafe { item.setTarget(Some(app)) };
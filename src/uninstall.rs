@@ -0,0 +1,74 @@
+use std::io::Write as _;
+
+use color_eyre::eyre::{Result, bail};
+
+use crate::{
+  CONFIG_PATH,
+  history::HISTORY_PATH,
+  ipc, launch_agent,
+  providers::claude_code,
+  utils::{cache::CACHE_DIR, log::LOG_DIR},
+};
+
+/// Removes everything liment wrote: the launch agent, logs, cache (including the persisted history),
+/// a token entered in liment, and the config unless `keep_config` is set. Asks first unless `yes` is set.
+pub fn run(keep_config: bool, yes: bool) -> Result<()> {
+  // A running app would write its history and socket back on quit.
+  if ipc::is_running() {
+    bail!("liment is running, quit it first");
+  }
+
+  let mut paths = vec![
+    launch_agent::plist_path()?,
+    LOG_DIR.clone(),
+    HISTORY_PATH.clone(),
+    CACHE_DIR.clone(),
+  ];
+  if !keep_config && let Some(config_dir) = CONFIG_PATH.parent() {
+    paths.push(config_dir.to_owned());
+  }
+  paths.retain(|path| fs_err::exists(path).unwrap_or(false));
+
+  println!("This removes:");
+  for path in &paths {
+    println!("  {path}");
+  }
+  println!("  the token entered in liment, from the keychain (if any)");
+
+  if !yes && !confirm("Continue? [y/N] ")? {
+    println!("Nothing was removed");
+
+    return Ok(());
+  }
+
+  for path in &paths {
+    if fs_err::metadata(path)?.is_dir() {
+      fs_err::remove_dir_all(path)?;
+    }
+    else {
+      fs_err::remove_file(path)?;
+    }
+
+    println!("Removed {path}");
+  }
+
+  if claude_code::remove_manual_token()? {
+    println!("Removed the token from the keychain");
+  }
+
+  if keep_config {
+    println!("Kept {}", *CONFIG_PATH);
+  }
+
+  return Ok(());
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+  print!("{prompt}");
+  std::io::stdout().flush()?;
+
+  let mut answer = String::new();
+  std::io::stdin().read_line(&mut answer)?;
+
+  return Ok(matches!(answer.trim(), "y" | "Y" | "yes"));
+}
@@ -8,9 +8,9 @@ use objc2_app_kit::NSApplication;
 use semver::Version;
 use serde::Deserialize;
 
-use crate::constants::LIMENT_OVERRIDE_VERSION;
+use crate::{constants::LIMENT_OVERRIDE_VERSION, utils::http};
 
-const GITHUB_RELEASES_URL: &str = "https://api.github.com/repos/polina4096/liment/releases/latest";
+pub const GITHUB_RELEASES_URL: &str = "https://api.github.com/repos/polina4096/liment/releases/latest";
 const ASSET_NAME: &str = "liment.app.zip";
 
 #[derive(Debug, Clone)]
@@ -100,12 +100,11 @@ struct GitHubAsset {
 fn fetch_latest_release() -> color_eyre::eyre::Result<VersionInfo> {
   log::debug!("Checking for updates...");
 
-  let mut response = ureq::get(GITHUB_RELEASES_URL)
-    .header("User-Agent", "liment-updater")
-    .call()
-    .context("Failed to fetch latest release")?;
+  let mut response =
+    http::with_retries(|| http::call(ureq::get(GITHUB_RELEASES_URL).header("User-Agent", "liment-updater")))
+      .context("Failed to fetch latest release")?;
 
-  let body = response.body_mut().read_to_string().context("Failed to read response body")?;
+  let body = http::read_body(&mut response).context("Failed to read response body")?;
   let release: GitHubRelease = serde_json::from_str(&body).context("Failed to parse release JSON")?;
 
   let tag = release.tag_name.strip_prefix('v').unwrap_or(&release.tag_name);
@@ -137,9 +136,7 @@ fn download_and_extract(url: &str) -> color_eyre::eyre::Result<Utf8PathBuf> {
   log::info!("Downloading update from {url}");
 
   // Download the update zip.
-  let mut response = ureq::get(url) //
-    .header("User-Agent", "liment-updater")
-    .call()
+  let mut response = http::with_retries(|| http::call(ureq::get(url).header("User-Agent", "liment-updater")))
     .context("Failed to download update")?;
 
   let body = response.body_mut().read_to_vec().context("Failed to read update body")?;
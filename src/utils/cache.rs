@@ -0,0 +1,40 @@
+use std::sync::LazyLock;
+
+use camino::Utf8PathBuf;
+use serde::{Serialize, de::DeserializeOwned};
+
+/// Directory for state that should survive restarts but can be discarded at any time.
+pub static CACHE_DIR: LazyLock<Utf8PathBuf> = LazyLock::new(|| {
+  let cache_dir = etcetera::base_strategy::Xdg::new()
+    .ok()
+    .and_then(|s| Utf8PathBuf::try_from(etcetera::BaseStrategy::cache_dir(&s)).ok())
+    .unwrap_or_else(|| Utf8PathBuf::from("~/.cache"));
+
+  return cache_dir.join("liment");
+});
+
+/// Reads a cached JSON value. Returns `None` if it's missing or unreadable.
+pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+  let path = CACHE_DIR.join(name);
+  if !fs_err::exists(&path).unwrap_or(false) {
+    return None;
+  }
+
+  let json = fs_err::read_to_string(&path).inspect_err(|e| log::warn!("Failed to read cache file: {}", e)).ok()?;
+
+  return serde_json::from_str(&json)
+    .inspect_err(|e| log::warn!("Failed to parse cache file {}: {}", path, e))
+    .ok();
+}
+
+/// Writes a value to the cache as JSON. Failures are logged and otherwise ignored.
+pub fn store<T: Serialize>(name: &str, value: &T) {
+  let result = serde_json::to_string(value).map_err(std::io::Error::from).and_then(|json| {
+    fs_err::create_dir_all(&*CACHE_DIR)?;
+    return fs_err::write(CACHE_DIR.join(name), json);
+  });
+
+  if let Err(e) = result {
+    log::warn!("Failed to write cache file {}: {}", name, e);
+  }
+}
@@ -0,0 +1,197 @@
+use std::{
+  collections::HashMap,
+  fmt::Write as _,
+  io::Write as _,
+  sync::{
+    Mutex, OnceLock,
+    atomic::{AtomicU32, Ordering},
+  },
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use camino::Utf8PathBuf;
+use color_eyre::eyre::{Context as _, Result};
+use jiff::{Timestamp, Zoned, fmt::strtime};
+use ureq::{
+  Body, RequestBuilder,
+  http::{HeaderMap, Response},
+  typestate::{WithBody, WithoutBody},
+};
+
+use crate::utils::log::LOG_DIR;
+
+/// Upper bound for API response bodies. Usage and profile responses are a few KB at most,
+/// anything bigger means a misbehaving endpoint or proxy.
+pub const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+
+/// Headers whose values never end up in the trace file.
+const REDACTED_HEADERS: &[&str] = &[
+  "authorization",
+  "proxy-authorization",
+  "cookie",
+  "set-cookie",
+  "x-api-key",
+];
+
+static TRACE_FILE: OnceLock<Mutex<fs_err::File>> = OnceLock::new();
+
+static FETCH_RETRIES: AtomicU32 = AtomicU32::new(2);
+
+/// Delay before the first retry, doubled for each further one and jittered by ±50%.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Sets how many times transient failures are retried. Mirrors `Config::fetch_retries`.
+pub fn set_fetch_retries(retries: u32) {
+  FETCH_RETRIES.store(retries, Ordering::Relaxed);
+}
+
+/// Enables HTTP tracing into a separate file in the logs directory and returns its path.
+pub fn enable_tracing() -> Result<Utf8PathBuf> {
+  fs_err::create_dir_all(&*LOG_DIR).context("Failed to create log directory")?;
+
+  let now = strtime::format("%Y_%m_%dT%H_%M_%S", &Zoned::now()).context("Failed to format time")?;
+  let path = LOG_DIR.join(format!("http_trace_{now}.log"));
+  let file = fs_err::File::create(&path).context("Failed to create HTTP trace file")?;
+
+  let _ = TRACE_FILE.set(Mutex::new(file));
+
+  return Ok(path);
+}
+
+/// Sends a request without a body, tracing it if enabled.
+pub fn call(request: RequestBuilder<WithoutBody>) -> Result<Response<Body>, ureq::Error> {
+  let description = TRACE_FILE.get().map(|_| describe_request(&request));
+
+  return traced(description, || request.call());
+}
+
+/// Sends a request with a string body, tracing it if enabled. The body itself is not traced.
+pub fn send(request: RequestBuilder<WithBody>, body: &str) -> Result<Response<Body>, ureq::Error> {
+  let description = TRACE_FILE.get().map(|_| describe_request(&request));
+
+  return traced(description, || request.send(body));
+}
+
+/// Runs a request, retrying transient failures (connection errors, timeouts, 5xx) with jittered
+/// exponential backoff. `attempt` builds and sends the request once, e.g. `|| http::call(...)`.
+/// Not for requests that mustn't be repeated, like exchanging a single-use token.
+pub fn with_retries(
+  mut attempt: impl FnMut() -> Result<Response<Body>, ureq::Error>,
+) -> Result<Response<Body>, ureq::Error> {
+  let retries = FETCH_RETRIES.load(Ordering::Relaxed);
+  let mut delay = RETRY_BASE_DELAY;
+
+  for retry in 1 ..= retries {
+    match attempt() {
+      Err(e) if is_transient(&e) => {
+        let wait = jittered(delay);
+        log::warn!("Request failed ({e}), retry {retry}/{retries} in {}ms", wait.as_millis());
+
+        std::thread::sleep(wait);
+        delay *= 2;
+      }
+      result => return result,
+    }
+  }
+
+  return attempt();
+}
+
+fn is_transient(error: &ureq::Error) -> bool {
+  return match error {
+    ureq::Error::StatusCode(status) => matches!(status, 500 | 502 | 503 | 504),
+    ureq::Error::Io(_) | ureq::Error::Timeout(_) | ureq::Error::ConnectionFailed | ureq::Error::HostNotFound => true,
+    _ => false,
+  };
+}
+
+/// Spreads the delay between 50% and 150%, so clients that failed together don't retry together.
+fn jittered(delay: Duration) -> Duration {
+  let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+  let factor = 0.5 + f64::from(nanos % 1000) / 1000.0;
+
+  return delay.mul_f64(factor);
+}
+
+/// Adds user-configured headers from provider settings to a request.
+pub fn with_headers<B>(mut request: RequestBuilder<B>, headers: &HashMap<String, String>) -> RequestBuilder<B> {
+  for (name, value) in headers {
+    request = request.header(name.as_str(), value.as_str());
+  }
+
+  return request;
+}
+
+/// Reads a response body as a string, failing if it exceeds `MAX_RESPONSE_SIZE`.
+pub fn read_body(response: &mut Response<Body>) -> Result<String, ureq::Error> {
+  return response.body_mut().with_config().limit(MAX_RESPONSE_SIZE).read_to_string();
+}
+
+/// Records the full body of a response that failed to parse.
+pub fn trace_parse_failure(url: &str, body: &str, error: &dyn std::fmt::Display) {
+  write_trace(&format!("[{}] Failed to parse response from {url}: {error}\n{body}\n", Timestamp::now()));
+}
+
+fn traced(
+  description: Option<String>,
+  send: impl FnOnce() -> Result<Response<Body>, ureq::Error>,
+) -> Result<Response<Body>, ureq::Error> {
+  let start = Instant::now();
+  let result = send();
+
+  let Some(mut entry) = description
+  else {
+    return result;
+  };
+
+  let elapsed = start.elapsed().as_millis();
+  match &result {
+    Ok(response) => {
+      let _ = writeln!(entry, "< {} ({elapsed}ms)", response.status());
+      write_headers(&mut entry, "<", response.headers());
+    }
+
+    Err(e) => {
+      let _ = writeln!(entry, "< error: {e} ({elapsed}ms)");
+    }
+  }
+
+  write_trace(&entry);
+
+  return result;
+}
+
+fn describe_request<B>(request: &RequestBuilder<B>) -> String {
+  let method = request.method_ref().map(|m| m.as_str()).unwrap_or("?");
+  let uri = request.uri_ref().map(|u| u.to_string()).unwrap_or_default();
+
+  let mut out = format!("[{}] {method} {uri}\n", Timestamp::now());
+  if let Some(headers) = request.headers_ref() {
+    write_headers(&mut out, ">", headers);
+  }
+
+  return out;
+}
+
+fn write_headers(out: &mut String, prefix: &str, headers: &HeaderMap) {
+  for (name, value) in headers {
+    // Custom headers often carry credentials (e.g. access service tokens), hide anything that looks like one.
+    let name_str = name.as_str();
+    let is_secret = REDACTED_HEADERS.contains(&name_str) || name_str.contains("secret") || name_str.contains("token");
+
+    let value = if is_secret { "<redacted>" } else { value.to_str().unwrap_or("<binary>") };
+
+    let _ = writeln!(out, "{prefix} {name}: {value}");
+  }
+}
+
+fn write_trace(entry: &str) {
+  let Some(file) = TRACE_FILE.get()
+  else {
+    return;
+  };
+
+  if let Err(e) = writeln!(file.lock().unwrap(), "{entry}") {
+    log::warn!("Failed to write HTTP trace: {e}");
+  }
+}
@@ -0,0 +1,37 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
+
+static STRICT_PARSING: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether malformed response fields fail the whole response. Mirrors `Config::strict_parsing`.
+pub fn set_strict_parsing(strict: bool) {
+  STRICT_PARSING.store(strict, Ordering::Relaxed);
+}
+
+/// Deserializes an optional field, dropping it with a warning if it's malformed instead of
+/// failing the whole response. In strict mode the error is propagated as usual.
+///
+/// Usage: `#[serde(default, deserialize_with = "json::lenient")]`
+pub fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+  D: Deserializer<'de>,
+  T: DeserializeOwned,
+{
+  let Some(value) = Option::<serde_json::Value>::deserialize(deserializer)?
+  else {
+    return Ok(None);
+  };
+
+  return match serde_json::from_value(value) {
+    Ok(parsed) => Ok(Some(parsed)),
+
+    Err(e) if STRICT_PARSING.load(Ordering::Relaxed) => Err(serde::de::Error::custom(e)),
+
+    Err(e) => {
+      log::warn!("Ignoring malformed response field: {e}");
+
+      Ok(None)
+    }
+  };
+}
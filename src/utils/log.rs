@@ -19,8 +19,8 @@ pub static LOG_DIR: LazyLock<Utf8PathBuf> = LazyLock::new(|| {
   });
 });
 
-fn open_log_file() -> Result<Option<File>> {
-  if std::env::var(LIMENT_NO_DISK_LOGS).is_ok() {
+fn open_log_file(disk: bool) -> Result<Option<File>> {
+  if !disk || std::env::var(LIMENT_NO_DISK_LOGS).is_ok() {
     return Ok(None);
   }
 
@@ -36,7 +36,8 @@ fn open_log_file() -> Result<Option<File>> {
   return Ok(Some(file.into_parts().0));
 }
 
-pub fn init_logger() {
+/// Logs to stderr, and with `disk` to a new file in the logs directory.
+pub fn init_logger(disk: bool) {
   if std::env::var(LIMENT_NO_LOGS).is_ok() {
     return;
   }
@@ -52,7 +53,7 @@ pub fn init_logger() {
     .level(level)
     .chain(std::io::stderr())
     .pipe(|d| {
-      match open_log_file() {
+      match open_log_file(disk) {
         Ok(Some(file)) => d.chain(file),
         Ok(None) => d,
         Err(e) => {
@@ -1,5 +1,5 @@
-/// Creates a repeating `NSTimer`, adds it to the current run loop, and drops the reference.
-/// The run loop retains the timer, so it stays alive for the app's lifetime.
+/// Creates a repeating `NSTimer`, adds it to the current run loop, and returns it.
+/// The run loop retains the timer, so it stays alive until invalidated.
 ///
 /// Usage: `schedule_timer!(interval_secs, target, selector)`
 macro_rules! schedule_timer {
@@ -14,12 +14,22 @@ macro_rules! schedule_timer {
       objc2_foundation::NSRunLoop::currentRunLoop()
         .addTimer_forMode(&timer, objc2_foundation::NSDefaultRunLoopMode);
     }
+
+    timer
   }};
 }
 
-use objc2_app_kit::NSView;
+use objc2_app_kit::{NSPasteboard, NSPasteboardTypeString, NSView};
+use objc2_foundation::NSString;
 pub(crate) use schedule_timer;
 
+/// Replaces the clipboard contents with the text.
+pub fn copy_to_pasteboard(text: &str) {
+  let pasteboard = NSPasteboard::generalPasteboard();
+  pasteboard.clearContents();
+  pasteboard.setString_forType(&NSString::from_str(text), unsafe { NSPasteboardTypeString });
+}
+
 pub trait NSViewExt {
   #[expect(non_snake_case)]
   fn noAutoresize(&self);
@@ -1,6 +1,10 @@
+pub mod cache;
 pub mod codesign;
+pub mod http;
+pub mod json;
 pub mod log;
 pub mod macos;
 pub mod notification;
+pub mod sandbox;
 pub mod time;
 pub mod toml;
@@ -2,7 +2,8 @@ use block2::RcBlock;
 use objc2::{rc::Retained, runtime::Bool};
 use objc2_foundation::{NSBundle, NSError, NSString};
 use objc2_user_notifications::{
-  UNAuthorizationOptions, UNMutableNotificationContent, UNNotificationRequest, UNUserNotificationCenter,
+  UNAuthorizationOptions, UNMutableNotificationContent, UNNotificationRequest, UNNotificationSound,
+  UNUserNotificationCenter,
 };
 
 /// Requests notification authorization. Call once on startup.
@@ -34,9 +35,15 @@ pub fn request_authorization() {
   );
 }
 
-/// Posts a macOS notification with the given title and body.
+/// Posts a silent macOS notification with the given title and body.
 /// Silently skips if the app is not running from a bundle (e.g. cargo run).
 pub fn send(title: &str, body: &str) {
+  send_with_sound(title, body, None);
+}
+
+/// Posts a macOS notification that plays `sound`: "default" for the system default, otherwise the
+/// name of a sound in the sound library (e.g. "Glass").
+pub fn send_with_sound(title: &str, body: &str, sound: Option<&str>) {
   if NSBundle::mainBundle().bundleIdentifier().is_none() {
     return;
   }
@@ -45,6 +52,12 @@ pub fn send(title: &str, body: &str) {
   content.setTitle(&NSString::from_str(title));
   content.setBody(&NSString::from_str(body));
 
+  match sound {
+    Some("default") => content.setSound(Some(&UNNotificationSound::defaultSound())),
+    Some(name) => content.setSound(Some(&UNNotificationSound::soundNamed(&NSString::from_str(name)))),
+    None => {}
+  }
+
   let id = NSString::from_str(&format!(
     "liment-{}",
     std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis()
@@ -0,0 +1,39 @@
+use camino::Utf8PathBuf;
+use color_eyre::eyre::{Context as _, Result};
+
+/// Whether the app runs inside the App Sandbox (e.g. a notarized build with the sandbox
+/// entitlement). Sandboxed builds can't sign or replace their own bundle or script other apps,
+/// so self-signing, the built-in updater and the Terminal config editor are turned off.
+pub fn is_sandboxed() -> bool {
+  return std::env::var_os("APP_SANDBOX_CONTAINER_ID").is_some();
+}
+
+/// The user's home directory. Inside the sandbox `HOME` points into the app's container
+/// (`~/Library/Containers/<id>/Data`), so the real one is taken from in front of it. Reading
+/// from it is limited to the paths the entitlements make exceptions for.
+pub fn home_dir() -> Result<Utf8PathBuf> {
+  let home = Utf8PathBuf::try_from(etcetera::home_dir()?).context("Home directory is not valid UTF-8")?;
+
+  if is_sandboxed()
+    && let Some((real_home, _)) = home.as_str().split_once("/Library/Containers/")
+  {
+    return Ok(Utf8PathBuf::from(real_home));
+  }
+
+  return Ok(home);
+}
+
+/// The config directory shared with other tools, `~/.config` unless `XDG_CONFIG_HOME` says
+/// otherwise. Inside the sandbox it's always the one in the real home directory.
+pub fn config_dir() -> Utf8PathBuf {
+  let config_dir = if is_sandboxed() {
+    home_dir().ok().map(|home| home.join(".config"))
+  }
+  else {
+    etcetera::base_strategy::Xdg::new()
+      .ok()
+      .and_then(|s| Utf8PathBuf::try_from(etcetera::BaseStrategy::config_dir(&s)).ok())
+  };
+
+  return config_dir.unwrap_or_else(|| Utf8PathBuf::from("~/.config"));
+}
@@ -1,4 +1,47 @@
-use jiff::Timestamp;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use jiff::{Timestamp, Zoned};
+use objc2_foundation::{NSDateFormatter, NSLocale, NSString};
+
+use crate::config::DateOrder;
+
+static MONTH_FIRST: AtomicBool = AtomicBool::new(false);
+
+/// Sets the order of day and month in formatted dates. Mirrors `Config::date_order`.
+pub fn set_date_order(order: DateOrder) {
+  let month_first = match order {
+    DateOrder::Locale => locale_month_first(),
+    DateOrder::DayMonth => false,
+    DateOrder::MonthDay => true,
+  };
+
+  MONTH_FIRST.store(month_first, Ordering::Relaxed);
+}
+
+/// Whether the system locale puts the month before the day, e.g. "M/d" in the US.
+fn locale_month_first() -> bool {
+  let locale = NSLocale::currentLocale();
+  let Some(format) =
+    NSDateFormatter::dateFormatFromTemplate_options_locale(&NSString::from_str("dM"), 0, Some(&locale))
+  else {
+    return false;
+  };
+
+  let format = format.to_string();
+  return match (format.find('M'), format.find('d')) {
+    (Some(month), Some(day)) => month < day,
+    _ => false,
+  };
+}
+
+/// Formats the day and month of a date as "DD.MM" or "MM/DD", depending on the date order.
+fn format_day_month(dt: &Zoned) -> String {
+  if MONTH_FIRST.load(Ordering::Relaxed) {
+    return format!("{:02}/{:02}", dt.month(), dt.day());
+  }
+
+  return format!("{:02}.{:02}", dt.day(), dt.month());
+}
 
 pub fn format_reset_time(resets_at: &Timestamp) -> String {
   let now = Timestamp::now();
@@ -25,10 +68,25 @@ pub fn format_reset_time(resets_at: &Timestamp) -> String {
 
 pub fn format_absolute_time(resets_at: &Timestamp) -> String {
   let dt = resets_at.to_zoned(jiff::tz::TimeZone::system());
-  return format!("{:02}.{:02}, {:02}:{:02}", dt.day(), dt.month(), dt.hour(), dt.minute());
+  return format!("{}, {:02}:{:02}", format_day_month(&dt), dt.hour(), dt.minute());
+}
+
+/// Formats a past timestamp as "HH:MM" if it's today, otherwise "DD.MM, HH:MM" (or "MM/DD, HH:MM").
+pub fn format_past_time(ts: &Timestamp) -> String {
+  let tz = jiff::tz::TimeZone::system();
+  let target = ts.to_zoned(tz.clone());
+  let now = Timestamp::now().to_zoned(tz);
+
+  let time = format!("{:02}:{:02}", target.hour(), target.minute());
+
+  if target.date() == now.date() {
+    return time;
+  }
+
+  return format!("{}, {}", format_day_month(&target), time);
 }
 
-/// Formats a future timestamp as "HH:MM", "tomorrow, HH:MM", or "DD.MM, HH:MM".
+/// Formats a future timestamp as "HH:MM", "tomorrow, HH:MM", or "DD.MM, HH:MM" (or "MM/DD, HH:MM").
 pub fn format_until_time(ts: &Timestamp) -> String {
   let tz = jiff::tz::TimeZone::system();
   let target = ts.to_zoned(tz.clone());
@@ -49,5 +107,5 @@ pub fn format_until_time(ts: &Timestamp) -> String {
     }
   }
 
-  return format!("{:02}.{:02}, {}", target.day(), target.month(), time);
+  return format!("{}, {}", format_day_month(&target), time);
 }
@@ -0,0 +1,49 @@
+use jiff::Timestamp;
+
+use crate::providers::{UsageData, UsageWindow};
+
+pub const WEEK_SECONDS: i64 = 7 * 86400;
+
+/// Progress of the weekly window against the user's personal utilization goal.
+pub struct GoalProgress {
+  /// Goal utilization for the whole window (0–100).
+  pub goal: f64,
+
+  /// Current utilization of the weekly window (0–100).
+  pub utilization: f64,
+
+  /// Share of the goal allotted to the time elapsed so far (0–100).
+  pub paced_goal: f64,
+
+  /// When the weekly window resets.
+  pub resets_at: Timestamp,
+}
+
+impl GoalProgress {
+  /// Computes progress for the first weekly window shown in the tray. Returns `None` if the
+  /// provider has no such window or its reset time is unknown.
+  pub fn compute(goal: f64, data: &UsageData) -> Option<Self> {
+    let window = weekly_window(data)?;
+
+    let resets_at = window.resets_at?;
+    let remaining = (resets_at.as_second() - Timestamp::now().as_second()).clamp(0, WEEK_SECONDS);
+    let elapsed = (WEEK_SECONDS - remaining) as f64 / WEEK_SECONDS as f64;
+
+    return Some(Self {
+      goal,
+      utilization: window.utilization,
+      paced_goal: goal * elapsed,
+      resets_at,
+    });
+  }
+
+  /// Whether utilization is ahead of an even pace towards the goal.
+  pub fn is_over_pace(&self) -> bool {
+    return self.utilization > self.paced_goal;
+  }
+}
+
+/// The first weekly window shown in the tray.
+pub fn weekly_window(data: &UsageData) -> Option<&UsageWindow> {
+  return data.windows.iter().find(|w| w.short_title.is_some() && w.period_seconds == Some(WEEK_SECONDS));
+}